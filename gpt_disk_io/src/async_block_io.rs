@@ -0,0 +1,295 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use core::fmt::{Debug, Display};
+use core::task::{Context, Poll};
+use gpt_disk_types::{BlockSize, Lba};
+
+/// Trait for asynchronously reading from and writing to a block
+/// device. This is the asynchronous version of [`BlockIo`], used by
+/// [`AsyncDisk`].
+///
+/// The interface is poll-based, so it can be implemented without
+/// allocation and used with any executor. If a method returns
+/// [`Poll::Pending`], the implementation must arrange for the waker in
+/// `cx` to be woken when progress can be made. The caller will then
+/// call the same method again with the same arguments until it returns
+/// [`Poll::Ready`]. Buffers are passed again on each call rather than
+/// being held by the implementation, so an implementation that
+/// transfers data in the background (for example with DMA) must use
+/// its own buffer and copy to or from the caller's buffer.
+///
+/// Synchronous [`BlockIo`] implementations can be used through
+/// [`AsyncBlockIoAdapter`]. Other asynchronous interfaces, such as an
+/// async runtime's file handles or a flash driver, can implement this
+/// trait directly.
+///
+/// [`AsyncDisk`]: crate::AsyncDisk
+pub trait AsyncBlockIo {
+    /// IO error type.
+    type Error: Debug + Display + Send + Sync + 'static;
+
+    /// Get the [`BlockSize`]. The return value is not allowed to
+    /// change.
+    fn block_size(&self) -> BlockSize;
+
+    /// Get the number of logical blocks in the disk. See
+    /// [`BlockIo::num_blocks`].
+    fn poll_num_blocks(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<u64, Self::Error>>;
+
+    /// Read contiguous blocks from the disk. The `dst` buffer size must
+    /// be a multiple of [`block_size`]. Implementations are permitted
+    /// to panic if this precondition is not met, e.g. by calling
+    /// [`BlockSize::assert_valid_block_buffer`].
+    ///
+    /// [`block_size`]: Self::block_size
+    fn poll_read_blocks(
+        &mut self,
+        cx: &mut Context<'_>,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Poll<Result<(), Self::Error>>;
+
+    /// Write contiguous blocks to the disk. The `src` buffer size must
+    /// be a multiple of [`block_size`]. Implementations are permitted
+    /// to panic if this precondition is not met, e.g. by calling
+    /// [`BlockSize::assert_valid_block_buffer`].
+    ///
+    /// Writes are not guaranteed to be complete until [`poll_flush`]
+    /// has returned [`Poll::Ready`].
+    ///
+    /// [`block_size`]: Self::block_size
+    /// [`poll_flush`]: Self::poll_flush
+    fn poll_write_blocks(
+        &mut self,
+        cx: &mut Context<'_>,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Poll<Result<(), Self::Error>>;
+
+    /// Flush any pending writes to the device.
+    fn poll_flush(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>>;
+
+    /// Read blocks from several locations on the disk. Each request is
+    /// a start LBA and a `dst` buffer, with the same requirements as
+    /// [`poll_read_blocks`].
+    ///
+    /// On success, returns the number of requests that were completed,
+    /// which must be at least one unless `requests` is empty. The
+    /// completed requests are at the start of `requests`. The caller
+    /// then calls this method again with the remaining requests, so
+    /// progress is not lost if a later request is pending.
+    ///
+    /// The default implementation completes only the first request,
+    /// with [`poll_read_blocks`]. Implementations for devices that can
+    /// queue several requests at once can override this method to
+    /// submit all of the requests together. Requests may complete in
+    /// any order.
+    ///
+    /// [`poll_read_blocks`]: Self::poll_read_blocks
+    fn poll_read_blocks_vectored(
+        &mut self,
+        cx: &mut Context<'_>,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Poll<Result<usize, Self::Error>> {
+        match requests.first_mut() {
+            Some((start_lba, dst)) => self
+                .poll_read_blocks(cx, *start_lba, dst)
+                .map(|r| r.map(|()| 1)),
+            None => Poll::Ready(Ok(0)),
+        }
+    }
+
+    /// Write blocks to several locations on the disk. Each request is
+    /// a start LBA and a `src` buffer, with the same requirements as
+    /// [`poll_write_blocks`].
+    ///
+    /// The return value and the default implementation are the same as
+    /// for [`poll_read_blocks_vectored`]. As with [`poll_write_blocks`],
+    /// writes are not guaranteed to be complete until [`poll_flush`]
+    /// has returned [`Poll::Ready`].
+    ///
+    /// [`poll_flush`]: Self::poll_flush
+    /// [`poll_read_blocks_vectored`]: Self::poll_read_blocks_vectored
+    /// [`poll_write_blocks`]: Self::poll_write_blocks
+    fn poll_write_blocks_vectored(
+        &mut self,
+        cx: &mut Context<'_>,
+        requests: &[(Lba, &[u8])],
+    ) -> Poll<Result<usize, Self::Error>> {
+        match requests.first() {
+            Some((start_lba, src)) => self
+                .poll_write_blocks(cx, *start_lba, src)
+                .map(|r| r.map(|()| 1)),
+            None => Poll::Ready(Ok(0)),
+        }
+    }
+}
+
+impl<T: AsyncBlockIo + ?Sized> AsyncBlockIo for &mut T {
+    type Error = T::Error;
+
+    fn block_size(&self) -> BlockSize {
+        (**self).block_size()
+    }
+
+    fn poll_num_blocks(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<u64, Self::Error>> {
+        (**self).poll_num_blocks(cx)
+    }
+
+    fn poll_read_blocks(
+        &mut self,
+        cx: &mut Context<'_>,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Poll<Result<(), Self::Error>> {
+        (**self).poll_read_blocks(cx, start_lba, dst)
+    }
+
+    fn poll_write_blocks(
+        &mut self,
+        cx: &mut Context<'_>,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Poll<Result<(), Self::Error>> {
+        (**self).poll_write_blocks(cx, start_lba, src)
+    }
+
+    fn poll_flush(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        (**self).poll_flush(cx)
+    }
+
+    fn poll_read_blocks_vectored(
+        &mut self,
+        cx: &mut Context<'_>,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Poll<Result<usize, Self::Error>> {
+        (**self).poll_read_blocks_vectored(cx, requests)
+    }
+
+    fn poll_write_blocks_vectored(
+        &mut self,
+        cx: &mut Context<'_>,
+        requests: &[(Lba, &[u8])],
+    ) -> Poll<Result<usize, Self::Error>> {
+        (**self).poll_write_blocks_vectored(cx, requests)
+    }
+}
+
+/// Adapter that implements [`AsyncBlockIo`] for a synchronous
+/// [`BlockIo`].
+///
+/// Every operation completes immediately, blocking the current task
+/// until the underlying [`BlockIo`] returns. This is suitable for
+/// in-memory storage, or for devices that are fast enough that
+/// blocking does not matter. Vectored requests are passed on to
+/// [`BlockIo::read_blocks_vectored`] and
+/// [`BlockIo::write_blocks_vectored`] together.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AsyncBlockIoAdapter<Io> {
+    io: Io,
+}
+
+impl<Io: BlockIo> AsyncBlockIoAdapter<Io> {
+    /// Create a new `AsyncBlockIoAdapter` wrapping `io`.
+    #[must_use]
+    pub fn new(io: Io) -> Self {
+        Self { io }
+    }
+
+    /// Get a reference to the underlying [`BlockIo`].
+    #[must_use]
+    pub fn inner(&self) -> &Io {
+        &self.io
+    }
+
+    /// Consume the `AsyncBlockIoAdapter` and return the underlying
+    /// [`BlockIo`].
+    #[must_use]
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+}
+
+impl<Io: BlockIo> AsyncBlockIo for AsyncBlockIoAdapter<Io> {
+    type Error = Io::Error;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn poll_num_blocks(
+        &mut self,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<u64, Self::Error>> {
+        Poll::Ready(self.io.num_blocks())
+    }
+
+    fn poll_read_blocks(
+        &mut self,
+        _cx: &mut Context<'_>,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(self.io.read_blocks(start_lba, dst))
+    }
+
+    fn poll_write_blocks(
+        &mut self,
+        _cx: &mut Context<'_>,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(self.io.write_blocks(start_lba, src))
+    }
+
+    fn poll_flush(
+        &mut self,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(self.io.flush())
+    }
+
+    fn poll_read_blocks_vectored(
+        &mut self,
+        _cx: &mut Context<'_>,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Poll<Result<usize, Self::Error>> {
+        Poll::Ready(
+            self.io
+                .read_blocks_vectored(requests)
+                .map(|()| requests.len()),
+        )
+    }
+
+    fn poll_write_blocks_vectored(
+        &mut self,
+        _cx: &mut Context<'_>,
+        requests: &[(Lba, &[u8])],
+    ) -> Poll<Result<usize, Self::Error>> {
+        Poll::Ready(
+            self.io
+                .write_blocks_vectored(requests)
+                .map(|()| requests.len()),
+        )
+    }
+}
@@ -0,0 +1,411 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::disk::{clip_block_buf, entry_array_error, fill_block};
+use crate::{AsyncBlockIo, DiskError, GptTable};
+use bytemuck::{bytes_of, from_bytes};
+use core::future::poll_fn;
+use core::mem;
+use gpt_disk_types::{
+    GptHeader, GptPartitionEntry, GptPartitionEntryArray,
+    GptPartitionEntryArrayLayout, Lba, MasterBootRecord,
+};
+
+/// Asynchronous iterator over entries in a partition entry array.
+/// Created by [`AsyncDisk::gpt_partition_entry_array_iter`].
+#[allow(clippy::module_name_repetitions)]
+pub struct AsyncGptPartitionEntryIter<'disk, 'buf, Io: AsyncBlockIo> {
+    disk: &'disk mut AsyncDisk<Io>,
+    block_buf: &'buf mut [u8],
+    layout: GptPartitionEntryArrayLayout,
+    next_index: u32,
+    next_lba: Lba,
+    byte_offset_within_lba: usize,
+    entry_size: usize,
+}
+
+impl<Io: AsyncBlockIo> AsyncGptPartitionEntryIter<'_, '_, Io> {
+    /// Get the next partition entry, reading the next block of the
+    /// partition entry array if needed. Returns `None` once all entries
+    /// have been read.
+    ///
+    /// If an error occurs, it is returned and iteration stops.
+    pub async fn next_entry(
+        &mut self,
+    ) -> Option<Result<GptPartitionEntry, DiskError<Io::Error>>> {
+        if self.next_index >= self.layout.num_entries {
+            return None;
+        }
+
+        if self.byte_offset_within_lba + self.entry_size > self.block_buf.len()
+        {
+            if let Err(err) =
+                self.disk.read_blocks(self.next_lba, self.block_buf).await
+            {
+                self.next_index = self.layout.num_entries;
+                return Some(Err(err));
+            }
+            self.next_lba = Lba(self.next_lba.to_u64() + 1);
+            self.byte_offset_within_lba = 0;
+        }
+
+        let entry_bytes = &self.block_buf[self.byte_offset_within_lba..]
+            [..mem::size_of::<GptPartitionEntry>()];
+        self.byte_offset_within_lba += self.entry_size;
+        self.next_index += 1;
+
+        Some(Ok(*from_bytes::<GptPartitionEntry>(entry_bytes)))
+    }
+}
+
+/// Asynchronously read and write GPT disk data.
+///
+/// This is the asynchronous version of [`Disk`], using an
+/// [`AsyncBlockIo`] instead of a [`BlockIo`]. It provides the same
+/// methods for reading and writing the MBR, GPT headers, and partition
+/// entry arrays, including [`read_gpt_headers`] and
+/// [`read_gpt_partition_entry_arrays`], as well as [`read_gpt_table`].
+/// A [`GptTable`] can be written back with [`GptTable::commit_async`],
+/// or with [`GptTable::commit_transactional_async`] if the GPT must
+/// stay consistent when the commit is interrupted. Higher-level
+/// operations such as verifying, repairing, or resizing the GPT are
+/// only available on [`Disk`].
+///
+/// As with [`Disk`], `block_buf` arguments are mutable byte buffers
+/// with a length of at least one block, so no memory allocation is
+/// needed.
+///
+/// Unlike [`Disk`], an `AsyncDisk` is not flushed when it is dropped,
+/// since that would require blocking. Call [`flush`] before dropping
+/// the disk.
+///
+/// [`BlockIo`]: crate::BlockIo
+/// [`Disk`]: crate::Disk
+/// [`flush`]: Self::flush
+/// [`read_gpt_headers`]: Self::read_gpt_headers
+/// [`read_gpt_partition_entry_arrays`]: Self::read_gpt_partition_entry_arrays
+/// [`read_gpt_table`]: Self::read_gpt_table
+#[allow(clippy::module_name_repetitions)]
+pub struct AsyncDisk<Io: AsyncBlockIo> {
+    pub(crate) io: Io,
+}
+
+impl<Io: AsyncBlockIo> AsyncDisk<Io> {
+    /// Create an `AsyncDisk`.
+    pub fn new(io: Io) -> Result<Self, DiskError<Io::Error>> {
+        Ok(Self { io })
+    }
+
+    /// Consume the `AsyncDisk` and return the underlying
+    /// [`AsyncBlockIo`].
+    #[must_use]
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+
+    pub(crate) async fn num_blocks(
+        &mut self,
+    ) -> Result<u64, DiskError<Io::Error>> {
+        Ok(poll_fn(|cx| self.io.poll_num_blocks(cx)).await?)
+    }
+
+    async fn last_lba(&mut self) -> Result<Lba, DiskError<Io::Error>> {
+        let num_blocks = self.num_blocks().await?;
+        Ok(Lba(num_blocks.checked_sub(1).ok_or(DiskError::Overflow)?))
+    }
+
+    async fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        Ok(poll_fn(|cx| self.io.poll_read_blocks(cx, start_lba, dst)).await?)
+    }
+
+    pub(crate) async fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        Ok(poll_fn(|cx| self.io.poll_write_blocks(cx, start_lba, src)).await?)
+    }
+
+    async fn read_blocks_vectored(
+        &mut self,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let mut num_done = 0;
+        while num_done < requests.len() {
+            let remaining = &mut requests[num_done..];
+            let n =
+                poll_fn(|cx| self.io.poll_read_blocks_vectored(cx, remaining))
+                    .await?;
+            assert!(n > 0, "no vectored read requests were completed");
+            num_done += n;
+        }
+        Ok(())
+    }
+
+    /// Read the primary GPT header from the second block. No validation
+    /// of the header is performed.
+    pub async fn read_primary_gpt_header(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        self.read_gpt_header(Lba(1), block_buf).await
+    }
+
+    /// Read the secondary GPT header from the last block. No validation
+    /// of the header is performed.
+    pub async fn read_secondary_gpt_header(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        let last_block = self.last_lba().await?;
+        self.read_gpt_header(last_block, block_buf).await
+    }
+
+    /// Read the primary GPT header from the second block and the
+    /// secondary GPT header from the last block, with a single call to
+    /// [`AsyncBlockIo::poll_read_blocks_vectored`]. See
+    /// [`Disk::read_gpt_headers`] for details.
+    ///
+    /// [`Disk::read_gpt_headers`]: crate::Disk::read_gpt_headers
+    pub async fn read_gpt_headers(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<(GptHeader, GptHeader), DiskError<Io::Error>> {
+        let block_size =
+            self.io.block_size().to_usize().ok_or(DiskError::Overflow)?;
+        let last_block = self.last_lba().await?;
+        let (primary_buf, secondary_buf) = block_buf
+            .get_mut(..block_size.checked_mul(2).ok_or(DiskError::Overflow)?)
+            .ok_or(DiskError::BufferTooSmall)?
+            .split_at_mut(block_size);
+
+        self.read_blocks_vectored(&mut [
+            (Lba(1), &mut *primary_buf),
+            (last_block, &mut *secondary_buf),
+        ])
+        .await?;
+        // OK to unwrap since the block size type guarantees a minimum
+        // size greater than GptHeader.
+        let parse_header = |buf: &[u8]| -> GptHeader {
+            *from_bytes(buf.get(..mem::size_of::<GptHeader>()).unwrap())
+        };
+        Ok((parse_header(primary_buf), parse_header(secondary_buf)))
+    }
+
+    /// Read a GPT header at the given [`Lba`]. No validation of the
+    /// header is performed.
+    pub async fn read_gpt_header(
+        &mut self,
+        lba: Lba,
+        mut block_buf: &mut [u8],
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        block_buf = clip_block_buf(self.io.block_size(), block_buf)?;
+        self.read_blocks(lba, block_buf).await?;
+        let bytes = block_buf
+            .get(..mem::size_of::<GptHeader>())
+            // OK to unwrap since the block size type guarantees a
+            // minimum size greater than GptHeader.
+            .unwrap();
+        Ok(*from_bytes(bytes))
+    }
+
+    /// Read the entire partition entry array. The `storage` buffer must
+    /// be at least [`layout.num_bytes_rounded_to_block`] in size.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    pub async fn read_gpt_partition_entry_array<'buf>(
+        &mut self,
+        layout: GptPartitionEntryArrayLayout,
+        storage: &'buf mut [u8],
+    ) -> Result<GptPartitionEntryArray<'buf>, DiskError<Io::Error>> {
+        let mut entry_array =
+            GptPartitionEntryArray::new(layout, self.io.block_size(), storage)
+                .map_err(entry_array_error)?;
+        self.read_blocks(layout.start_lba, entry_array.storage_mut())
+            .await?;
+        Ok(entry_array)
+    }
+
+    /// Read two entire partition entry arrays, usually the primary and
+    /// secondary arrays, with a single call to
+    /// [`AsyncBlockIo::poll_read_blocks_vectored`]. See
+    /// [`Disk::read_gpt_partition_entry_arrays`] for details.
+    ///
+    /// [`Disk::read_gpt_partition_entry_arrays`]: crate::Disk::read_gpt_partition_entry_arrays
+    pub async fn read_gpt_partition_entry_arrays<'buf>(
+        &mut self,
+        layouts: [GptPartitionEntryArrayLayout; 2],
+        storage: &'buf mut [u8],
+    ) -> Result<
+        (GptPartitionEntryArray<'buf>, GptPartitionEntryArray<'buf>),
+        DiskError<Io::Error>,
+    > {
+        let block_size = self.io.block_size();
+        let first_len = layouts[0]
+            .num_bytes_rounded_to_block_as_usize(block_size)
+            .ok_or(DiskError::Overflow)?;
+        if storage.len() < first_len {
+            return Err(DiskError::BufferTooSmall);
+        }
+        let (first_storage, second_storage) = storage.split_at_mut(first_len);
+
+        let mut first =
+            GptPartitionEntryArray::new(layouts[0], block_size, first_storage)
+                .map_err(entry_array_error)?;
+        let mut second =
+            GptPartitionEntryArray::new(layouts[1], block_size, second_storage)
+                .map_err(entry_array_error)?;
+        self.read_blocks_vectored(&mut [
+            (layouts[0].start_lba, first.storage_mut()),
+            (layouts[1].start_lba, second.storage_mut()),
+        ])
+        .await?;
+        Ok((first, second))
+    }
+
+    /// Write an entire [`GptPartitionEntryArray`] to disk.
+    pub async fn write_gpt_partition_entry_array(
+        &mut self,
+        entry_array: &GptPartitionEntryArray<'_>,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.write_blocks(entry_array.layout().start_lba, entry_array.storage())
+            .await
+    }
+
+    /// Get an asynchronous iterator over partition entries. The
+    /// `layout` parameter indicates where to read the entries from; see
+    /// [`GptPartitionEntryArrayLayout`] for more.
+    ///
+    /// Entries are read one block at a time into `block_buf` by
+    /// [`AsyncGptPartitionEntryIter::next_entry`].
+    pub fn gpt_partition_entry_array_iter<'disk, 'buf>(
+        &'disk mut self,
+        layout: GptPartitionEntryArrayLayout,
+        mut block_buf: &'buf mut [u8],
+    ) -> Result<AsyncGptPartitionEntryIter<'disk, 'buf, Io>, DiskError<Io::Error>>
+    {
+        block_buf = clip_block_buf(self.io.block_size(), block_buf)?;
+
+        let entry_size =
+            layout.entry_size.to_usize().ok_or(DiskError::Overflow)?;
+        if entry_size > block_buf.len() {
+            return Err(DiskError::BlockSizeSmallerThanPartitionEntry);
+        }
+
+        Ok(AsyncGptPartitionEntryIter {
+            disk: self,
+            // Start at the end of the buffer so that the first block is
+            // read by the first call to `next_entry`.
+            byte_offset_within_lba: block_buf.len(),
+            block_buf,
+            layout,
+            next_index: 0,
+            next_lba: layout.start_lba,
+            entry_size,
+        })
+    }
+
+    /// Read the MBR from the first block. No validation of the MBR is
+    /// performed.
+    pub async fn read_mbr(
+        &mut self,
+        mut block_buf: &mut [u8],
+    ) -> Result<MasterBootRecord, DiskError<Io::Error>> {
+        block_buf = clip_block_buf(self.io.block_size(), block_buf)?;
+        self.read_blocks(Lba(0), block_buf).await?;
+        let bytes = block_buf
+            .get(..mem::size_of::<MasterBootRecord>())
+            // OK to unwrap since the block size type guarantees a
+            // minimum size of 512 bytes, the size of the MBR.
+            .unwrap();
+        Ok(*from_bytes(bytes))
+    }
+
+    /// Write a protective MBR to the first block. If the block size is
+    /// bigger than the MBR, the rest of the block will be filled with
+    /// zeroes.
+    pub async fn write_protective_mbr(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let num_blocks = self.num_blocks().await?;
+        let mbr = MasterBootRecord::protective_mbr(num_blocks);
+        self.write_mbr(&mbr, block_buf).await
+    }
+
+    /// Write an MBR to the first block. If the block size is bigger
+    /// than the MBR, the rest of the block will be filled with zeroes.
+    pub async fn write_mbr(
+        &mut self,
+        mbr: &MasterBootRecord,
+        mut block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        block_buf = clip_block_buf(self.io.block_size(), block_buf)?;
+        fill_block(block_buf, bytes_of(mbr));
+        self.write_blocks(Lba(0), block_buf).await
+    }
+
+    /// Write the primary GPT header to the second block. The rest of
+    /// the block is set to zero.
+    pub async fn write_primary_gpt_header(
+        &mut self,
+        header: &GptHeader,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.write_gpt_header(Lba(1), header, block_buf).await
+    }
+
+    /// Write the secondary GPT header to the last block. The rest of
+    /// the block is set to zero.
+    pub async fn write_secondary_gpt_header(
+        &mut self,
+        header: &GptHeader,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let last_block = self.last_lba().await?;
+        self.write_gpt_header(last_block, header, block_buf).await
+    }
+
+    /// Write a [`GptHeader`] to the specified [`Lba`]. The rest of the
+    /// block is set to zero.
+    pub async fn write_gpt_header(
+        &mut self,
+        lba: Lba,
+        header: &GptHeader,
+        mut block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        block_buf = clip_block_buf(self.io.block_size(), block_buf)?;
+        fill_block(block_buf, bytes_of(header));
+        self.write_blocks(lba, block_buf).await
+    }
+
+    /// Read the primary GPT header and partition entry array into a
+    /// [`GptTable`]. See [`Disk::read_gpt_table`] for details.
+    ///
+    /// [`Disk::read_gpt_table`]: crate::Disk::read_gpt_table
+    pub async fn read_gpt_table<'buf>(
+        &mut self,
+        block_buf: &mut [u8],
+        storage: &'buf mut [u8],
+    ) -> Result<GptTable<'buf>, DiskError<Io::Error>> {
+        let header = self.read_primary_gpt_header(block_buf).await?;
+        let layout = GptTable::check_header(&header)?;
+        let entry_array =
+            self.read_gpt_partition_entry_array(layout, storage).await?;
+        GptTable::from_parts(header, entry_array)
+    }
+
+    /// Flush any pending writes to the disk.
+    pub async fn flush(&mut self) -> Result<(), DiskError<Io::Error>> {
+        Ok(poll_fn(|cx| self.io.poll_flush(cx)).await?)
+    }
+}
@@ -6,8 +6,16 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+pub(crate) mod cached_block_io;
+pub(crate) mod partition_block_io;
+pub(crate) mod read_only_block_io;
 pub(crate) mod slice_block_io;
 
+#[cfg(feature = "alloc")]
+pub(crate) mod fault_injecting_block_io;
+#[cfg(feature = "alloc")]
+pub(crate) mod overlay_block_io;
+
 #[cfg(feature = "std")]
 pub(crate) mod std_block_io;
 
@@ -65,6 +73,102 @@ pub trait BlockIo {
 
     /// Flush any pending writes to the device.
     fn flush(&mut self) -> Result<(), Self::Error>;
+
+    /// Read blocks from several locations on the disk. Each request is
+    /// a start LBA and a `dst` buffer, with the same requirements as
+    /// [`read_blocks`].
+    ///
+    /// The default implementation calls [`read_blocks`] for each
+    /// request in order. Implementations for devices that can queue
+    /// several requests at once, such as NVM Express queues,
+    /// `io_uring`, or UEFI's `EFI_BLOCK_IO2_PROTOCOL`, can override
+    /// this method to submit all of the requests together. Requests
+    /// may complete in any order.
+    ///
+    /// [`read_blocks`]: Self::read_blocks
+    fn read_blocks_vectored(
+        &mut self,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        for (start_lba, dst) in requests {
+            self.read_blocks(*start_lba, dst)?;
+        }
+        Ok(())
+    }
+
+    /// Write blocks to several locations on the disk. Each request is
+    /// a start LBA and a `src` buffer, with the same requirements as
+    /// [`write_blocks`].
+    ///
+    /// The default implementation calls [`write_blocks`] for each
+    /// request in order. Implementations can override this method to
+    /// submit all of the requests together, in which case they may
+    /// complete in any order. As with [`write_blocks`], writes are not
+    /// guaranteed to be complete until [`flush`] is called.
+    ///
+    /// [`flush`]: Self::flush
+    /// [`write_blocks`]: Self::write_blocks
+    fn write_blocks_vectored(
+        &mut self,
+        requests: &[(Lba, &[u8])],
+    ) -> Result<(), Self::Error> {
+        for (start_lba, src) in requests {
+            self.write_blocks(*start_lba, src)?;
+        }
+        Ok(())
+    }
+}
+
+/// Allows a [`Disk`] or a wrapper such as [`PartitionBlockIo`] to
+/// borrow a `BlockIo` rather than take ownership of it, so that the
+/// `BlockIo` can be used again once the borrow ends.
+///
+/// [`Disk`]: crate::Disk
+/// [`PartitionBlockIo`]: crate::PartitionBlockIo
+impl<T: BlockIo + ?Sized> BlockIo for &mut T {
+    type Error = T::Error;
+
+    fn block_size(&self) -> BlockSize {
+        (**self).block_size()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        (**self).num_blocks()
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        (**self).read_blocks(start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        (**self).write_blocks(start_lba, src)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
+
+    fn read_blocks_vectored(
+        &mut self,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        (**self).read_blocks_vectored(requests)
+    }
+
+    fn write_blocks_vectored(
+        &mut self,
+        requests: &[(Lba, &[u8])],
+    ) -> Result<(), Self::Error> {
+        (**self).write_blocks_vectored(requests)
+    }
 }
 
 /// Adapter for types that can act as storage, but don't have a block
@@ -0,0 +1,372 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use gpt_disk_types::{BlockSize, Lba};
+
+#[cfg(feature = "alloc")]
+use {alloc::vec, alloc::vec::Vec};
+
+/// When writes to a [`CachedBlockIo`] reach the underlying device.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum CacheWriteMode {
+    /// Writes are passed to the underlying device immediately, and
+    /// the cache is updated with the written blocks.
+    WriteThrough,
+
+    /// Written blocks are held in the cache, and only written to the
+    /// underlying device when they are evicted or when
+    /// [`BlockIo::flush`] is called. Blocks may reach the device in a
+    /// different order than they were written.
+    WriteBack,
+}
+
+/// State of one block in a [`CachedBlockIo`]. Storage for these is
+/// provided by the caller; see [`CachedBlockIo::new`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct CacheSlot {
+    lba: Option<Lba>,
+    dirty: bool,
+    last_used: u64,
+}
+
+/// [`BlockIo`] wrapper that caches recently used blocks.
+///
+/// The cache holds a fixed number of blocks. When it is full, the
+/// least recently used block is evicted. Each cached block needs one
+/// block of `data` storage and one [`CacheSlot`]. In a `no_std`
+/// environment these are provided by the caller, for example as
+/// arrays; with the `alloc` feature, [`with_capacity`] allocates them.
+///
+/// This is useful for slow devices, since [`Disk`] methods read the
+/// same headers and partition entry blocks repeatedly.
+///
+/// In [`CacheWriteMode::WriteBack`] mode, dirty blocks are written
+/// to the device by [`BlockIo::flush`]. They are also flushed when
+/// the `CachedBlockIo` is dropped, but any errors at that point are
+/// ignored.
+///
+/// Vectored reads are passed on to the device together if none of the
+/// requested blocks are cached. In [`CacheWriteMode::WriteThrough`]
+/// mode, vectored writes are always passed on together.
+///
+/// [`Disk`]: crate::Disk
+/// [`with_capacity`]: Self::with_capacity
+pub struct CachedBlockIo<Io, D, S>
+where
+    Io: BlockIo,
+    D: AsMut<[u8]>,
+    S: AsMut<[CacheSlot]>,
+{
+    io: Io,
+    mode: CacheWriteMode,
+    data: D,
+    slots: S,
+    num_slots: usize,
+    block_size: usize,
+    clock: u64,
+}
+
+impl<Io, D, S> CachedBlockIo<Io, D, S>
+where
+    Io: BlockIo,
+    D: AsMut<[u8]>,
+    S: AsMut<[CacheSlot]>,
+{
+    /// Create a new `CachedBlockIo` wrapping `io`.
+    ///
+    /// The number of blocks that can be cached is the smaller of the
+    /// number of whole blocks in `data` and the number of entries in
+    /// `slots`. Any existing contents of `slots` are reset.
+    pub fn new(
+        io: Io,
+        mode: CacheWriteMode,
+        mut data: D,
+        mut slots: S,
+    ) -> Self {
+        let block_size = io.block_size().to_usize().unwrap_or(usize::MAX);
+        slots.as_mut().fill(CacheSlot::default());
+        let num_slots =
+            slots.as_mut().len().min(data.as_mut().len() / block_size);
+        Self {
+            io,
+            mode,
+            data,
+            slots,
+            num_slots,
+            block_size,
+            clock: 0,
+        }
+    }
+
+    /// Get the [`CacheWriteMode`].
+    #[must_use]
+    pub fn mode(&self) -> CacheWriteMode {
+        self.mode
+    }
+
+    /// Get the maximum number of blocks that can be cached.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.num_slots
+    }
+
+    /// Get a reference to the underlying [`BlockIo`].
+    #[must_use]
+    pub fn inner(&self) -> &Io {
+        &self.io
+    }
+
+    fn lba_at(start_lba: Lba, index: usize) -> Lba {
+        // OK to unwrap: buffer lengths fit in a `u64`.
+        Lba(start_lba.to_u64() + u64::try_from(index).unwrap())
+    }
+
+    /// Find the slot containing `lba`, if any.
+    fn find(&mut self, lba: Lba) -> Option<usize> {
+        self.slots.as_mut()[..self.num_slots]
+            .iter()
+            .position(|slot| slot.lba == Some(lba))
+    }
+
+    /// Get the data of the slot at `index`, and mark it as most
+    /// recently used.
+    fn use_slot(&mut self, index: usize) -> (&mut CacheSlot, &mut [u8]) {
+        self.clock += 1;
+        let slot = &mut self.slots.as_mut()[index];
+        slot.last_used = self.clock;
+        let data = &mut self.data.as_mut()[index * self.block_size..]
+            [..self.block_size];
+        (slot, data)
+    }
+
+    /// Write the slot at `index` to the device if it is dirty.
+    fn write_back(&mut self, index: usize) -> Result<(), Io::Error> {
+        let slot = &mut self.slots.as_mut()[index];
+        if let (Some(lba), true) = (slot.lba, slot.dirty) {
+            let data = &self.data.as_mut()[index * self.block_size..]
+                [..self.block_size];
+            self.io.write_blocks(lba, data)?;
+            slot.dirty = false;
+        }
+        Ok(())
+    }
+
+    /// Add a block to the cache, evicting the least recently used
+    /// block if the cache is full. Does nothing if the capacity is
+    /// zero.
+    fn insert(
+        &mut self,
+        lba: Lba,
+        src: &[u8],
+        dirty: bool,
+    ) -> Result<(), Io::Error> {
+        let Some(index) = self.slots.as_mut()[..self.num_slots]
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, slot)| (slot.lba.is_some(), slot.last_used))
+            .map(|(index, _)| index)
+        else {
+            return Ok(());
+        };
+        self.write_back(index)?;
+
+        let (slot, data) = self.use_slot(index);
+        slot.lba = Some(lba);
+        slot.dirty = dirty;
+        data.copy_from_slice(src);
+        Ok(())
+    }
+
+    /// Check whether any of the blocks covered by `buf` are cached.
+    fn is_any_cached(&mut self, start_lba: Lba, buf: &[u8]) -> bool {
+        (0..buf.len() / self.block_size)
+            .any(|i| self.find(Self::lba_at(start_lba, i)).is_some())
+    }
+
+    /// Update the cached copy of each block in `src`, if present.
+    /// Blocks that are not cached are inserted if `insert_missing` is
+    /// true.
+    fn update(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+        dirty: bool,
+        insert_missing: bool,
+    ) -> Result<(), Io::Error> {
+        for (i, block) in src.chunks_exact(self.block_size).enumerate() {
+            let lba = Self::lba_at(start_lba, i);
+            if let Some(index) = self.find(lba) {
+                let (slot, data) = self.use_slot(index);
+                slot.dirty = dirty;
+                data.copy_from_slice(block);
+            } else if insert_missing {
+                self.insert(lba, block, dirty)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<Io: BlockIo> CachedBlockIo<Io, Vec<u8>, Vec<CacheSlot>> {
+    /// Create a new `CachedBlockIo` wrapping `io`, with storage for
+    /// `num_blocks` blocks allocated internally.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of the storage overflows a `usize`.
+    #[must_use]
+    pub fn with_capacity(
+        io: Io,
+        mode: CacheWriteMode,
+        num_blocks: usize,
+    ) -> Self {
+        let block_size = io.block_size().to_usize().unwrap();
+        let data = vec![0; block_size.checked_mul(num_blocks).unwrap()];
+        let slots = vec![CacheSlot::default(); num_blocks];
+        Self::new(io, mode, data, slots)
+    }
+}
+
+impl<Io, D, S> BlockIo for CachedBlockIo<Io, D, S>
+where
+    Io: BlockIo,
+    D: AsMut<[u8]>,
+    S: AsMut<[CacheSlot]>,
+{
+    type Error = Io::Error;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        self.io.num_blocks()
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size().assert_valid_block_buffer(dst);
+        let bs = self.block_size;
+        let num_blocks = dst.len() / bs;
+
+        let mut i = 0;
+        while i < num_blocks {
+            let lba = Self::lba_at(start_lba, i);
+            if let Some(index) = self.find(lba) {
+                let (_, data) = self.use_slot(index);
+                dst[i * bs..][..bs].copy_from_slice(data);
+                i += 1;
+                continue;
+            }
+
+            // Read all consecutive uncached blocks at once.
+            let run_start = i;
+            while i < num_blocks
+                && self.find(Self::lba_at(start_lba, i)).is_none()
+            {
+                i += 1;
+            }
+            let run = &mut dst[run_start * bs..i * bs];
+            self.io.read_blocks(lba, run)?;
+            self.update(lba, run, false, true)?;
+        }
+        Ok(())
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size().assert_valid_block_buffer(src);
+        let num_blocks = src.len() / self.block_size;
+
+        if self.mode == CacheWriteMode::WriteBack
+            && num_blocks <= self.num_slots
+        {
+            return self.update(start_lba, src, true, true);
+        }
+
+        // Writes larger than the cache go directly to the device, and
+        // only update blocks that are already cached.
+        self.io.write_blocks(start_lba, src)?;
+        self.update(start_lba, src, false, num_blocks <= self.num_slots)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        for index in 0..self.num_slots {
+            self.write_back(index)?;
+        }
+        self.io.flush()
+    }
+
+    fn read_blocks_vectored(
+        &mut self,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        let mut any_cached = false;
+        for (start_lba, dst) in requests.iter() {
+            self.block_size().assert_valid_block_buffer(dst);
+            any_cached |= self.is_any_cached(*start_lba, dst);
+        }
+
+        // Cached blocks must not be read from the device, since they
+        // may be dirty, so read each request separately.
+        if any_cached {
+            for (start_lba, dst) in requests {
+                self.read_blocks(*start_lba, dst)?;
+            }
+            return Ok(());
+        }
+
+        self.io.read_blocks_vectored(requests)?;
+        for (start_lba, dst) in requests.iter() {
+            self.update(*start_lba, dst, false, true)?;
+        }
+        Ok(())
+    }
+
+    fn write_blocks_vectored(
+        &mut self,
+        requests: &[(Lba, &[u8])],
+    ) -> Result<(), Self::Error> {
+        if self.mode == CacheWriteMode::WriteBack {
+            for (start_lba, src) in requests {
+                self.write_blocks(*start_lba, src)?;
+            }
+            return Ok(());
+        }
+
+        for (_, src) in requests {
+            self.block_size().assert_valid_block_buffer(src);
+        }
+        self.io.write_blocks_vectored(requests)?;
+        for (start_lba, src) in requests {
+            let num_blocks = src.len() / self.block_size;
+            self.update(*start_lba, src, false, num_blocks <= self.num_slots)?;
+        }
+        Ok(())
+    }
+}
+
+impl<Io, D, S> Drop for CachedBlockIo<Io, D, S>
+where
+    Io: BlockIo,
+    D: AsMut<[u8]>,
+    S: AsMut<[CacheSlot]>,
+{
+    fn drop(&mut self) {
+        // Throw away any errors.
+        let _r = self.flush();
+    }
+}
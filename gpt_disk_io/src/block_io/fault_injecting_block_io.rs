@@ -0,0 +1,289 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use gpt_disk_types::{BlockSize, Lba};
+
+/// Error type used by [`FaultInjectingBlockIo`].
+///
+/// If the `std` feature is enabled, this type implements the [`Error`]
+/// trait.
+///
+/// [`Error`]: std::error::Error
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum FaultInjectingBlockIoError<IoError> {
+    /// A write or flush failed because the limit set with
+    /// [`FaultInjectingBlockIo::set_fail_after`] was reached.
+    InjectedFault,
+
+    /// Error from the underlying [`BlockIo`].
+    Io(IoError),
+}
+
+impl<IoError> From<IoError> for FaultInjectingBlockIoError<IoError>
+where
+    IoError: Debug + Display,
+{
+    fn from(err: IoError) -> Self {
+        FaultInjectingBlockIoError::Io(err)
+    }
+}
+
+impl<IoError> Display for FaultInjectingBlockIoError<IoError>
+where
+    IoError: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InjectedFault => f.write_str("injected fault"),
+            Self::Io(io) => Display::fmt(io, f),
+        }
+    }
+}
+
+/// Write or flush recorded by a [`FaultInjectingBlockIo`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum BlockIoEvent {
+    /// Call to [`BlockIo::write_blocks`].
+    Write {
+        /// Start LBA.
+        start_lba: Lba,
+
+        /// Data written.
+        data: Vec<u8>,
+    },
+
+    /// Call to [`BlockIo::flush`].
+    Flush,
+}
+
+/// [`BlockIo`] wrapper for testing that operations are crash safe.
+///
+/// Every successful write and flush is recorded, in order, as a
+/// [`BlockIoEvent`]. After an operation has run, [`replay_crashes`]
+/// recreates each state the disk could have been left in if power was
+/// lost part way through, so that each state can be checked.
+///
+/// Faults can also be injected directly: after the number of events set
+/// with [`set_fail_after`], all further writes and flushes fail with
+/// [`FaultInjectingBlockIoError::InjectedFault`].
+///
+/// [`replay_crashes`]: Self::replay_crashes
+/// [`set_fail_after`]: Self::set_fail_after
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FaultInjectingBlockIo<Io> {
+    io: Io,
+    events: Vec<BlockIoEvent>,
+    fail_after: Option<usize>,
+}
+
+impl<Io: BlockIo> FaultInjectingBlockIo<Io> {
+    /// Maximum number of writes between two flushes that
+    /// [`replay_crashes`] can handle.
+    ///
+    /// [`replay_crashes`]: Self::replay_crashes
+    pub const MAX_WRITES_BETWEEN_FLUSHES: usize = 16;
+
+    /// Create a new `FaultInjectingBlockIo` wrapping `io`.
+    #[must_use]
+    pub fn new(io: Io) -> Self {
+        Self {
+            io,
+            events: Vec::new(),
+            fail_after: None,
+        }
+    }
+
+    /// Make writes and flushes fail once `num_events` events have been
+    /// recorded. If `None`, no faults are injected.
+    pub fn set_fail_after(&mut self, num_events: Option<usize>) {
+        self.fail_after = num_events;
+    }
+
+    /// Get the writes and flushes recorded so far.
+    #[must_use]
+    pub fn events(&self) -> &[BlockIoEvent] {
+        &self.events
+    }
+
+    /// Forget all recorded events.
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+
+    /// Get a reference to the underlying [`BlockIo`].
+    #[must_use]
+    pub fn inner(&self) -> &Io {
+        &self.io
+    }
+
+    /// Consume the `FaultInjectingBlockIo` and return the underlying
+    /// [`BlockIo`].
+    #[must_use]
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+
+    /// Replay the recorded events onto a copy of `original`, calling
+    /// `on_state` with each state the disk could be in after a power
+    /// loss.
+    ///
+    /// Flushes split the events into groups of writes. Every write in
+    /// a group reaches the device before any write in the next group,
+    /// but the writes within a group may land in any order. For each
+    /// group, there is a state for every subset of its writes that may
+    /// have landed, on top of all earlier groups. A write of more than
+    /// one block may also be torn, so for each subset there are also
+    /// states in which one of the other writes has only written its
+    /// first blocks. Writes of a single block are assumed to be atomic.
+    ///
+    /// The first state is `original` with no events applied. The
+    /// number of states grows exponentially with the number of writes
+    /// between flushes.
+    ///
+    /// `on_state` can modify the state it is given, for example to run
+    /// a recovery step; each state starts from a fresh copy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a recorded write is outside of `original`, or if more
+    /// than [`MAX_WRITES_BETWEEN_FLUSHES`] writes were recorded between
+    /// two flushes.
+    ///
+    /// [`MAX_WRITES_BETWEEN_FLUSHES`]: Self::MAX_WRITES_BETWEEN_FLUSHES
+    pub fn replay_crashes(
+        &self,
+        original: &[u8],
+        mut on_state: impl FnMut(&mut [u8]),
+    ) {
+        let block_size = self.io.block_size().to_usize().unwrap_or(usize::MAX);
+        let apply = |image: &mut [u8], start_lba: Lba, data: &[u8]| {
+            // OK to unwrap: the write succeeded, so its location fits
+            // in a `usize` for any slice-backed `original`.
+            let start = usize::try_from(start_lba.to_u64())
+                .unwrap()
+                .checked_mul(block_size)
+                .unwrap();
+            image[start..][..data.len()].copy_from_slice(data);
+        };
+
+        // `image` is the disk after all earlier groups, and `landed` is
+        // `image` plus the writes in the current subset.
+        let mut image = original.to_vec();
+        let mut landed = original.to_vec();
+        let mut state = original.to_vec();
+        on_state(&mut state);
+
+        for group in self.events.split(|event| *event == BlockIoEvent::Flush) {
+            let writes: Vec<(Lba, &[u8])> = group
+                .iter()
+                .filter_map(|event| match event {
+                    BlockIoEvent::Write { start_lba, data } => {
+                        Some((*start_lba, data.as_slice()))
+                    }
+                    BlockIoEvent::Flush => None,
+                })
+                .collect();
+            assert!(
+                writes.len() <= Self::MAX_WRITES_BETWEEN_FLUSHES,
+                "too many writes between flushes"
+            );
+
+            for subset in 0..1u32 << writes.len() {
+                let is_landed = |i: usize| subset & (1 << i) != 0;
+                landed.copy_from_slice(&image);
+                for (_, (start_lba, data)) in
+                    writes.iter().enumerate().filter(|(i, _)| is_landed(*i))
+                {
+                    apply(&mut landed, *start_lba, data);
+                }
+
+                // The empty subset is the same as the last state of the
+                // previous group.
+                if subset != 0 {
+                    state.copy_from_slice(&landed);
+                    on_state(&mut state);
+                }
+
+                for (_, (start_lba, data)) in
+                    writes.iter().enumerate().filter(|(i, _)| !is_landed(*i))
+                {
+                    for len in (block_size..data.len()).step_by(block_size) {
+                        state.copy_from_slice(&landed);
+                        apply(&mut state, *start_lba, &data[..len]);
+                        on_state(&mut state);
+                    }
+                }
+            }
+
+            for (start_lba, data) in writes {
+                apply(&mut image, start_lba, data);
+            }
+        }
+    }
+
+    fn check_fault(&self) -> Result<(), FaultInjectingBlockIoError<Io::Error>> {
+        if self.fail_after.map_or(false, |n| self.events.len() >= n) {
+            Err(FaultInjectingBlockIoError::InjectedFault)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<Io: BlockIo> BlockIo for FaultInjectingBlockIo<Io> {
+    type Error = FaultInjectingBlockIoError<Io::Error>;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.io.num_blocks()?)
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        Ok(self.io.read_blocks(start_lba, dst)?)
+    }
+
+    fn read_blocks_vectored(
+        &mut self,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        Ok(self.io.read_blocks_vectored(requests)?)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.check_fault()?;
+        self.io.write_blocks(start_lba, src)?;
+        self.events.push(BlockIoEvent::Write {
+            start_lba,
+            data: src.to_vec(),
+        });
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.check_fault()?;
+        self.io.flush()?;
+        self.events.push(BlockIoEvent::Flush);
+        Ok(())
+    }
+}
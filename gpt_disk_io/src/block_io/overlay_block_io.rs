@@ -0,0 +1,374 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use gpt_disk_types::{BlockSize, Lba, LbaRangeInclusive};
+
+/// Error type used by [`OverlayBlockIo`].
+///
+/// If the `std` feature is enabled, this type implements the [`Error`]
+/// trait.
+///
+/// [`Error`]: std::error::Error
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum OverlayBlockIoError<IoError> {
+    /// Numeric overflow occurred.
+    Overflow,
+
+    /// A write is past the end of the base [`BlockIo`].
+    OutOfBounds {
+        /// Start LBA.
+        start_lba: Lba,
+
+        /// Length in bytes.
+        length_in_bytes: usize,
+    },
+
+    /// Error from the base [`BlockIo`].
+    Io(IoError),
+}
+
+impl<IoError> From<IoError> for OverlayBlockIoError<IoError>
+where
+    IoError: Debug + Display,
+{
+    fn from(err: IoError) -> Self {
+        OverlayBlockIoError::Io(err)
+    }
+}
+
+impl<IoError> Display for OverlayBlockIoError<IoError>
+where
+    IoError: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => f.write_str("numeric overflow occurred"),
+            Self::OutOfBounds {
+                start_lba,
+                length_in_bytes,
+            } => {
+                write!(
+                    f,
+                    "out of bounds: start_lba={start_lba}, length_in_bytes={length_in_bytes}"
+                )
+            }
+            Self::Io(io) => Display::fmt(io, f),
+        }
+    }
+}
+
+/// Order in which [`OverlayBlockIo::commit`] writes blocks to the base
+/// [`BlockIo`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum OverlayCommitOrder {
+    /// Write blocks from the lowest LBA to the highest.
+    Ascending,
+
+    /// Write blocks from the highest LBA to the lowest. For a GPT
+    /// disk, this writes the secondary header before the primary
+    /// header.
+    Descending,
+}
+
+/// Copy-on-write [`BlockIo`] wrapper that keeps all writes in memory.
+///
+/// Reads return the most recently written data for a block if it has
+/// been written, and otherwise read from the base `BlockIo`. Writes
+/// are never passed to the base until [`commit`] is called, so a
+/// [`Disk`] wrapping an `OverlayBlockIo` can be used to preview
+/// changes to a disk, or to modify a read-only disk image in tests.
+///
+/// Writes are still bounds-checked against the base, so that an
+/// operation that would fail on the real disk also fails here. A
+/// vectored write stores nothing if any of its requests is out of
+/// bounds. Vectored reads are passed on to the base together.
+///
+/// If the `OverlayBlockIo` is dropped without calling [`commit`], the
+/// changes are discarded.
+///
+/// ```
+/// use gpt_disk_io::gpt_disk_types::{BlockSize, Lba, LbaRangeInclusive};
+/// use gpt_disk_io::{BlockIo, BlockIoAdapter, OverlayBlockIo};
+///
+/// let data: &[u8] = &[0; 512 * 4];
+/// let base = BlockIoAdapter::new(data, BlockSize::BS_512);
+/// let mut overlay = OverlayBlockIo::new(base);
+///
+/// overlay.write_blocks(Lba(2), &[1; 512]).unwrap();
+/// let mut block = [0; 512];
+/// overlay.read_blocks(Lba(2), &mut block).unwrap();
+/// assert_eq!(block, [1; 512]);
+///
+/// assert_eq!(
+///     overlay.diff().unwrap(),
+///     [LbaRangeInclusive::new(Lba(2), Lba(2)).unwrap()]
+/// );
+/// ```
+///
+/// [`Disk`]: crate::Disk
+/// [`commit`]: Self::commit
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OverlayBlockIo<Io> {
+    base: Io,
+    blocks: BTreeMap<u64, Vec<u8>>,
+}
+
+impl<Io: BlockIo> OverlayBlockIo<Io> {
+    /// Create a new `OverlayBlockIo` with no changes on top of `base`.
+    #[must_use]
+    pub fn new(base: Io) -> Self {
+        Self {
+            base,
+            blocks: BTreeMap::new(),
+        }
+    }
+
+    /// Get a reference to the base [`BlockIo`].
+    #[must_use]
+    pub fn base(&self) -> &Io {
+        &self.base
+    }
+
+    /// Consume the `OverlayBlockIo` and return the base [`BlockIo`].
+    /// Uncommitted changes are discarded.
+    #[must_use]
+    pub fn into_base(self) -> Io {
+        self.base
+    }
+
+    /// Get the number of blocks that have been written.
+    #[must_use]
+    pub fn num_modified_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Get an iterator over each block that has been written, in
+    /// ascending LBA order, along with its contents.
+    pub fn modified_blocks(&self) -> impl Iterator<Item = (Lba, &[u8])> {
+        self.blocks
+            .iter()
+            .map(|(lba, data)| (Lba(*lba), data.as_slice()))
+    }
+
+    /// Get the ranges of blocks whose contents differ from the base.
+    /// Blocks that were written with the same data as the base are not
+    /// included. Adjacent blocks are combined into a single range.
+    pub fn diff(
+        &mut self,
+    ) -> Result<Vec<LbaRangeInclusive>, OverlayBlockIoError<Io::Error>> {
+        let mut base_block = vec![0; self.block_size_as_usize()?];
+        let mut ranges: Vec<LbaRangeInclusive> = Vec::new();
+        for (lba, data) in &self.blocks {
+            self.base.read_blocks(Lba(*lba), &mut base_block)?;
+            if base_block == *data {
+                continue;
+            }
+            let lba = Lba(*lba);
+            match ranges.last_mut() {
+                Some(last) if last.end().to_u64() + 1 == lba.to_u64() => {
+                    // OK to unwrap: `lba` is after `last.start()`.
+                    *last = LbaRangeInclusive::new(last.start(), lba).unwrap();
+                }
+                // OK to unwrap: a single-block range is always valid.
+                _ => ranges.push(LbaRangeInclusive::new(lba, lba).unwrap()),
+            }
+        }
+        Ok(ranges)
+    }
+
+    /// Throw away all changes.
+    pub fn discard(&mut self) {
+        self.blocks.clear();
+    }
+
+    /// Write a single modified block to the base. Returns `false` if
+    /// the block has not been modified.
+    ///
+    /// This can be used to commit changes in an order not covered by
+    /// [`OverlayCommitOrder`]. Call [`BlockIo::flush`] on the base
+    /// afterwards to ensure the writes are complete.
+    pub fn commit_block(
+        &mut self,
+        lba: Lba,
+    ) -> Result<bool, OverlayBlockIoError<Io::Error>> {
+        let Some(data) = self.blocks.get(&lba.to_u64()) else {
+            return Ok(false);
+        };
+        self.base.write_blocks(lba, data)?;
+        self.blocks.remove(&lba.to_u64());
+        Ok(true)
+    }
+
+    /// Write all modified blocks to the base in the given `order`, then
+    /// flush the base.
+    ///
+    /// Blocks are removed from the overlay as they are written, so if
+    /// an error occurs, the blocks that were not yet written remain in
+    /// the overlay and the commit can be retried.
+    pub fn commit(
+        &mut self,
+        order: OverlayCommitOrder,
+    ) -> Result<(), OverlayBlockIoError<Io::Error>> {
+        loop {
+            let next = match order {
+                OverlayCommitOrder::Ascending => self.blocks.first_key_value(),
+                OverlayCommitOrder::Descending => self.blocks.last_key_value(),
+            };
+            let Some((lba, _)) = next else {
+                break;
+            };
+            self.commit_block(Lba(*lba))?;
+        }
+        Ok(self.base.flush()?)
+    }
+
+    fn block_size_as_usize(
+        &self,
+    ) -> Result<usize, OverlayBlockIoError<Io::Error>> {
+        self.base
+            .block_size()
+            .to_usize()
+            .ok_or(OverlayBlockIoError::Overflow)
+    }
+
+    /// Replace the blocks of `dst`, which was read from the base
+    /// starting at `start_lba`, with any blocks that have been written.
+    fn apply_written_blocks(
+        &self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), OverlayBlockIoError<Io::Error>> {
+        let bs = self.block_size_as_usize()?;
+        for (i, block) in dst.chunks_exact_mut(bs).enumerate() {
+            let lba = u64::try_from(i)
+                .ok()
+                .and_then(|i| start_lba.to_u64().checked_add(i))
+                .ok_or(OverlayBlockIoError::Overflow)?;
+            if let Some(data) = self.blocks.get(&lba) {
+                block.copy_from_slice(data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that a write of `src` to `start_lba` is within a base with
+    /// `num_blocks` blocks.
+    fn check_write_bounds(
+        &self,
+        start_lba: Lba,
+        src: &[u8],
+        num_blocks: u64,
+    ) -> Result<(), OverlayBlockIoError<Io::Error>> {
+        self.block_size().assert_valid_block_buffer(src);
+        let bs = self.block_size_as_usize()?;
+
+        let src_blocks = u64::try_from(src.len() / bs)
+            .map_err(|_| OverlayBlockIoError::Overflow)?;
+        let end = start_lba
+            .to_u64()
+            .checked_add(src_blocks)
+            .ok_or(OverlayBlockIoError::Overflow)?;
+        if end > num_blocks {
+            return Err(OverlayBlockIoError::OutOfBounds {
+                start_lba,
+                length_in_bytes: src.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Store the blocks of `src`, which must already be bounds-checked.
+    fn insert_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), OverlayBlockIoError<Io::Error>> {
+        let bs = self.block_size_as_usize()?;
+        for (lba, block) in (start_lba.to_u64()..).zip(src.chunks_exact(bs)) {
+            self.blocks.insert(lba, block.to_vec());
+        }
+        Ok(())
+    }
+}
+
+impl<Io: BlockIo> BlockIo for OverlayBlockIo<Io> {
+    type Error = OverlayBlockIoError<Io::Error>;
+
+    fn block_size(&self) -> BlockSize {
+        self.base.block_size()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.base.num_blocks()?)
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size().assert_valid_block_buffer(dst);
+
+        // Read the whole range from the base, then replace any blocks
+        // that have been written.
+        self.base.read_blocks(start_lba, dst)?;
+        self.apply_written_blocks(start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        let num_blocks = self.base.num_blocks()?;
+        self.check_write_bounds(start_lba, src, num_blocks)?;
+        self.insert_blocks(start_lba, src)
+    }
+
+    fn read_blocks_vectored(
+        &mut self,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        for (_, dst) in requests.iter() {
+            self.block_size().assert_valid_block_buffer(dst);
+        }
+        self.base.read_blocks_vectored(requests)?;
+        for (start_lba, dst) in requests.iter_mut() {
+            self.apply_written_blocks(*start_lba, dst)?;
+        }
+        Ok(())
+    }
+
+    fn write_blocks_vectored(
+        &mut self,
+        requests: &[(Lba, &[u8])],
+    ) -> Result<(), Self::Error> {
+        // Check every request first, so that nothing is stored if any
+        // request is out of bounds.
+        let num_blocks = self.base.num_blocks()?;
+        for (start_lba, src) in requests {
+            self.check_write_bounds(*start_lba, src, num_blocks)?;
+        }
+        for (start_lba, src) in requests {
+            self.insert_blocks(*start_lba, src)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // Nothing to do; writes are only kept in memory.
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use core::fmt::{self, Debug, Display, Formatter};
+use gpt_disk_types::{BlockSize, Lba, LbaRangeInclusive};
+
+/// Maximum number of requests passed to the underlying
+/// [`BlockIo::write_blocks_vectored`] in one call.
+const VECTORED_BATCH_LEN: usize = 16;
+
+/// Error type used by [`PartitionBlockIo`].
+///
+/// If the `std` feature is enabled, this type implements the [`Error`]
+/// trait.
+///
+/// [`Error`]: std::error::Error
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum PartitionBlockIoError<IoError> {
+    /// Numeric overflow occurred.
+    Overflow,
+
+    /// A read or write is outside the partition.
+    OutOfBounds {
+        /// Start LBA, relative to the start of the partition.
+        start_lba: Lba,
+
+        /// Length in bytes.
+        length_in_bytes: usize,
+    },
+
+    /// Error from the underlying [`BlockIo`].
+    Io(IoError),
+}
+
+impl<IoError> From<IoError> for PartitionBlockIoError<IoError>
+where
+    IoError: Debug + Display,
+{
+    fn from(err: IoError) -> Self {
+        PartitionBlockIoError::Io(err)
+    }
+}
+
+impl<IoError> Display for PartitionBlockIoError<IoError>
+where
+    IoError: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => f.write_str("numeric overflow occurred"),
+            Self::OutOfBounds {
+                start_lba,
+                length_in_bytes,
+            } => {
+                write!(
+                    f,
+                    "out of bounds: start_lba={start_lba}, length_in_bytes={length_in_bytes}"
+                )
+            }
+            Self::Io(io) => Display::fmt(io, f),
+        }
+    }
+}
+
+/// [`BlockIo`] wrapper that exposes a range of blocks as a separate
+/// device, for example a single partition.
+///
+/// Block zero of the `PartitionBlockIo` is the first block of the
+/// range, and [`BlockIo::num_blocks`] returns the number of blocks in
+/// the range. Reads and writes that extend past the end of the range
+/// fail with [`PartitionBlockIoError::OutOfBounds`].
+///
+/// Vectored reads and writes are translated and passed on to the
+/// wrapped `io` as vectored requests, with writes split into batches of
+/// up to 16 requests. All requests are checked before any are passed
+/// on.
+///
+/// This is useful for passing a partition to code that expects a
+/// whole device, such as a filesystem implementation. Since
+/// [`BlockIo`] is implemented for `&mut T`, the wrapped `io` can be
+/// borrowed rather than moved:
+///
+/// ```
+/// use gpt_disk_io::gpt_disk_types::{BlockSize, Lba, LbaRangeInclusive};
+/// use gpt_disk_io::{BlockIo, BlockIoAdapter, PartitionBlockIo};
+///
+/// let mut data = vec![0; 512 * 8];
+/// let mut io = BlockIoAdapter::new(data.as_mut_slice(), BlockSize::BS_512);
+///
+/// let range = LbaRangeInclusive::new(Lba(2), Lba(5)).unwrap();
+/// let mut part = PartitionBlockIo::new(&mut io, range);
+/// assert_eq!(part.num_blocks().unwrap(), 4);
+/// part.write_blocks(Lba(1), &[1; 512]).unwrap();
+/// assert!(part.write_blocks(Lba(4), &[1; 512]).is_err());
+///
+/// drop(part);
+/// assert_eq!(data[512 * 3], 1);
+/// ```
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PartitionBlockIo<Io> {
+    io: Io,
+    range: LbaRangeInclusive,
+}
+
+impl<Io: BlockIo> PartitionBlockIo<Io> {
+    /// Create a new `PartitionBlockIo` that exposes `range` of `io`.
+    ///
+    /// The range is not checked against the size of `io`; accesses to
+    /// blocks past the end of `io` fail with the error from `io`.
+    #[must_use]
+    pub fn new(io: Io, range: LbaRangeInclusive) -> Self {
+        Self { io, range }
+    }
+
+    /// Get the range of blocks in the underlying [`BlockIo`].
+    #[must_use]
+    pub fn range(&self) -> LbaRangeInclusive {
+        self.range
+    }
+
+    /// Get a reference to the underlying [`BlockIo`].
+    #[must_use]
+    pub fn inner(&self) -> &Io {
+        &self.io
+    }
+
+    /// Consume the `PartitionBlockIo` and return the underlying
+    /// [`BlockIo`].
+    #[must_use]
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+
+    /// Convert `start_lba` to an LBA in the underlying device, checking
+    /// that all of `buf` is within the range.
+    fn translate(
+        &self,
+        start_lba: Lba,
+        buf: &[u8],
+    ) -> Result<Lba, PartitionBlockIoError<Io::Error>> {
+        let num_blocks = u64::try_from(buf.len())
+            .map_err(|_| PartitionBlockIoError::Overflow)?
+            / self.block_size().to_u64();
+        let end = start_lba
+            .to_u64()
+            .checked_add(num_blocks)
+            .ok_or(PartitionBlockIoError::Overflow)?;
+        if end > self.range.num_blocks() {
+            return Err(PartitionBlockIoError::OutOfBounds {
+                start_lba,
+                length_in_bytes: buf.len(),
+            });
+        }
+        // OK to unwrap: `start_lba` is within the range, so this
+        // cannot exceed `range.end()`.
+        Ok(Lba(self
+            .range
+            .start()
+            .to_u64()
+            .checked_add(start_lba.to_u64())
+            .unwrap()))
+    }
+}
+
+impl<Io: BlockIo> BlockIo for PartitionBlockIo<Io> {
+    type Error = PartitionBlockIoError<Io::Error>;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.range.num_blocks())
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size().assert_valid_block_buffer(dst);
+        let lba = self.translate(start_lba, dst)?;
+        Ok(self.io.read_blocks(lba, dst)?)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size().assert_valid_block_buffer(src);
+        let lba = self.translate(start_lba, src)?;
+        Ok(self.io.write_blocks(lba, src)?)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(self.io.flush()?)
+    }
+
+    fn read_blocks_vectored(
+        &mut self,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        // Check every request before reading anything.
+        for (start_lba, dst) in requests.iter() {
+            self.block_size().assert_valid_block_buffer(dst);
+            self.translate(*start_lba, dst)?;
+        }
+
+        // Translate the requests in place, and restore the original
+        // LBAs afterwards. OK to unwrap: the requests were checked.
+        for (start_lba, dst) in requests.iter_mut() {
+            *start_lba = self.translate(*start_lba, dst).unwrap();
+        }
+        let result = self.io.read_blocks_vectored(requests);
+        let offset = self.range.start().to_u64();
+        for (start_lba, _) in requests.iter_mut() {
+            *start_lba = Lba(start_lba.to_u64() - offset);
+        }
+        Ok(result?)
+    }
+
+    fn write_blocks_vectored(
+        &mut self,
+        requests: &[(Lba, &[u8])],
+    ) -> Result<(), Self::Error> {
+        // Check every request before writing anything.
+        for (start_lba, src) in requests {
+            self.block_size().assert_valid_block_buffer(src);
+            self.translate(*start_lba, src)?;
+        }
+
+        // The requests cannot be modified, so the translated requests
+        // are passed on in fixed-size batches.
+        let mut batch = [(Lba(0), &[][..]); VECTORED_BATCH_LEN];
+        for chunk in requests.chunks(VECTORED_BATCH_LEN) {
+            for (translated, (start_lba, src)) in batch.iter_mut().zip(chunk) {
+                // OK to unwrap: the requests were checked.
+                *translated = (self.translate(*start_lba, src).unwrap(), *src);
+            }
+            self.io.write_blocks_vectored(&batch[..chunk.len()])?;
+        }
+        Ok(())
+    }
+}
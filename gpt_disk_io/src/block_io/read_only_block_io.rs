@@ -0,0 +1,127 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use core::fmt::{self, Debug, Display, Formatter};
+use gpt_disk_types::{BlockSize, Lba};
+
+/// Error type used by [`ReadOnlyBlockIo`].
+///
+/// If the `std` feature is enabled, this type implements the [`Error`]
+/// trait.
+///
+/// [`Error`]: std::error::Error
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum ReadOnlyBlockIoError<IoError> {
+    /// Attempted to write to a read-only device.
+    ReadOnly,
+
+    /// Error from the underlying [`BlockIo`].
+    Io(IoError),
+}
+
+impl<IoError> From<IoError> for ReadOnlyBlockIoError<IoError>
+where
+    IoError: Debug + Display,
+{
+    fn from(err: IoError) -> Self {
+        ReadOnlyBlockIoError::Io(err)
+    }
+}
+
+impl<IoError> Display for ReadOnlyBlockIoError<IoError>
+where
+    IoError: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadOnly => {
+                f.write_str("attempted to write to a read-only device")
+            }
+            Self::Io(io) => Display::fmt(io, f),
+        }
+    }
+}
+
+/// [`BlockIo`] wrapper that never modifies the underlying device.
+///
+/// Reads are passed through to the wrapped `io`. Writes fail with
+/// [`ReadOnlyBlockIoError::ReadOnly`] without reaching `io`, and
+/// flushes do nothing. This guarantees that the device is not
+/// modified even by code that is generic over [`BlockIo`].
+///
+/// See also [`Disk::open_read_only`], which uses this wrapper.
+///
+/// [`Disk::open_read_only`]: crate::Disk::open_read_only
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReadOnlyBlockIo<Io> {
+    io: Io,
+}
+
+impl<Io: BlockIo> ReadOnlyBlockIo<Io> {
+    /// Create a new `ReadOnlyBlockIo` wrapping `io`.
+    #[must_use]
+    pub fn new(io: Io) -> Self {
+        Self { io }
+    }
+
+    /// Get a reference to the underlying [`BlockIo`].
+    #[must_use]
+    pub fn inner(&self) -> &Io {
+        &self.io
+    }
+
+    /// Consume the `ReadOnlyBlockIo` and return the underlying
+    /// [`BlockIo`].
+    #[must_use]
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+}
+
+impl<Io: BlockIo> BlockIo for ReadOnlyBlockIo<Io> {
+    type Error = ReadOnlyBlockIoError<Io::Error>;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.io.num_blocks()?)
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        Ok(self.io.read_blocks(start_lba, dst)?)
+    }
+
+    fn read_blocks_vectored(
+        &mut self,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        Ok(self.io.read_blocks_vectored(requests)?)
+    }
+
+    fn write_blocks(
+        &mut self,
+        _start_lba: Lba,
+        _src: &[u8],
+    ) -> Result<(), Self::Error> {
+        Err(ReadOnlyBlockIoError::ReadOnly)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // Nothing can have been written, so there is nothing to flush.
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{BlockIo, Disk, DiskError};
+use gpt_disk_types::{
+    BlockSize, Crc32, GptHeader, GptPartitionEntryArrayLayout,
+    GptPartitionEntrySize, Guid, Lba, LbaLe, U32Le,
+};
+
+/// Builder for a new GPT with an empty partition entry array.
+///
+/// The builder takes care of the disk layout: the primary header is
+/// placed in the second block, followed immediately by the primary
+/// partition entry array. The secondary header is placed in the last
+/// block, preceded immediately by the secondary partition entry
+/// array. At least 16,384 bytes are reserved for each array, as
+/// required by the UEFI Specification, even if the array itself is
+/// smaller. The usable range of the disk is everything in between.
+///
+/// Use [`Disk::format_gpt`] to write the GPT to a disk, or
+/// [`build_headers`] to just get the headers.
+///
+/// # Examples
+///
+/// ```
+/// use gpt_disk_io::{BlockIoAdapter, Disk, GptBuilder};
+/// use gpt_disk_types::{guid, BlockSize};
+///
+/// let mut disk_storage = vec![0; 4 * 1024 * 1024];
+/// let bs = BlockSize::BS_512;
+/// let block_io = BlockIoAdapter::new(disk_storage.as_mut_slice(), bs);
+/// let mut disk = Disk::new(block_io)?;
+///
+/// let builder =
+///     GptBuilder::new(guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870"));
+///
+/// let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+/// let primary_header = disk.format_gpt(&builder, &mut block_buf)?;
+/// assert_eq!(primary_header.first_usable_lba.to_u64(), 34);
+/// assert_eq!(primary_header.last_usable_lba.to_u64(), 8158);
+///
+/// # Ok::<(), gpt_disk_io::DiskError<gpt_disk_io::SliceBlockIoError>>(())
+/// ```
+///
+/// [`build_headers`]: Self::build_headers
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct GptBuilder {
+    disk_guid: Guid,
+    num_partition_entries: u32,
+    partition_entry_size: GptPartitionEntrySize,
+}
+
+impl GptBuilder {
+    /// Default number of entries in the partition entry array. Combined
+    /// with the default entry size of 128 bytes, this matches the
+    /// minimum of 16,384 bytes reserved for the array required by the
+    /// UEFI Specification.
+    pub const DEFAULT_NUM_PARTITION_ENTRIES: u32 = 128;
+
+    /// Minimum number of bytes reserved for each partition entry array,
+    /// as required by the UEFI Specification.
+    pub const MIN_PARTITION_ENTRY_ARRAY_BYTES: u64 = 16_384;
+
+    /// Create a `GptBuilder` for a disk with the given GUID. The
+    /// partition entry array defaults to
+    /// [`DEFAULT_NUM_PARTITION_ENTRIES`] entries of 128 bytes each.
+    ///
+    /// [`DEFAULT_NUM_PARTITION_ENTRIES`]: Self::DEFAULT_NUM_PARTITION_ENTRIES
+    #[must_use]
+    pub fn new(disk_guid: Guid) -> Self {
+        Self {
+            disk_guid,
+            num_partition_entries: Self::DEFAULT_NUM_PARTITION_ENTRIES,
+            partition_entry_size: GptPartitionEntrySize::default(),
+        }
+    }
+
+    /// Set the number of entries in the partition entry array.
+    #[must_use]
+    pub fn num_partition_entries(mut self, num_entries: u32) -> Self {
+        self.num_partition_entries = num_entries;
+        self
+    }
+
+    /// Set the size in bytes of each entry in the partition entry array.
+    #[must_use]
+    pub fn partition_entry_size(
+        mut self,
+        entry_size: GptPartitionEntrySize,
+    ) -> Self {
+        self.partition_entry_size = entry_size;
+        self
+    }
+
+    /// Get the [`GptPartitionEntryArrayLayout`] of the primary
+    /// partition entry array, which starts at the third block.
+    #[must_use]
+    pub fn partition_entry_array_layout(&self) -> GptPartitionEntryArrayLayout {
+        GptPartitionEntryArrayLayout {
+            start_lba: Lba(2),
+            entry_size: self.partition_entry_size,
+            num_entries: self.num_partition_entries,
+        }
+    }
+
+    /// Get the number of blocks reserved for each partition entry
+    /// array. This is the size of the array rounded up to a whole
+    /// block, or [`MIN_PARTITION_ENTRY_ARRAY_BYTES`] rounded up to a
+    /// whole block if that is larger.
+    ///
+    /// Returns `None` if overflow occurs.
+    ///
+    /// [`MIN_PARTITION_ENTRY_ARRAY_BYTES`]: Self::MIN_PARTITION_ENTRY_ARRAY_BYTES
+    #[must_use]
+    pub fn num_reserved_blocks(&self, block_size: BlockSize) -> Option<u64> {
+        num_reserved_blocks(&self.partition_entry_array_layout(), block_size)
+    }
+
+    /// Create the primary and secondary headers for a disk with
+    /// `num_blocks` blocks of size `block_size`. Both headers have
+    /// valid CRC32 checksums for a partition entry array that is
+    /// entirely zero.
+    ///
+    /// The usable range starts after the blocks reserved for the
+    /// primary array and ends before the blocks reserved for the
+    /// secondary array; see [`num_reserved_blocks`].
+    ///
+    /// Returns `None` if the disk is too small to hold the GPT with at
+    /// least one usable block, or if overflow occurs.
+    ///
+    /// [`num_reserved_blocks`]: Self::num_reserved_blocks
+    #[must_use]
+    pub fn build_headers(
+        &self,
+        block_size: BlockSize,
+        num_blocks: u64,
+    ) -> Option<(GptHeader, GptHeader)> {
+        let layout = self.partition_entry_array_layout();
+        let array_blocks = layout.num_blocks(block_size)?;
+        let reserved_blocks = self.num_reserved_blocks(block_size)?;
+
+        let first_usable_lba =
+            layout.start_lba.to_u64().checked_add(reserved_blocks)?;
+        let secondary_header_lba = num_blocks.checked_sub(1)?;
+        let secondary_array_lba =
+            secondary_header_lba.checked_sub(array_blocks)?;
+        let last_usable_lba = secondary_header_lba
+            .checked_sub(reserved_blocks)?
+            .checked_sub(1)?;
+        if first_usable_lba > last_usable_lba {
+            return None;
+        }
+
+        let mut primary = GptHeader {
+            my_lba: LbaLe::from_u64(1),
+            alternate_lba: LbaLe::from_u64(secondary_header_lba),
+            first_usable_lba: LbaLe::from_u64(first_usable_lba),
+            last_usable_lba: LbaLe::from_u64(last_usable_lba),
+            disk_guid: self.disk_guid,
+            partition_entry_lba: layout.start_lba.into(),
+            number_of_partition_entries: U32Le::from_u32(layout.num_entries),
+            size_of_partition_entry: U32Le::from_u32(
+                layout.entry_size.to_u32(),
+            ),
+            partition_entry_array_crc32: zeroed_array_crc32(&layout)?,
+            ..GptHeader::default()
+        };
+        primary.update_header_crc32();
+
+        let mut secondary = GptHeader {
+            my_lba: primary.alternate_lba,
+            alternate_lba: primary.my_lba,
+            partition_entry_lba: LbaLe::from_u64(secondary_array_lba),
+            ..primary
+        };
+        secondary.update_header_crc32();
+
+        Some((primary, secondary))
+    }
+}
+
+/// Get the number of blocks reserved for a partition entry array with
+/// the given layout. See [`GptBuilder::num_reserved_blocks`].
+pub(crate) fn num_reserved_blocks(
+    layout: &GptPartitionEntryArrayLayout,
+    block_size: BlockSize,
+) -> Option<u64> {
+    let array_blocks = layout.num_blocks(block_size)?;
+    let block_size = block_size.to_u64();
+    let min_blocks = (GptBuilder::MIN_PARTITION_ENTRY_ARRAY_BYTES + block_size
+        - 1)
+        / block_size;
+    Some(array_blocks.max(min_blocks))
+}
+
+/// Calculate the CRC32 checksum of a partition entry array with the
+/// given layout in which every byte is zero.
+fn zeroed_array_crc32(layout: &GptPartitionEntryArrayLayout) -> Option<Crc32> {
+    const ZEROES: [u8; 128] = [0; 128];
+
+    let crc = gpt_disk_types::crc::Crc::<u32>::new(&Crc32::ALGORITHM);
+    let mut digest = crc.digest();
+    let mut remaining = layout.num_bytes_exact()?;
+    while remaining > 0 {
+        let len = remaining.min(128);
+        // OK to unwrap: `len` is at most 128.
+        digest.update(&ZEROES[..usize::try_from(len).unwrap()]);
+        remaining -= len;
+    }
+    Some(Crc32(U32Le(digest.finalize().to_le_bytes())))
+}
+
+impl<Io: BlockIo> Disk<Io> {
+    /// Format the disk with a new, empty GPT.
+    ///
+    /// This writes a protective MBR, both partition entry arrays (all
+    /// zeroes, along with the rest of the blocks reserved for them),
+    /// and both GPT headers, as described by `builder`. Any
+    /// existing partition table is overwritten. The primary header is
+    /// returned.
+    ///
+    /// Returns [`DiskError::DiskTooSmall`] if the disk cannot hold the
+    /// GPT.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn format_gpt(
+        &mut self,
+        builder: &GptBuilder,
+        mut block_buf: &mut [u8],
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+
+        let block_size = self.io.block_size();
+        let num_blocks = self.io.num_blocks()?;
+        let (primary, secondary) = builder
+            .build_headers(block_size, num_blocks)
+            .ok_or(DiskError::DiskTooSmall)?;
+
+        // Zero out the blocks reserved for both partition entry arrays.
+        block_buf.fill(0);
+        let reserved = [
+            primary.partition_entry_lba.to_u64()
+                ..primary.first_usable_lba.to_u64(),
+            primary.last_usable_lba.to_u64() + 1..secondary.my_lba.to_u64(),
+        ];
+        for range in reserved {
+            for lba in range {
+                self.io.write_blocks(Lba(lba), block_buf)?;
+            }
+        }
+
+        self.write_protective_mbr(block_buf)?;
+        self.write_primary_gpt_header(&primary, block_buf)?;
+        self.write_secondary_gpt_header(&secondary, block_buf)?;
+
+        Ok(primary)
+    }
+}
@@ -6,19 +6,20 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::BlockIo;
+use crate::{BlockIo, GptTableError, ReadOnlyBlockIo, ReadOnlyBlockIoError};
 use bytemuck::{bytes_of, from_bytes};
 use core::fmt::{self, Debug, Display, Formatter};
+use core::marker::PhantomData;
 use core::mem;
 use gpt_disk_types::{
-    GptHeader, GptPartitionEntry, GptPartitionEntryArray,
+    BlockSize, GptHeader, GptPartitionEntry, GptPartitionEntryArray,
     GptPartitionEntryArrayError, GptPartitionEntryArrayLayout, Lba,
     MasterBootRecord,
 };
 
 /// Iterator over entries in a partition entry array.
-struct GptPartitionEntryIter<'disk, 'buf, Io: BlockIo> {
-    disk: &'disk mut Disk<Io>,
+struct GptPartitionEntryIter<'disk, 'buf, Io: BlockIo, Mode: DiskMode> {
+    disk: &'disk mut Disk<Io, Mode>,
     block_buf: &'buf mut [u8],
     layout: GptPartitionEntryArrayLayout,
     next_index: u32,
@@ -27,9 +28,11 @@ struct GptPartitionEntryIter<'disk, 'buf, Io: BlockIo> {
     entry_size: usize,
 }
 
-impl<'disk, 'buf, Io: BlockIo> GptPartitionEntryIter<'disk, 'buf, Io> {
+impl<'disk, 'buf, Io: BlockIo, Mode: DiskMode>
+    GptPartitionEntryIter<'disk, 'buf, Io, Mode>
+{
     fn new(
-        disk: &'disk mut Disk<Io>,
+        disk: &'disk mut Disk<Io, Mode>,
         layout: GptPartitionEntryArrayLayout,
         block_buf: &'buf mut [u8],
     ) -> Result<Self, DiskError<Io::Error>> {
@@ -74,8 +77,8 @@ impl<'disk, 'buf, Io: BlockIo> GptPartitionEntryIter<'disk, 'buf, Io> {
     }
 }
 
-impl<'disk, 'buf, Io: BlockIo> Iterator
-    for GptPartitionEntryIter<'disk, 'buf, Io>
+impl<'disk, 'buf, Io: BlockIo, Mode: DiskMode> Iterator
+    for GptPartitionEntryIter<'disk, 'buf, Io, Mode>
 {
     type Item = Result<GptPartitionEntry, DiskError<Io::Error>>;
 
@@ -104,8 +107,12 @@ pub trait Captures<'a, 'b> {}
 impl<'a, 'b, T: ?Sized> Captures<'a, 'b> for T {}
 
 /// Error type used by [`Disk`] methods.
+///
+/// New variants may be added in future releases without a breaking
+/// change, so matches on this type need a wildcard arm.
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum DiskError<IoError: Debug + Display> {
     /// The storage buffer is not large enough.
     BufferTooSmall,
@@ -116,6 +123,65 @@ pub enum DiskError<IoError: Debug + Display> {
     /// The partition entry size is larger than a single block.
     BlockSizeSmallerThanPartitionEntry,
 
+    /// The disk does not have enough blocks to hold the GPT.
+    DiskTooSmall,
+
+    /// The GPT header has an invalid signature, checksum, or field
+    /// value.
+    InvalidGptHeader,
+
+    /// The partition entry array's checksum does not match the GPT
+    /// header.
+    InvalidPartitionEntryArray,
+
+    /// A partition would extend past the usable range of the disk after
+    /// resizing.
+    PartitionOutsideDisk {
+        /// Index of the partition entry.
+        index: u32,
+    },
+
+    /// The size requested when shrinking the disk is larger than the
+    /// current size of the disk.
+    ShrinkTargetTooLarge {
+        /// Requested number of blocks.
+        num_blocks: u64,
+    },
+
+    /// The MBR or an extended boot record has an invalid signature,
+    /// describes an invalid extended partition chain, or is a
+    /// protective MBR.
+    InvalidMbr,
+
+    /// A link in an extended boot record chain leads back to an
+    /// earlier extended boot record.
+    EbrLoop {
+        /// Location of the extended boot record containing the link.
+        lba: Lba,
+    },
+
+    /// A link or logical partition in an extended boot record is not
+    /// inside the extended partition.
+    EbrOutOfRange {
+        /// Location of the extended boot record.
+        lba: Lba,
+    },
+
+    /// A partition cannot be represented in an MBR, either because its
+    /// type has no MBR equivalent or because it does not fit within
+    /// 32-bit LBAs.
+    PartitionNotMbrCompatible {
+        /// Index of the partition entry.
+        index: u32,
+    },
+
+    /// A partition table operation failed.
+    Table(GptTableError),
+
+    /// The block for a commit intent record is outside the disk,
+    /// overlaps the GPT, or is in the usable range of the GPT.
+    InvalidIntentLba,
+
     /// Error from a [`BlockIo`] implementation (see [`BlockIo::Error`]).
     ///
     /// [`BlockIo`]: crate::BlockIo
@@ -143,11 +209,125 @@ where
             Self::BlockSizeSmallerThanPartitionEntry => {
                 f.write_str("partition entries are larger than a single block")
             }
+            Self::DiskTooSmall => f.write_str("disk is too small"),
+            Self::InvalidGptHeader => f.write_str("invalid GPT header"),
+            Self::InvalidPartitionEntryArray => {
+                f.write_str("invalid partition entry array")
+            }
+            Self::PartitionOutsideDisk { index } => {
+                write!(f, "partition {index} extends past the end of the disk")
+            }
+            Self::ShrinkTargetTooLarge { num_blocks } => {
+                write!(
+                    f,
+                    "cannot shrink disk to {num_blocks} blocks, which is \
+                     larger than its current size"
+                )
+            }
+            Self::InvalidMbr => f.write_str("invalid MBR"),
+            Self::EbrLoop { lba } => {
+                write!(f, "extended boot record at LBA {lba} creates a loop")
+            }
+            Self::EbrOutOfRange { lba } => {
+                write!(
+                    f,
+                    "extended boot record at LBA {lba} points outside the \
+                     extended partition"
+                )
+            }
+            Self::PartitionNotMbrCompatible { index } => {
+                write!(f, "partition {index} cannot be represented in an MBR")
+            }
+            Self::Table(err) => Display::fmt(err, f),
+            Self::InvalidIntentLba => f.write_str(
+                "intent record block is outside the disk, overlaps the GPT, \
+                 or is in the usable range",
+            ),
             Self::Io(io) => Display::fmt(io, f),
         }
     }
 }
 
+mod private {
+    pub trait Sealed {}
+}
+
+/// Access mode of a [`Disk`]: either [`ReadWrite`] or [`ReadOnly`].
+///
+/// Methods that read from the disk are available in both modes, but
+/// methods that write to the disk are only available in
+/// [`ReadWrite`] mode. This trait is sealed and cannot be implemented
+/// outside this crate.
+pub trait DiskMode: private::Sealed {
+    /// Whether the disk is flushed when it is dropped.
+    #[doc(hidden)]
+    const FLUSH_ON_DROP: bool;
+}
+
+/// [`DiskMode`] that allows both reading and writing. This is the
+/// default.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum ReadWrite {}
+
+impl private::Sealed for ReadWrite {}
+
+impl DiskMode for ReadWrite {
+    const FLUSH_ON_DROP: bool = true;
+}
+
+/// [`DiskMode`] that only allows reading. See
+/// [`Disk::open_read_only`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum ReadOnly {}
+
+impl private::Sealed for ReadOnly {}
+
+impl DiskMode for ReadOnly {
+    const FLUSH_ON_DROP: bool = false;
+}
+
+/// Convert an error from creating a [`GptPartitionEntryArray`] to a
+/// [`DiskError`].
+pub(crate) fn entry_array_error<IoError: Debug + Display>(
+    err: GptPartitionEntryArrayError,
+) -> DiskError<IoError> {
+    match err {
+        GptPartitionEntryArrayError::BufferTooSmall => {
+            DiskError::BufferTooSmall
+        }
+        GptPartitionEntryArrayError::Overflow => DiskError::Overflow,
+    }
+}
+
+/// Clip the size of `block_buf` to a single block. Return
+/// `BufferTooSmall` if the buffer isn't big enough.
+pub(crate) fn clip_block_buf<IoError: Debug + Display>(
+    block_size: BlockSize,
+    block_buf: &mut [u8],
+) -> Result<&mut [u8], DiskError<IoError>> {
+    if let Some(block_size) = block_size.to_usize() {
+        block_buf
+            .get_mut(..block_size)
+            .ok_or(DiskError::BufferTooSmall)
+    } else {
+        Err(DiskError::BufferTooSmall)
+    }
+}
+
+/// Copy `bytes` to the beginning of `block_buf`, and set the rest of
+/// the block to zero.
+pub(crate) fn fill_block(block_buf: &mut [u8], bytes: &[u8]) {
+    // This should always be true because the block_buf size is already
+    // known to match the block size, and the block size is enforced to
+    // be at least 512 bytes, which is the size of the MBR struct and
+    // much larger than the size of the GptHeader struct.
+    assert!(block_buf.len() >= bytes.len());
+
+    let (left, right) = block_buf.split_at_mut(bytes.len());
+    left.copy_from_slice(bytes);
+    right.fill(0);
+}
+
 /// Read and write GPT disk data.
 ///
 /// The disk is accessed via an object implementing the [`BlockIo`]
@@ -176,33 +356,71 @@ where
 /// [`write_gpt_partition_entry_array`]; a block-at-a-time method may be
 /// added in the future.
 ///
+/// # Read-only access
+///
+/// A `Disk` created with [`open_read_only`] has the [`ReadOnly`] mode.
+/// Only methods that read from the disk are available on it, so
+/// attempts to modify the disk are rejected at compile time:
+///
+/// ```compile_fail
+/// use gpt_disk_io::gpt_disk_types::BlockSize;
+/// use gpt_disk_io::{BlockIoAdapter, Disk};
+///
+/// let data = vec![0; 512 * 8];
+/// let io = BlockIoAdapter::new(data.as_slice(), BlockSize::BS_512);
+/// let mut disk = Disk::open_read_only(io).unwrap();
+/// let mut block_buf = vec![0; 512];
+/// disk.write_protective_mbr(&mut block_buf).unwrap();
+/// ```
+///
+/// The underlying [`BlockIo`] is also wrapped in a [`ReadOnlyBlockIo`],
+/// so the device cannot be modified at runtime either, and the disk is
+/// not flushed when it is dropped.
+///
 /// [`flush`]: Self::flush
 /// [`gpt_partition_entry_array_iter`]: Self::gpt_partition_entry_array_iter
+/// [`open_read_only`]: Self::open_read_only
 /// [`read_gpt_partition_entry_array`]: Self::read_gpt_partition_entry_array
 /// [`write_gpt_partition_entry_array`]: Self::write_gpt_partition_entry_array
-pub struct Disk<Io: BlockIo> {
-    io: Io,
+pub struct Disk<Io: BlockIo, Mode: DiskMode = ReadWrite> {
+    pub(crate) io: Io,
+    mode: PhantomData<Mode>,
 }
 
 impl<Io: BlockIo> Disk<Io> {
     /// Create a `Disk`.
     pub fn new(io: Io) -> Result<Self, DiskError<Io::Error>> {
-        Ok(Self { io })
+        Ok(Self {
+            io,
+            mode: PhantomData,
+        })
+    }
+}
+
+impl<Io: BlockIo> Disk<ReadOnlyBlockIo<Io>, ReadOnly> {
+    /// Create a read-only `Disk`. Methods that write to the disk are
+    /// not available, and the disk is not flushed when dropped.
+    ///
+    /// `io` is wrapped in a [`ReadOnlyBlockIo`], so the device is
+    /// never written to.
+    pub fn open_read_only(
+        io: Io,
+    ) -> Result<Self, DiskError<ReadOnlyBlockIoError<Io::Error>>> {
+        Ok(Self {
+            io: ReadOnlyBlockIo::new(io),
+            mode: PhantomData,
+        })
     }
+}
 
+impl<Io: BlockIo, Mode: DiskMode> Disk<Io, Mode> {
     /// Clip the size of `block_buf` to a single block. Return
     /// `BufferTooSmall` if the buffer isn't big enough.
-    fn clip_block_buf_size<'buf>(
+    pub(crate) fn clip_block_buf_size<'buf>(
         &self,
         block_buf: &'buf mut [u8],
     ) -> Result<&'buf mut [u8], DiskError<Io::Error>> {
-        if let Some(block_size) = self.io.block_size().to_usize() {
-            block_buf
-                .get_mut(..block_size)
-                .ok_or(DiskError::BufferTooSmall)
-        } else {
-            Err(DiskError::BufferTooSmall)
-        }
+        clip_block_buf(self.io.block_size(), block_buf)
     }
 
     /// Read the primary GPT header from the second block. No validation
@@ -228,6 +446,41 @@ impl<Io: BlockIo> Disk<Io> {
         self.read_gpt_header(last_block, block_buf)
     }
 
+    /// Read the primary GPT header from the second block and the
+    /// secondary GPT header from the last block, with a single call to
+    /// [`BlockIo::read_blocks_vectored`]. No validation of the headers
+    /// is performed.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// two blocks. Afterwards, the first block of `block_buf` contains
+    /// the primary header's block and the second block contains the
+    /// secondary header's block.
+    pub fn read_gpt_headers(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<(GptHeader, GptHeader), DiskError<Io::Error>> {
+        let block_size =
+            self.io.block_size().to_usize().ok_or(DiskError::Overflow)?;
+        let num_blocks = self.io.num_blocks()?;
+        let last_block =
+            Lba(num_blocks.checked_sub(1).ok_or(DiskError::Overflow)?);
+        let (primary_buf, secondary_buf) = block_buf
+            .get_mut(..block_size.checked_mul(2).ok_or(DiskError::Overflow)?)
+            .ok_or(DiskError::BufferTooSmall)?
+            .split_at_mut(block_size);
+
+        self.io.read_blocks_vectored(&mut [
+            (Lba(1), &mut *primary_buf),
+            (last_block, &mut *secondary_buf),
+        ])?;
+        // OK to unwrap since the block size type guarantees a minimum
+        // size greater than GptHeader.
+        let parse_header = |buf: &[u8]| -> GptHeader {
+            *from_bytes(buf.get(..mem::size_of::<GptHeader>()).unwrap())
+        };
+        Ok((parse_header(primary_buf), parse_header(secondary_buf)))
+    }
+
     /// Read a GPT header at the given [`Lba`]. No validation of the
     /// header is performed.
     ///
@@ -258,28 +511,50 @@ impl<Io: BlockIo> Disk<Io> {
     ) -> Result<GptPartitionEntryArray<'buf>, DiskError<Io::Error>> {
         let mut entry_array =
             GptPartitionEntryArray::new(layout, self.io.block_size(), storage)
-                .map_err(|err| match err {
-                    GptPartitionEntryArrayError::BufferTooSmall => {
-                        DiskError::BufferTooSmall
-                    }
-                    GptPartitionEntryArrayError::Overflow => {
-                        DiskError::Overflow
-                    }
-                })?;
+                .map_err(entry_array_error)?;
         self.io
             .read_blocks(layout.start_lba, entry_array.storage_mut())?;
         Ok(entry_array)
     }
 
-    /// Write an entire [`GptPartitionEntryArray`] to disk.
-    pub fn write_gpt_partition_entry_array(
+    /// Read two entire partition entry arrays, usually the primary and
+    /// secondary arrays, with a single call to
+    /// [`BlockIo::read_blocks_vectored`].
+    ///
+    /// The first array is read into the beginning of `storage`, and the
+    /// second array immediately after it. The `storage` buffer must be
+    /// at least the sum of [`layout.num_bytes_rounded_to_block`] for
+    /// both layouts in size.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    pub fn read_gpt_partition_entry_arrays<'buf>(
         &mut self,
-        entry_array: &GptPartitionEntryArray,
-    ) -> Result<(), DiskError<Io::Error>> {
-        Ok(self.io.write_blocks(
-            entry_array.layout().start_lba,
-            entry_array.storage(),
-        )?)
+        layouts: [GptPartitionEntryArrayLayout; 2],
+        storage: &'buf mut [u8],
+    ) -> Result<
+        (GptPartitionEntryArray<'buf>, GptPartitionEntryArray<'buf>),
+        DiskError<Io::Error>,
+    > {
+        let block_size = self.io.block_size();
+        let first_len = layouts[0]
+            .num_bytes_rounded_to_block_as_usize(block_size)
+            .ok_or(DiskError::Overflow)?;
+        if storage.len() < first_len {
+            return Err(DiskError::BufferTooSmall);
+        }
+        let (first_storage, second_storage) = storage.split_at_mut(first_len);
+
+        let mut first =
+            GptPartitionEntryArray::new(layouts[0], block_size, first_storage)
+                .map_err(entry_array_error)?;
+        let mut second =
+            GptPartitionEntryArray::new(layouts[1], block_size, second_storage)
+                .map_err(entry_array_error)?;
+        self.io.read_blocks_vectored(&mut [
+            (layouts[0].start_lba, first.storage_mut()),
+            (layouts[1].start_lba, second.storage_mut()),
+        ])?;
+        Ok((first, second))
     }
 
     /// Get an iterator over partition entries. The `layout` parameter
@@ -307,6 +582,47 @@ impl<Io: BlockIo> Disk<Io> {
         GptPartitionEntryIter::<'disk, 'buf>::new(self, layout, block_buf)
     }
 
+    /// Read the MBR from the first block. No validation of the MBR is
+    /// performed.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn read_mbr(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<MasterBootRecord, DiskError<Io::Error>> {
+        self.read_mbr_at(Lba(0), block_buf)
+    }
+
+    /// Read an MBR-formatted block, such as an extended boot record,
+    /// at the given [`Lba`].
+    pub(crate) fn read_mbr_at(
+        &mut self,
+        lba: Lba,
+        mut block_buf: &mut [u8],
+    ) -> Result<MasterBootRecord, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        self.io.read_blocks(lba, block_buf)?;
+        let bytes = block_buf
+            .get(..mem::size_of::<MasterBootRecord>())
+            // OK to unwrap since the block size type guarantees a
+            // minimum size of 512 bytes, the size of the MBR.
+            .unwrap();
+        Ok(*from_bytes(bytes))
+    }
+}
+
+impl<Io: BlockIo> Disk<Io> {
+    /// Write an entire [`GptPartitionEntryArray`] to disk.
+    pub fn write_gpt_partition_entry_array(
+        &mut self,
+        entry_array: &GptPartitionEntryArray,
+    ) -> Result<(), DiskError<Io::Error>> {
+        Ok(self.io.write_blocks(
+            entry_array.layout().start_lba,
+            entry_array.storage(),
+        )?)
+    }
+
     /// Write a protective MBR to the first block. If the block size is
     /// bigger than the MBR, the rest of the block will be filled with
     /// zeroes.
@@ -327,25 +643,22 @@ impl<Io: BlockIo> Disk<Io> {
     pub fn write_mbr(
         &mut self,
         mbr: &MasterBootRecord,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.write_mbr_at(Lba(0), mbr, block_buf)
+    }
+
+    /// Write an MBR-formatted block, such as an extended boot record,
+    /// at the given [`Lba`].
+    pub(crate) fn write_mbr_at(
+        &mut self,
+        lba: Lba,
+        mbr: &MasterBootRecord,
         mut block_buf: &mut [u8],
     ) -> Result<(), DiskError<Io::Error>> {
         block_buf = self.clip_block_buf_size(block_buf)?;
-
-        let mbr_bytes = bytes_of(mbr);
-
-        // This should always be true because the block_buf size is
-        // already known to match the block size, and the block size is
-        // enforced to be at least 512 bytes which is the size of the
-        // MBR struct.
-        assert!(block_buf.len() >= mbr_bytes.len());
-
-        {
-            let (left, right) = block_buf.split_at_mut(mbr_bytes.len());
-            left.copy_from_slice(mbr_bytes);
-            right.fill(0);
-        }
-
-        self.io.write_blocks(Lba(0), block_buf)?;
+        fill_block(block_buf, bytes_of(mbr));
+        self.io.write_blocks(lba, block_buf)?;
         Ok(())
     }
 
@@ -399,21 +712,7 @@ impl<Io: BlockIo> Disk<Io> {
         mut block_buf: &mut [u8],
     ) -> Result<(), DiskError<Io::Error>> {
         block_buf = self.clip_block_buf_size(block_buf)?;
-
-        let header_bytes = bytes_of(header);
-
-        // This should always be true because the block_buf size is
-        // already known to match the block size, and the block size is
-        // enforced to be at least 512 bytes which is much larger than
-        // the size of the GptHeader struct.
-        assert!(block_buf.len() >= header_bytes.len());
-
-        {
-            let (left, right) = block_buf.split_at_mut(header_bytes.len());
-            left.copy_from_slice(header_bytes);
-            right.fill(0);
-        }
-
+        fill_block(block_buf, bytes_of(header));
         self.io.write_blocks(lba, block_buf)?;
         Ok(())
     }
@@ -428,9 +727,11 @@ impl<Io: BlockIo> Disk<Io> {
     }
 }
 
-impl<Io: BlockIo> Drop for Disk<Io> {
+impl<Io: BlockIo, Mode: DiskMode> Drop for Disk<Io, Mode> {
     fn drop(&mut self) {
-        // Throw away any errors.
-        let _r = self.flush();
+        if Mode::FLUSH_ON_DROP {
+            // Throw away any errors.
+            let _r = self.io.flush();
+        }
     }
 }
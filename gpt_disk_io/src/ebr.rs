@@ -0,0 +1,179 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::disk::Captures;
+use crate::{BlockIo, Disk, DiskError, DiskMode};
+use gpt_disk_types::{Lba, LbaRangeInclusive, MbrPartitionRecord};
+
+/// Logical partition in an MBR extended partition. See
+/// [`Disk::mbr_logical_partition_iter`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct MbrLogicalPartition {
+    /// Location of the extended boot record (EBR) describing the
+    /// partition.
+    pub ebr_lba: Lba,
+
+    /// Partition record from the EBR. The [`starting_lba`] is relative
+    /// to [`ebr_lba`].
+    ///
+    /// [`ebr_lba`]: Self::ebr_lba
+    /// [`starting_lba`]: MbrPartitionRecord::starting_lba
+    pub record: MbrPartitionRecord,
+
+    /// Blocks covered by the partition, as absolute LBAs.
+    pub range: LbaRangeInclusive,
+}
+
+/// Iterator over the logical partitions in an extended partition.
+struct EbrChainIter<'disk, 'buf, Io: BlockIo, Mode: DiskMode> {
+    disk: &'disk mut Disk<Io, Mode>,
+    block_buf: &'buf mut [u8],
+    extended: LbaRangeInclusive,
+
+    /// LBA of the next EBR to read, or an error found in the link to
+    /// it. `None` if iteration is complete.
+    next_ebr: Option<Result<Lba, DiskError<Io::Error>>>,
+
+    // State for Brent's cycle detection algorithm. The chain contains
+    // a loop if a link leads back to `tortoise`, which moves to the
+    // current EBR each time the number of links followed since it last
+    // moved reaches `power`.
+    tortoise: Lba,
+    power: u64,
+    links_since_tortoise: u64,
+}
+
+impl<Io: BlockIo, Mode: DiskMode> EbrChainIter<'_, '_, Io, Mode> {
+    /// Follow the link to the next EBR. The link's starting LBA is
+    /// relative to the start of the extended partition.
+    fn follow_link(
+        &mut self,
+        ebr_lba: Lba,
+        link: &MbrPartitionRecord,
+    ) -> Result<Lba, DiskError<Io::Error>> {
+        if !link.is_extended() {
+            return Err(DiskError::InvalidMbr);
+        }
+        let next_lba = link
+            .lba_range(self.extended.start())
+            .ok_or(DiskError::EbrOutOfRange { lba: ebr_lba })?
+            .start();
+        if next_lba > self.extended.end() {
+            return Err(DiskError::EbrOutOfRange { lba: ebr_lba });
+        }
+        if next_lba == self.tortoise {
+            return Err(DiskError::EbrLoop { lba: ebr_lba });
+        }
+
+        self.links_since_tortoise += 1;
+        if self.links_since_tortoise == self.power {
+            self.tortoise = next_lba;
+            self.power = self.power.saturating_mul(2);
+            self.links_since_tortoise = 0;
+        }
+        Ok(next_lba)
+    }
+
+    /// Read the EBR at `ebr_lba`, and set up the link to the next
+    /// one. Returns `None` if the EBR does not contain a partition.
+    fn read_ebr(
+        &mut self,
+        ebr_lba: Lba,
+    ) -> Result<Option<MbrLogicalPartition>, DiskError<Io::Error>> {
+        let ebr = self.disk.read_mbr_at(ebr_lba, self.block_buf)?;
+        if !ebr.is_signature_valid() {
+            return Err(DiskError::InvalidMbr);
+        }
+        let [record, link, ..] = ebr.partitions;
+
+        if link.is_used() {
+            self.next_ebr = Some(self.follow_link(ebr_lba, &link));
+        }
+
+        if !record.is_used() {
+            return Ok(None);
+        }
+        let range = record
+            .lba_range(ebr_lba)
+            .filter(|range| range.end() <= self.extended.end())
+            .ok_or(DiskError::EbrOutOfRange { lba: ebr_lba })?;
+        Ok(Some(MbrLogicalPartition {
+            ebr_lba,
+            record,
+            range,
+        }))
+    }
+}
+
+impl<Io: BlockIo, Mode: DiskMode> Iterator for EbrChainIter<'_, '_, Io, Mode> {
+    type Item = Result<MbrLogicalPartition, DiskError<Io::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let ebr_lba = match self.next_ebr.take()? {
+                Ok(ebr_lba) => ebr_lba,
+                Err(err) => return Some(Err(err)),
+            };
+            match self.read_ebr(ebr_lba) {
+                Ok(Some(partition)) => return Some(Ok(partition)),
+                Ok(None) => {}
+                Err(err) => {
+                    self.next_ebr = None;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+impl<Io: BlockIo, Mode: DiskMode> Disk<Io, Mode> {
+    /// Get an iterator over the logical partitions in an MBR extended
+    /// partition (see [`MbrPartitionRecord::is_extended`]). `extended`
+    /// is the range of the extended partition, as returned by
+    /// [`MbrPartitionRecord::lba_range`] for a record in the MBR.
+    ///
+    /// The extended partition starts with an extended boot record
+    /// (EBR). Each EBR describes one logical partition, relative to the
+    /// EBR, and links to the next EBR, relative to the start of the
+    /// extended partition. EBRs that do not describe a partition are
+    /// skipped.
+    ///
+    /// If an error occurs, it is returned as the last item. The errors
+    /// are:
+    /// * [`DiskError::InvalidMbr`] if an EBR has an invalid signature,
+    ///   or a link is not an extended partition type.
+    /// * [`DiskError::EbrOutOfRange`] if a link or logical partition is
+    ///   not inside the extended partition.
+    /// * [`DiskError::EbrLoop`] if a link leads back to an earlier EBR.
+    ///   Loops are detected with Brent's algorithm, which needs no
+    ///   memory allocation. As a result, partitions in a long loop may
+    ///   be returned more than once before the loop is detected.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn mbr_logical_partition_iter<'disk, 'buf>(
+        &'disk mut self,
+        extended: LbaRangeInclusive,
+        mut block_buf: &'buf mut [u8],
+    ) -> Result<
+        impl Iterator<Item = Result<MbrLogicalPartition, DiskError<Io::Error>>>
+            + Captures<'disk, 'buf>,
+        DiskError<Io::Error>,
+    > {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+
+        Ok(EbrChainIter {
+            disk: self,
+            block_buf,
+            extended,
+            next_ebr: Some(Ok(extended.start())),
+            tortoise: extended.start(),
+            power: 1,
+            links_since_tortoise: 0,
+        })
+    }
+}
@@ -19,9 +19,17 @@
 //! byte-oriented storage backends, such as `&mut [u8]` and `File` (the
 //! latter requires the `std` feature).
 //!
+//! For asynchronous code, [`AsyncDisk`] provides the same low-level
+//! reads and writes through the poll-based [`AsyncBlockIo`] trait, and
+//! [`GptTable::commit_async`] writes a [`GptTable`] to it. Synchronous
+//! [`BlockIo`] implementations can be used with it through
+//! [`AsyncBlockIoAdapter`].
+//!
 //! # Features
 //!
-//! * `alloc`: Enables [`Vec`] implementation of [`BlockIoAdapter`].
+//! * `alloc`: Enables [`Vec`] implementation of [`BlockIoAdapter`],
+//!   [`OverlayBlockIo`], [`FaultInjectingBlockIo`], and
+//!   [`Disk::verify_gpt`].
 //! * `std`: Enables [`std::io`] implementations of [`BlockIoAdapter`],
 //!   as well as `std::error::Error` implementations for all of the
 //!   error types. Off by default.
@@ -134,17 +142,60 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+mod async_block_io;
+mod async_disk;
 mod block_io;
+mod builder;
 mod disk;
+mod ebr;
+mod mbr;
+mod mbr_verify;
+mod partition_move;
+mod probe;
+mod repair;
+mod resize;
 #[cfg(feature = "std")]
 mod std_support;
+mod table;
+mod transaction;
+mod verify;
 
 // Re-export dependencies.
 pub use gpt_disk_types;
 
+pub use async_block_io::{AsyncBlockIo, AsyncBlockIoAdapter};
+pub use async_disk::{AsyncDisk, AsyncGptPartitionEntryIter};
+pub use block_io::cached_block_io::{CacheSlot, CacheWriteMode, CachedBlockIo};
+pub use block_io::partition_block_io::{
+    PartitionBlockIo, PartitionBlockIoError,
+};
+pub use block_io::read_only_block_io::{ReadOnlyBlockIo, ReadOnlyBlockIoError};
 pub use block_io::slice_block_io::SliceBlockIoError;
 pub use block_io::{BlockIo, BlockIoAdapter};
-pub use disk::{Disk, DiskError};
+pub use builder::GptBuilder;
+pub use disk::{Disk, DiskError, DiskMode, ReadOnly, ReadWrite};
+pub use ebr::MbrLogicalPartition;
+pub use mbr_verify::{MbrVerifyFinding, MbrVerifyIssue, PartitionScheme};
+pub use partition_move::GptPartitionMoveProgress;
+pub use probe::{BlockSizeConfidence, BlockSizeProbe};
+pub use repair::GptRepairSummary;
+pub use table::{GptTable, GptTableError};
+pub use transaction::{GptCommitIntent, GptCommitPhase, GptCommitRecovery};
+pub use verify::{
+    GptHeaderCopy, GptHeaderField, GptVerifyFinding, GptVerifyIssue,
+    VerifySeverity,
+};
+
+#[cfg(feature = "alloc")]
+pub use block_io::fault_injecting_block_io::{
+    BlockIoEvent, FaultInjectingBlockIo, FaultInjectingBlockIoError,
+};
+#[cfg(feature = "alloc")]
+pub use block_io::overlay_block_io::{
+    OverlayBlockIo, OverlayBlockIoError, OverlayCommitOrder,
+};
+#[cfg(feature = "alloc")]
+pub use verify::GptVerifyReport;
 
 #[cfg(feature = "std")]
 pub use block_io::std_block_io::ReadWriteSeek;
@@ -0,0 +1,563 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{BlockIo, Disk, DiskError, GptBuilder, GptTable, GptTableError};
+use gpt_disk_types::{
+    Chs, DiskGeometry, GptHeader, GptPartitionAttributes, GptPartitionEntry,
+    GptPartitionEntryArray, GptPartitionEntryArrayError, GptPartitionName,
+    GptPartitionType, Guid, Lba, LbaRangeInclusive, MasterBootRecord, MbrKind,
+    MbrPartitionRecord, U32Le,
+};
+
+/// Index of the first logical partition in a GPT converted from an
+/// MBR. Primary partitions keep their index, and logical partitions
+/// follow them, matching the partition numbers used by Linux.
+const FIRST_LOGICAL_INDEX: u32 = 4;
+
+/// MBR `os_indicator` of an extended partition that uses LBA
+/// addressing.
+const EXTENDED_OS_INDICATOR: u8 = 0x0f;
+
+/// `os_indicator` of the link to the next extended boot record.
+const EBR_LINK_OS_INDICATOR: u8 = 0x05;
+
+/// Number of partition records in an MBR.
+const MAX_PRIMARY_PARTITIONS: usize = 4;
+
+/// CHS value used for LBAs that are too large for CHS addressing.
+const CHS_OUT_OF_RANGE: Chs = Chs([0xff, 0xff, 0xff]);
+
+/// Create an MBR partition record covering `range`, with the starting
+/// LBA relative to `base`. Returns `None` if the record's fields
+/// cannot hold the range.
+fn create_mbr_record(
+    os_indicator: u8,
+    bootable: bool,
+    range: LbaRangeInclusive,
+    base: Lba,
+) -> Option<MbrPartitionRecord> {
+    let starting_lba = range.start().to_u64().checked_sub(base.to_u64())?;
+    let chs = |lba| {
+        Chs::from_lba(lba, DiskGeometry::UNKNOWN).unwrap_or(CHS_OUT_OF_RANGE)
+    };
+    Some(MbrPartitionRecord {
+        boot_indicator: if bootable {
+            MbrPartitionRecord::BOOTABLE_BOOT_INDICATOR
+        } else {
+            0
+        },
+        start_chs: chs(range.start()),
+        os_indicator,
+        end_chs: chs(range.end()),
+        starting_lba: U32Le::from_u32(u32::try_from(starting_lba).ok()?),
+        size_in_lba: U32Le::from_u32(u32::try_from(range.num_blocks()).ok()?),
+    })
+}
+
+/// Create an MBR partition record for a GPT partition entry. Returns
+/// `None` if the entry cannot be represented in an MBR.
+fn entry_to_mbr_record(
+    entry: &GptPartitionEntry,
+    base: Lba,
+) -> Option<MbrPartitionRecord> {
+    let range = entry.lba_range()?;
+    // MBR partitions must end within the 32-bit LBA range.
+    u32::try_from(range.end().to_u64()).ok()?;
+    create_mbr_record(
+        entry.partition_type_guid.to_mbr_os_indicator()?,
+        entry.attributes.legacy_bios_bootable(),
+        range,
+        base,
+    )
+}
+
+/// Iterator over the used entries of a [`GptTable`] in order of
+/// starting LBA. Entries with the same starting LBA are ordered by
+/// index. Entries with an invalid range are skipped.
+struct EntriesByStart<'table, 'buf> {
+    table: &'table GptTable<'buf>,
+    /// Starting LBA and index of the previous entry.
+    prev: Option<(u64, u32)>,
+}
+
+impl<'table, 'buf> EntriesByStart<'table, 'buf> {
+    fn new(table: &'table GptTable<'buf>) -> Self {
+        Self { table, prev: None }
+    }
+}
+
+impl Iterator for EntriesByStart<'_, '_> {
+    type Item = (u32, GptPartitionEntry, LbaRangeInclusive);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut next: Option<Self::Item> = None;
+        for index in 0..self.table.num_entries() {
+            // OK to unwrap: the index is within the array.
+            let entry = self.table.get_partition_entry(index).unwrap();
+            if !entry.is_used() {
+                continue;
+            }
+            let Some(range) = entry.lba_range() else {
+                continue;
+            };
+            let key = (range.start().to_u64(), index);
+            if self.prev.map_or(false, |prev| key <= prev) {
+                continue;
+            }
+            if next.map_or(true, |(next_index, _, next_range)| {
+                key < (next_range.start().to_u64(), next_index)
+            }) {
+                next = Some((index, *entry, range));
+            }
+        }
+        let (index, _, range) = next?;
+        self.prev = Some((range.start().to_u64(), index));
+        next
+    }
+}
+
+/// Create a partition entry from an MBR partition record covering
+/// `range`, and store it at `index` in the table.
+fn insert_mbr_partition(
+    table: &mut GptTable,
+    index: u32,
+    record: &MbrPartitionRecord,
+    range: LbaRangeInclusive,
+    unique_partition_guid: Guid,
+) -> Result<(), GptTableError> {
+    table.check_range(range, None)?;
+
+    let mut attributes = GptPartitionAttributes::default();
+    attributes.update_legacy_bios_bootable(
+        record.boot_indicator == MbrPartitionRecord::BOOTABLE_BOOT_INDICATOR,
+    );
+    *table
+        .entry_array
+        .get_partition_entry_mut(index)
+        .ok_or(GptTableError::NoUnusedEntry)? = GptPartitionEntry {
+        partition_type_guid: GptPartitionType::from_mbr_os_indicator(
+            record.os_indicator,
+        )
+        .unwrap_or(GptPartitionType::BASIC_DATA),
+        unique_partition_guid,
+        starting_lba: range.start().into(),
+        ending_lba: range.end().into(),
+        attributes,
+        name: GptPartitionName::default(),
+    };
+    Ok(())
+}
+
+impl<Io: BlockIo> Disk<Io> {
+    /// Add the logical partitions in the `extended` partition to
+    /// `table`, starting at entry `*next_index`.
+    fn convert_logical_partitions(
+        &mut self,
+        table: &mut GptTable,
+        extended: LbaRangeInclusive,
+        next_index: &mut u32,
+        new_guid: &mut impl FnMut() -> Guid,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        for logical in self.mbr_logical_partition_iter(extended, block_buf)? {
+            let logical = logical?;
+            insert_mbr_partition(
+                table,
+                *next_index,
+                &logical.record,
+                logical.range,
+                new_guid(),
+            )
+            .map_err(DiskError::Table)?;
+            *next_index += 1;
+        }
+        Ok(())
+    }
+
+    /// Convert an MBR-partitioned disk to GPT in place, like the
+    /// conversion performed by `gdisk` when it loads an MBR disk. The
+    /// new primary header is returned.
+    ///
+    /// Primary MBR partitions keep their index in the partition entry
+    /// array (so partition 1 stays at index 0), and logical partitions
+    /// in an extended partition start at index 4. Partition types are
+    /// converted with [`GptPartitionType::from_mbr_os_indicator`];
+    /// types without a known equivalent become
+    /// [`GptPartitionType::BASIC_DATA`]. Partitions marked bootable in
+    /// the MBR get the legacy BIOS bootable attribute. `new_guid` is
+    /// called to get the unique GUID of each partition, and the disk
+    /// GUID and partition entry array layout come from `builder`.
+    ///
+    /// The GPT needs the blocks after the MBR for the primary header
+    /// and partition entry array, and the blocks at the end of the disk
+    /// for the secondary copies. These blocks must not be used by any
+    /// partition, otherwise a [`DiskError::Table`] error with
+    /// [`GptTableError::OutsideUsableRange`] is returned. Extended boot
+    /// records in those blocks are not a problem, since they are no
+    /// longer needed after the conversion. See
+    /// [`GptBuilder::num_reserved_blocks`] for how many blocks are
+    /// needed.
+    ///
+    /// Returns [`DiskError::InvalidMbr`] if the MBR or an extended boot
+    /// record has an invalid signature, if the extended partition chain
+    /// is invalid, or if the MBR is a protective MBR (meaning the disk
+    /// already has a GPT). Nothing is written if an error is found in
+    /// the MBR.
+    ///
+    /// The GPT is written with [`GptTable::commit_transactional`] before
+    /// the protective MBR replaces the old MBR, so the old MBR stays
+    /// valid until the GPT has been flushed to the disk.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// one block. The `storage` buffer must be at least
+    /// [`layout.num_bytes_rounded_to_block`] in size, where `layout` is
+    /// [`builder.partition_entry_array_layout()`].
+    ///
+    /// [`builder.partition_entry_array_layout()`]: GptBuilder::partition_entry_array_layout
+    /// [`layout.num_bytes_rounded_to_block`]: gpt_disk_types::GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    pub fn convert_mbr_to_gpt(
+        &mut self,
+        builder: &GptBuilder,
+        mut new_guid: impl FnMut() -> Guid,
+        mut block_buf: &mut [u8],
+        storage: &mut [u8],
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        let mbr = self.read_mbr(block_buf)?;
+        if mbr.kind() != Some(MbrKind::Legacy) {
+            return Err(DiskError::InvalidMbr);
+        }
+
+        let block_size = self.io.block_size();
+        let (header, _) = builder
+            .build_headers(block_size, self.io.num_blocks()?)
+            .ok_or(DiskError::DiskTooSmall)?;
+        storage.fill(0);
+        let entry_array = GptPartitionEntryArray::new(
+            builder.partition_entry_array_layout(),
+            block_size,
+            storage,
+        )
+        .map_err(|err| match err {
+            GptPartitionEntryArrayError::BufferTooSmall => {
+                DiskError::BufferTooSmall
+            }
+            GptPartitionEntryArrayError::Overflow => DiskError::Overflow,
+        })?;
+        let mut table = GptTable {
+            header,
+            entry_array,
+        };
+
+        let mut next_logical_index = FIRST_LOGICAL_INDEX;
+        for (index, record) in (0..).zip(mbr.partitions.iter()) {
+            if !record.is_used() {
+                continue;
+            }
+            let range = record.lba_range(Lba(0)).ok_or(DiskError::Overflow)?;
+            if record.is_extended() {
+                self.convert_logical_partitions(
+                    &mut table,
+                    range,
+                    &mut next_logical_index,
+                    &mut new_guid,
+                    block_buf,
+                )?;
+            } else {
+                insert_mbr_partition(
+                    &mut table,
+                    index,
+                    record,
+                    range,
+                    new_guid(),
+                )
+                .map_err(DiskError::Table)?;
+            }
+        }
+
+        table.commit_transactional(self, None, block_buf)?;
+        self.write_protective_mbr(block_buf)?;
+        Ok(table.header)
+    }
+
+    /// Check that every used partition in `table` can be represented
+    /// in an MBR. Returns the number of used partitions.
+    fn check_mbr_compatible(
+        table: &GptTable,
+    ) -> Result<usize, DiskError<Io::Error>> {
+        let num_partitions = EntriesByStart::new(table).count();
+        let mut prev_end = None;
+        for (n, (index, entry, range)) in EntriesByStart::new(table).enumerate()
+        {
+            let error = || DiskError::PartitionNotMbrCompatible { index };
+            entry_to_mbr_record(&entry, Lba(0)).ok_or_else(error)?;
+
+            // Each logical partition needs a free block immediately
+            // before it for its extended boot record.
+            if num_partitions > MAX_PRIMARY_PARTITIONS
+                && n >= MAX_PRIMARY_PARTITIONS - 1
+                && prev_end >= range.start().to_u64().checked_sub(1)
+            {
+                return Err(error());
+            }
+            prev_end = Some(range.end().to_u64());
+        }
+        Ok(num_partitions)
+    }
+
+    /// Write the extended boot records (EBRs) for the logical
+    /// partitions, which are the partitions after the first `skip` in
+    /// order of starting LBA. Each EBR is placed in the block before
+    /// its logical partition. Returns the MBR record for the extended
+    /// partition.
+    fn write_extended_boot_records(
+        &mut self,
+        table: &GptTable,
+        skip: usize,
+        block_buf: &mut [u8],
+    ) -> Result<MbrPartitionRecord, DiskError<Io::Error>> {
+        // Get the range from the EBR to the end of a logical partition.
+        let ebr_range = |range: LbaRangeInclusive| {
+            LbaRangeInclusive::new(Lba(range.start().to_u64() - 1), range.end())
+        };
+
+        let mut logicals = EntriesByStart::new(table).skip(skip).peekable();
+        let first = logicals.peek().ok_or(DiskError::Overflow)?.2;
+        let extended_start = Lba(first.start().to_u64() - 1);
+        let mut extended_end = first.end();
+        while let Some((index, entry, range)) = logicals.next() {
+            let error = || DiskError::PartitionNotMbrCompatible { index };
+            let ebr_lba = Lba(range.start().to_u64() - 1);
+            let logical =
+                entry_to_mbr_record(&entry, ebr_lba).ok_or_else(error)?;
+            let link = if let Some((_, _, next_range)) = logicals.peek() {
+                ebr_range(*next_range)
+                    .and_then(|range| {
+                        create_mbr_record(
+                            EBR_LINK_OS_INDICATOR,
+                            false,
+                            range,
+                            extended_start,
+                        )
+                    })
+                    .ok_or_else(error)?
+            } else {
+                MbrPartitionRecord::default()
+            };
+            extended_end = range.end();
+
+            let ebr = MasterBootRecord {
+                boot_strap_code: [0; 440],
+                unique_mbr_disk_signature: [0; 4],
+                unknown: [0; 2],
+                partitions: [
+                    logical,
+                    link,
+                    MbrPartitionRecord::default(),
+                    MbrPartitionRecord::default(),
+                ],
+                signature: [0x55, 0xaa],
+            };
+            self.write_mbr_at(ebr_lba, &ebr, block_buf)?;
+        }
+
+        LbaRangeInclusive::new(extended_start, extended_end)
+            .and_then(|range| {
+                create_mbr_record(EXTENDED_OS_INDICATOR, false, range, Lba(0))
+            })
+            .ok_or(DiskError::Overflow)
+    }
+
+    /// Write an MBR with the given partition records. The boot strap
+    /// code and disk signature of the existing MBR are kept.
+    fn replace_mbr_partitions(
+        &mut self,
+        partitions: [MbrPartitionRecord; 4],
+        block_buf: &mut [u8],
+    ) -> Result<MasterBootRecord, DiskError<Io::Error>> {
+        let old_mbr = self.read_mbr(block_buf)?;
+        let mbr = MasterBootRecord {
+            boot_strap_code: old_mbr.boot_strap_code,
+            unique_mbr_disk_signature: old_mbr.unique_mbr_disk_signature,
+            unknown: [0; 2],
+            partitions,
+            signature: [0x55, 0xaa],
+        };
+        self.write_mbr(&mbr, block_buf)?;
+        Ok(mbr)
+    }
+
+    /// Zero out both GPT headers and partition entry arrays.
+    fn wipe_gpt(
+        &mut self,
+        table: &GptTable,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let secondary = table
+            .secondary_header(self.io.block_size())
+            .ok_or(DiskError::Overflow)?;
+        let array_blocks = table
+            .entry_array
+            .layout()
+            .num_blocks(self.io.block_size())
+            .ok_or(DiskError::Overflow)?;
+
+        block_buf.fill(0);
+        for header in [&table.header, &secondary] {
+            let array_lba = header.partition_entry_lba.to_u64();
+            for lba in array_lba..array_lba + array_blocks {
+                self.io.write_blocks(Lba(lba), block_buf)?;
+            }
+            self.io.write_blocks(header.my_lba.into(), block_buf)?;
+        }
+        Ok(())
+    }
+
+    /// Convert a GPT disk to MBR in place, if the layout allows it. The
+    /// new MBR is returned.
+    ///
+    /// The GPT is read with [`read_gpt_table`]. If there are at most
+    /// four used partitions, each becomes a primary MBR partition.
+    /// Otherwise the first three partitions (in order of starting LBA)
+    /// are primary partitions, and the rest become logical partitions
+    /// in an extended partition. Each logical partition needs a free
+    /// block immediately before it to hold its extended boot record.
+    ///
+    /// Partition types are converted with
+    /// [`GptPartitionType::to_mbr_os_indicator`], and partitions with
+    /// the legacy BIOS bootable attribute are marked bootable. CHS
+    /// values are calculated with [`Chs::from_lba`] using
+    /// [`DiskGeometry::UNKNOWN`]. The boot strap code and disk
+    /// signature of the existing MBR are kept.
+    ///
+    /// Returns [`DiskError::PartitionNotMbrCompatible`] if a partition
+    /// has a type with no MBR equivalent, does not fit within 32-bit
+    /// LBAs, or has no room for its extended boot record. Nothing is
+    /// written in that case.
+    ///
+    /// If `wipe_gpt` is true, both GPT headers and partition entry
+    /// arrays are zeroed after the MBR is written. Otherwise they are
+    /// left in place, and tools that look for a GPT may still find
+    /// one.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// one block. The `storage` buffer must be at least
+    /// [`layout.num_bytes_rounded_to_block`] in size, where `layout` is
+    /// the primary header's partition entry array layout.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: gpt_disk_types::GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    /// [`read_gpt_table`]: Self::read_gpt_table
+    pub fn convert_gpt_to_mbr(
+        &mut self,
+        wipe_gpt: bool,
+        mut block_buf: &mut [u8],
+        storage: &mut [u8],
+    ) -> Result<MasterBootRecord, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        let table = self.read_gpt_table(block_buf, storage)?;
+        let num_partitions = Self::check_mbr_compatible(&table)?;
+
+        let num_primary = if num_partitions > MAX_PRIMARY_PARTITIONS {
+            MAX_PRIMARY_PARTITIONS - 1
+        } else {
+            num_partitions
+        };
+        let mut partitions = [MbrPartitionRecord::default(); 4];
+        for (record, (index, entry, _)) in partitions
+            .iter_mut()
+            .zip(EntriesByStart::new(&table))
+            .take(num_primary)
+        {
+            *record = entry_to_mbr_record(&entry, Lba(0))
+                .ok_or(DiskError::PartitionNotMbrCompatible { index })?;
+        }
+        if num_partitions > num_primary {
+            partitions[num_primary] = self.write_extended_boot_records(
+                &table,
+                num_primary,
+                block_buf,
+            )?;
+        }
+
+        let mbr = self.replace_mbr_partitions(partitions, block_buf)?;
+        if wipe_gpt {
+            self.wipe_gpt(&table, block_buf)?;
+        }
+        Ok(mbr)
+    }
+
+    /// Write a hybrid MBR that mirrors up to three GPT partitions. The
+    /// new MBR is returned.
+    ///
+    /// The first partition record is a protective partition (see
+    /// [`MasterBootRecord::PROTECTIVE_OS_TYPE`]) covering the primary
+    /// header and partition entry array, from LBA 1 to the block before
+    /// the first usable LBA. The partitions at `indices` in the GPT
+    /// follow it, in the order given. Partition types are converted
+    /// with [`GptPartitionType::to_mbr_os_indicator`], and partitions
+    /// with the legacy BIOS bootable attribute are marked bootable. The
+    /// boot strap code and disk signature of the existing MBR are kept.
+    ///
+    /// The GPT itself is not changed. A hybrid MBR is not valid
+    /// according to the UEFI Specification, but some firmware requires
+    /// one.
+    ///
+    /// Returns [`DiskError::PartitionNotMbrCompatible`] if a partition
+    /// cannot be represented in an MBR, or if more than three indices
+    /// are given. Returns [`DiskError::Table`] if an index does not
+    /// refer to a used partition entry. Nothing is written in either
+    /// case.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// one block. The `storage` buffer must be at least
+    /// [`layout.num_bytes_rounded_to_block`] in size, where `layout` is
+    /// the primary header's partition entry array layout.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: gpt_disk_types::GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    pub fn write_hybrid_mbr(
+        &mut self,
+        indices: &[u32],
+        mut block_buf: &mut [u8],
+        storage: &mut [u8],
+    ) -> Result<MasterBootRecord, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        let table = self.read_gpt_table(block_buf, storage)?;
+
+        let mut partitions = [MbrPartitionRecord::default(); 4];
+        let protective_end = table
+            .header
+            .first_usable_lba
+            .to_u64()
+            .checked_sub(1)
+            .ok_or(DiskError::InvalidGptHeader)?;
+        partitions[0] = LbaRangeInclusive::new(Lba(1), Lba(protective_end))
+            .and_then(|range| {
+                create_mbr_record(
+                    MasterBootRecord::PROTECTIVE_OS_TYPE,
+                    false,
+                    range,
+                    Lba(0),
+                )
+            })
+            .ok_or(DiskError::InvalidGptHeader)?;
+
+        for (n, index) in indices.iter().copied().enumerate() {
+            let error = || DiskError::PartitionNotMbrCompatible { index };
+            let record = partitions.get_mut(n + 1).ok_or_else(error)?;
+            let entry = table
+                .get_partition_entry(index)
+                .ok_or(DiskError::Table(GptTableError::IndexOutOfRange))?;
+            if !entry.is_used() {
+                return Err(DiskError::Table(GptTableError::EntryNotUsed));
+            }
+            *record = entry_to_mbr_record(entry, Lba(0)).ok_or_else(error)?;
+        }
+
+        self.replace_mbr_partitions(partitions, block_buf)
+    }
+}
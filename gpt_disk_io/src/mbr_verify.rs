@@ -0,0 +1,327 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::verify::is_protective_size_valid;
+use crate::{BlockIo, Disk, DiskError, DiskMode, VerifySeverity};
+use core::fmt::{self, Display, Formatter};
+use gpt_disk_types::{
+    Chs, DiskGeometry, GptHeader, Lba, MasterBootRecord, MbrKind,
+    MbrPartitionRecord,
+};
+
+/// Cylinder value used in a CHS address when the LBA is too large to
+/// be represented.
+const MAX_CYLINDER: u16 = 1023;
+
+/// Problem found by [`Disk::verify_mbr_with`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum MbrVerifyIssue {
+    /// The MBR signature is not `0x55, 0xaa`. If the signature is
+    /// invalid, no other checks are done.
+    InvalidSignature,
+
+    /// A used partition record extends past the end of the disk.
+    PartitionOutsideDisk {
+        /// Index of the MBR partition record.
+        index: usize,
+    },
+
+    /// Two used partition records overlap.
+    PartitionOverlap {
+        /// Index of the first MBR partition record.
+        index: usize,
+
+        /// Index of the second MBR partition record.
+        other_index: usize,
+    },
+
+    /// More than one used partition record is marked as bootable.
+    /// Reported for each bootable record after the first.
+    MultipleBootablePartitions {
+        /// Index of the MBR partition record.
+        index: usize,
+    },
+
+    /// The CHS start or end address of a used partition record does
+    /// not match its LBA range for the given [`DiskGeometry`].
+    ChsMismatch {
+        /// Index of the MBR partition record.
+        index: usize,
+    },
+}
+
+impl Display for MbrVerifyIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSignature => f.write_str("invalid MBR signature"),
+            Self::PartitionOutsideDisk { index } => {
+                write!(f, "MBR partition {index}: extends past end of disk")
+            }
+            Self::PartitionOverlap { index, other_index } => {
+                write!(
+                    f,
+                    "MBR partition {index} overlaps MBR partition \
+                     {other_index}"
+                )
+            }
+            Self::MultipleBootablePartitions { index } => {
+                write!(
+                    f,
+                    "MBR partition {index}: another partition is already \
+                     bootable"
+                )
+            }
+            Self::ChsMismatch { index } => {
+                write!(f, "MBR partition {index}: CHS does not match LBA")
+            }
+        }
+    }
+}
+
+/// A single result of MBR verification. See [`Disk::verify_mbr_with`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct MbrVerifyFinding {
+    /// How severe the problem is.
+    pub severity: VerifySeverity,
+
+    /// What the problem is.
+    pub issue: MbrVerifyIssue,
+}
+
+impl MbrVerifyFinding {
+    fn error(issue: MbrVerifyIssue) -> Self {
+        Self {
+            severity: VerifySeverity::Error,
+            issue,
+        }
+    }
+
+    fn warning(issue: MbrVerifyIssue) -> Self {
+        Self {
+            severity: VerifySeverity::Warning,
+            issue,
+        }
+    }
+}
+
+impl Display for MbrVerifyFinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.issue)
+    }
+}
+
+/// How a disk is partitioned. See [`Disk::detect_partition_scheme`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum PartitionScheme {
+    /// The disk has a legacy MBR ([`MbrKind::Legacy`]). Any GPT headers
+    /// on the disk are ignored, as UEFI firmware does.
+    Mbr,
+
+    /// The disk has a valid GPT header and a protective or hybrid MBR.
+    Gpt,
+
+    /// The disk has a valid GPT header, but the MBR has an invalid
+    /// signature, or its protective partition does not start at LBA 1.
+    /// In a protective MBR that is not hybrid, this is also the case if
+    /// the protective partition does not cover the rest of the disk.
+    /// Most tools will still treat the disk as GPT, but the protective
+    /// MBR should be rewritten.
+    GptWithInvalidProtectiveMbr,
+
+    /// The disk has neither a legacy MBR nor a valid GPT header.
+    Unknown,
+}
+
+impl Display for PartitionScheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mbr => f.write_str("MBR"),
+            Self::Gpt => f.write_str("GPT"),
+            Self::GptWithInvalidProtectiveMbr => {
+                f.write_str("GPT with invalid protective MBR")
+            }
+            Self::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+/// Check that the CHS addresses of `record` match its LBA range.
+fn is_chs_valid(record: &MbrPartitionRecord, geometry: DiskGeometry) -> bool {
+    let Some(range) = record.lba_range(Lba(0)) else {
+        return false;
+    };
+    let matches = |chs: Chs, lba| match Chs::from_lba(lba, geometry) {
+        Some(expected) => chs == expected,
+        // Addresses that cannot be represented are conventionally set
+        // to the maximum cylinder.
+        None => chs.cylinder() == MAX_CYLINDER,
+    };
+    matches(record.start_chs, range.start())
+        && matches(record.end_chs, range.end())
+}
+
+/// Check the partition records of `mbr` for a disk with `num_blocks`
+/// blocks.
+fn check_mbr(
+    mbr: &MasterBootRecord,
+    num_blocks: u64,
+    geometry: DiskGeometry,
+    on_finding: &mut dyn FnMut(MbrVerifyFinding),
+) {
+    if !mbr.is_signature_valid() {
+        on_finding(MbrVerifyFinding::error(MbrVerifyIssue::InvalidSignature));
+        return;
+    }
+
+    let mut found_bootable = false;
+    for (index, record) in mbr.partitions.iter().enumerate() {
+        if !record.is_used() {
+            continue;
+        }
+
+        if record.boot_indicator == MbrPartitionRecord::BOOTABLE_BOOT_INDICATOR
+        {
+            if found_bootable {
+                on_finding(MbrVerifyFinding::error(
+                    MbrVerifyIssue::MultipleBootablePartitions { index },
+                ));
+            }
+            found_bootable = true;
+        }
+
+        // OK to unwrap: the size is non-zero, and a `u32` offset from
+        // zero cannot overflow.
+        let range = record.lba_range(Lba(0)).unwrap();
+
+        if range.end().to_u64() >= num_blocks {
+            on_finding(MbrVerifyFinding::error(
+                MbrVerifyIssue::PartitionOutsideDisk { index },
+            ));
+        }
+
+        if !is_chs_valid(record, geometry) {
+            on_finding(MbrVerifyFinding::warning(
+                MbrVerifyIssue::ChsMismatch { index },
+            ));
+        }
+
+        for (other_index, other) in
+            mbr.partitions.iter().enumerate().skip(index + 1)
+        {
+            let Some(other_range) = other.lba_range(Lba(0)) else {
+                continue;
+            };
+            if other.is_used()
+                && range.start() <= other_range.end()
+                && other_range.start() <= range.end()
+            {
+                on_finding(MbrVerifyFinding::error(
+                    MbrVerifyIssue::PartitionOverlap { index, other_index },
+                ));
+            }
+        }
+    }
+}
+
+/// Check that the protective partition records of `mbr` start at LBA 1
+/// and, unless the MBR is hybrid, cover the rest of a disk with
+/// `num_blocks` blocks.
+fn is_protective_mbr_valid(mbr: &MasterBootRecord, num_blocks: u64) -> bool {
+    let is_hybrid = mbr.kind() == Some(MbrKind::Hybrid);
+    mbr.partitions
+        .iter()
+        .filter(|record| {
+            record.os_indicator == MasterBootRecord::PROTECTIVE_OS_TYPE
+        })
+        .all(|record| {
+            record.starting_lba.to_u32() == 1
+                && (is_hybrid || is_protective_size_valid(record, num_blocks))
+        })
+}
+
+/// Check a GPT header's signature and checksum.
+fn is_header_valid(header: &GptHeader) -> bool {
+    header.is_signature_valid()
+        && header.header_crc32 == header.calculate_header_crc32()
+}
+
+impl<Io: BlockIo, Mode: DiskMode> Disk<Io, Mode> {
+    /// Verify the MBR in the first block of the disk. Each problem
+    /// found is passed to `on_finding`; if no problems are found,
+    /// `on_finding` is never called.
+    ///
+    /// The following are checked:
+    /// * The MBR signature.
+    /// * Used partition records must be within the disk and must not
+    ///   overlap.
+    /// * At most one partition record may be marked bootable.
+    /// * The CHS addresses of used partition records should match
+    ///   their LBA ranges when converted with `geometry`. If the
+    ///   disk's geometry is not known, use [`DiskGeometry::UNKNOWN`].
+    ///
+    /// Logical partitions in an extended partition are not checked;
+    /// see [`mbr_logical_partition_iter`]. For a GPT disk, use
+    /// [`verify_gpt_with`] to check the protective MBR instead.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`mbr_logical_partition_iter`]: Self::mbr_logical_partition_iter
+    /// [`verify_gpt_with`]: Self::verify_gpt_with
+    pub fn verify_mbr_with(
+        &mut self,
+        geometry: DiskGeometry,
+        block_buf: &mut [u8],
+        mut on_finding: impl FnMut(MbrVerifyFinding),
+    ) -> Result<(), DiskError<Io::Error>> {
+        let mbr = self.read_mbr(block_buf)?;
+        let num_blocks = self.io.num_blocks()?;
+        check_mbr(&mbr, num_blocks, geometry, &mut on_finding);
+        Ok(())
+    }
+
+    /// Determine whether the disk is partitioned with an MBR or a GPT.
+    ///
+    /// A GPT header is considered valid if its signature and checksum
+    /// are valid. The primary header is checked first, then the
+    /// secondary header in the last block. A disk with a valid GPT
+    /// header but a legacy MBR is classified as [`PartitionScheme::Mbr`],
+    /// following section 5.2.3 "Protective MBR" of the UEFI
+    /// Specification.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn detect_partition_scheme(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<PartitionScheme, DiskError<Io::Error>> {
+        let mbr = self.read_mbr(block_buf)?;
+        let mbr_kind = mbr.kind();
+        if mbr_kind == Some(MbrKind::Legacy) {
+            return Ok(PartitionScheme::Mbr);
+        }
+
+        let num_blocks = self.io.num_blocks()?;
+        let is_mbr_valid =
+            mbr_kind.is_some() && is_protective_mbr_valid(&mbr, num_blocks);
+        let mut has_gpt = false;
+        for lba in [Lba(1), Lba(num_blocks.saturating_sub(1))] {
+            if lba.to_u64() >= num_blocks {
+                break;
+            }
+            if is_header_valid(&self.read_gpt_header(lba, block_buf)?) {
+                has_gpt = true;
+                break;
+            }
+        }
+
+        Ok(match (has_gpt, is_mbr_valid) {
+            (true, true) => PartitionScheme::Gpt,
+            (true, false) => PartitionScheme::GptWithInvalidProtectiveMbr,
+            (false, _) => PartitionScheme::Unknown,
+        })
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{BlockIo, Disk, DiskError, GptTable, GptTableError};
+use gpt_disk_types::{Lba, LbaRangeInclusive};
+
+/// Progress of a partition move. See [`Disk::move_partition`].
+///
+/// The progress is passed to a callback after each chunk is copied. It
+/// can be stored persistently and passed to
+/// [`Disk::resume_partition_move`] to continue a move that was
+/// interrupted, for example by a power failure.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct GptPartitionMoveProgress {
+    /// Index of the partition entry.
+    pub index: u32,
+
+    /// Blocks covered by the partition before the move.
+    pub source: LbaRangeInclusive,
+
+    /// First block of the partition after the move.
+    pub destination_start: Lba,
+
+    /// Number of blocks copied so far. If the partition is moving to a
+    /// higher LBA, blocks are copied starting from the end of the
+    /// partition; otherwise they are copied starting from the beginning.
+    pub blocks_copied: u64,
+}
+
+impl GptPartitionMoveProgress {
+    /// Total number of blocks to copy.
+    #[must_use]
+    pub fn num_blocks(&self) -> u64 {
+        self.source.num_blocks()
+    }
+
+    /// True if all blocks have been copied.
+    #[must_use]
+    pub fn is_copy_complete(&self) -> bool {
+        self.blocks_copied >= self.num_blocks()
+    }
+
+    /// Get the blocks covered by the partition after the move. Returns
+    /// `None` if overflow occurs.
+    #[must_use]
+    pub fn destination(&self) -> Option<LbaRangeInclusive> {
+        let end = self
+            .destination_start
+            .to_u64()
+            .checked_add(self.num_blocks() - 1)?;
+        LbaRangeInclusive::new(self.destination_start, Lba(end))
+    }
+}
+
+impl<Io: BlockIo> Disk<Io> {
+    /// Move the partition at `index` so that it starts at `new_start`.
+    /// The partition's data is copied, then the partition entry is
+    /// updated and both copies of the GPT are written with new
+    /// checksums by [`GptTable::commit_transactional`], so that an
+    /// interruption leaves at least one intact copy. The new range of
+    /// the partition is returned.
+    ///
+    /// Data is copied in chunks the size of `chunk_buf` (rounded down to
+    /// a whole number of blocks). The source and destination may
+    /// overlap; the copy runs in whichever direction avoids overwriting
+    /// source blocks that have not yet been copied. Chunks are also
+    /// limited to the distance of the move, so that if the copy is
+    /// interrupted, repeating the last chunk is always safe.
+    ///
+    /// `on_progress` is called after each chunk is copied. The disk is
+    /// flushed before each call, so the reported progress never gets
+    /// ahead of the data that has reached the device. To survive an
+    /// interruption, store the progress persistently (outside the
+    /// partition being moved) and pass it to
+    /// [`resume_partition_move`]. The partition entry is not changed
+    /// until the copy is complete.
+    ///
+    /// The new range must be within the usable range of the disk and
+    /// must not overlap another partition. If not, a
+    /// [`DiskError::Table`] error is returned and nothing is written.
+    ///
+    /// `chunk_buf` is a mutable byte buffer with a length of at least
+    /// one block; it is also used for reading and writing the GPT. The
+    /// `storage` buffer must be at least
+    /// [`layout.num_bytes_rounded_to_block`] in size, where `layout` is
+    /// the primary header's partition entry array layout.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: gpt_disk_types::GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    /// [`resume_partition_move`]: Self::resume_partition_move
+    pub fn move_partition(
+        &mut self,
+        index: u32,
+        new_start: Lba,
+        chunk_buf: &mut [u8],
+        storage: &mut [u8],
+        on_progress: impl FnMut(&GptPartitionMoveProgress),
+    ) -> Result<LbaRangeInclusive, DiskError<Io::Error>> {
+        let block_buf = self.clip_block_buf_size(chunk_buf)?;
+        let mut table = self.read_gpt_table(block_buf, storage)?;
+        let entry = table
+            .get_partition_entry(index)
+            .ok_or(DiskError::Table(GptTableError::IndexOutOfRange))?;
+        if !entry.is_used() {
+            return Err(DiskError::Table(GptTableError::EntryNotUsed));
+        }
+        let source = entry
+            .lba_range()
+            .ok_or(DiskError::Table(GptTableError::InvalidRange))?;
+
+        let progress = GptPartitionMoveProgress {
+            index,
+            source,
+            destination_start: new_start,
+            blocks_copied: 0,
+        };
+        self.continue_partition_move(
+            &mut table,
+            progress,
+            chunk_buf,
+            on_progress,
+        )
+    }
+
+    /// Continue a partition move started by [`move_partition`] that was
+    /// interrupted. `progress` is the last progress reported to the
+    /// callback.
+    ///
+    /// The GPT is first repaired with [`repair_gpt`], in case the move
+    /// was interrupted while the GPT was being written. Depending on
+    /// which copy was intact, the partition entry then covers either
+    /// the source or the destination range.
+    ///
+    /// If the move already completed, so that the partition entry
+    /// covers the destination range, nothing else is written. Returns
+    /// [`DiskError::Table`] with [`GptTableError::InvalidRange`] if the
+    /// partition entry covers neither the source nor destination
+    /// range.
+    ///
+    /// See [`move_partition`] for a description of the buffers.
+    ///
+    /// [`move_partition`]: Self::move_partition
+    /// [`repair_gpt`]: Self::repair_gpt
+    pub fn resume_partition_move(
+        &mut self,
+        progress: &GptPartitionMoveProgress,
+        chunk_buf: &mut [u8],
+        storage: &mut [u8],
+        on_progress: impl FnMut(&GptPartitionMoveProgress),
+    ) -> Result<LbaRangeInclusive, DiskError<Io::Error>> {
+        let block_buf = self.clip_block_buf_size(chunk_buf)?;
+        self.repair_gpt(block_buf, storage)?;
+        let mut table = self.read_gpt_table(block_buf, storage)?;
+        let range = table
+            .get_partition_entry(progress.index)
+            .ok_or(DiskError::Table(GptTableError::IndexOutOfRange))?
+            .lba_range();
+
+        match range {
+            Some(range) if Some(range) == progress.destination() => {
+                return Ok(range);
+            }
+            Some(range) if range == progress.source => {}
+            _ => return Err(DiskError::Table(GptTableError::InvalidRange)),
+        }
+        self.continue_partition_move(
+            &mut table,
+            *progress,
+            chunk_buf,
+            on_progress,
+        )
+    }
+
+    fn continue_partition_move(
+        &mut self,
+        table: &mut GptTable,
+        mut progress: GptPartitionMoveProgress,
+        chunk_buf: &mut [u8],
+        mut on_progress: impl FnMut(&GptPartitionMoveProgress),
+    ) -> Result<LbaRangeInclusive, DiskError<Io::Error>> {
+        let index = progress.index;
+        let destination = progress.destination().ok_or(DiskError::Overflow)?;
+        table
+            .check_range(destination, Some(index))
+            .map_err(DiskError::Table)?;
+
+        let block_size =
+            self.io.block_size().to_usize().ok_or(DiskError::Overflow)?;
+        let buf_blocks = u64::try_from(chunk_buf.len() / block_size)
+            .map_err(|_| DiskError::Overflow)?;
+        if buf_blocks == 0 {
+            return Err(DiskError::BufferTooSmall);
+        }
+
+        let source_start = progress.source.start().to_u64();
+        let destination_start = destination.start().to_u64();
+        let moving_up = destination_start > source_start;
+        let distance = if moving_up {
+            destination_start - source_start
+        } else {
+            source_start - destination_start
+        };
+        let num_blocks = progress.num_blocks();
+
+        while distance != 0 && !progress.is_copy_complete() {
+            let remaining = num_blocks - progress.blocks_copied;
+            let chunk_blocks = buf_blocks.min(distance).min(remaining);
+            // Offset of the chunk within the partition.
+            let offset = if moving_up {
+                remaining - chunk_blocks
+            } else {
+                progress.blocks_copied
+            };
+
+            // OK to unwrap: `chunk_blocks` is at most `buf_blocks`.
+            let chunk_len = usize::try_from(chunk_blocks).unwrap() * block_size;
+            let chunk = &mut chunk_buf[..chunk_len];
+            self.io.read_blocks(Lba(source_start + offset), chunk)?;
+            self.io
+                .write_blocks(Lba(destination_start + offset), chunk)?;
+
+            // Make sure the chunk is on the device before reporting it
+            // as copied. Otherwise a crash could lose this chunk after
+            // the progress was stored, and a later chunk that overlaps
+            // its source could already have overwritten it.
+            self.io.flush()?;
+
+            progress.blocks_copied += chunk_blocks;
+            on_progress(&progress);
+        }
+
+        // OK to unwrap: the range was checked by `check_range`.
+        let entry = table.entry_array.get_partition_entry_mut(index).unwrap();
+        entry.starting_lba = destination.start().into();
+        entry.ending_lba = destination.end().into();
+        let block_buf = self.clip_block_buf_size(chunk_buf)?;
+        table.commit_transactional(self, None, block_buf)?;
+
+        Ok(destination)
+    }
+}
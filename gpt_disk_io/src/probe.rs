@@ -0,0 +1,217 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::verify::calculate_header_crc32_from_block;
+use crate::{BlockIo, Disk, DiskError, DiskMode};
+use bytemuck::pod_read_unaligned;
+use core::fmt::{self, Display, Formatter};
+use core::mem;
+use gpt_disk_types::{BlockSize, GptHeader, Lba};
+
+/// Block sizes checked by [`Disk::probe_block_size`], in the order
+/// they are checked.
+const CANDIDATE_BLOCK_SIZES: [u32; 8] =
+    [512, 1024, 2048, 4096, 8192, 16384, 32768, 65536];
+
+/// How much evidence was found for a [`BlockSizeProbe`]. Later
+/// variants indicate more confidence.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum BlockSizeConfidence {
+    /// A primary GPT header signature was found, but the header's
+    /// checksum or `my_lba` is not valid.
+    Signature,
+
+    /// A valid primary GPT header was found.
+    PrimaryHeader,
+
+    /// A valid primary GPT header was found, and a valid secondary
+    /// header was found at the primary header's `alternate_lba`.
+    BothHeaders,
+}
+
+impl Display for BlockSizeConfidence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signature => f.write_str("signature only"),
+            Self::PrimaryHeader => f.write_str("valid primary header"),
+            Self::BothHeaders => {
+                f.write_str("valid primary and secondary headers")
+            }
+        }
+    }
+}
+
+/// Result of [`Disk::probe_block_size`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct BlockSizeProbe {
+    /// Detected block size.
+    pub block_size: BlockSize,
+
+    /// How much evidence was found for [`block_size`].
+    ///
+    /// [`block_size`]: Self::block_size
+    pub confidence: BlockSizeConfidence,
+
+    /// True if a GPT header was also found with another block size,
+    /// with the same confidence. This can happen if a disk image was
+    /// copied between devices with different block sizes without
+    /// removing the old GPT.
+    pub ambiguous: bool,
+}
+
+impl Display for BlockSizeProbe {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes ({}", self.block_size, self.confidence)?;
+        if self.ambiguous {
+            f.write_str(", ambiguous")?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// Check a GPT header read from the start of `bytes`, assuming the
+/// given block size. Returns `None` if the signature is not valid.
+/// Otherwise, returns the header along with whether its checksum and
+/// `my_lba` are valid.
+fn check_header(
+    bytes: &[u8],
+    block_size: u32,
+    my_lba: Lba,
+) -> Option<(GptHeader, bool)> {
+    let header: GptHeader =
+        pod_read_unaligned(&bytes[..mem::size_of::<GptHeader>()]);
+    if !header.is_signature_valid() {
+        return None;
+    }
+    let header_size =
+        usize::try_from(header.header_size.to_u32())
+            .ok()
+            .filter(|size| {
+                *size >= mem::size_of::<GptHeader>()
+                    && *size <= bytes.len()
+                    && u32::try_from(*size)
+                        .map_or(false, |size| size <= block_size)
+            });
+    let is_valid = header_size.map_or(false, |header_size| {
+        calculate_header_crc32_from_block(bytes, header_size)
+            == header.header_crc32
+    }) && header.my_lba.to_u64() == my_lba.to_u64();
+    Some((header, is_valid))
+}
+
+impl<Io: BlockIo, Mode: DiskMode> Disk<Io, Mode> {
+    /// Read the block containing `byte_offset`, and return the bytes
+    /// from `byte_offset` to the end of the block. Returns `None` if
+    /// the offset is past the end of the disk or fewer than
+    /// `size_of::<GptHeader>()` bytes remain in the block.
+    fn read_header_bytes_at<'buf>(
+        &mut self,
+        byte_offset: u64,
+        block_buf: &'buf mut [u8],
+    ) -> Result<Option<&'buf [u8]>, DiskError<Io::Error>> {
+        let block_size = self.io.block_size().to_u64();
+        let lba = byte_offset / block_size;
+        if lba >= self.io.num_blocks()? {
+            return Ok(None);
+        }
+        self.io.read_blocks(Lba(lba), block_buf)?;
+        let offset = usize::try_from(byte_offset % block_size)
+            .map_err(|_| DiskError::Overflow)?;
+        Ok(block_buf
+            .get(offset..)
+            .filter(|bytes| bytes.len() >= mem::size_of::<GptHeader>()))
+    }
+
+    /// Check for a GPT with the given block size.
+    fn probe_candidate(
+        &mut self,
+        block_size: u32,
+        block_buf: &mut [u8],
+    ) -> Result<Option<BlockSizeConfidence>, DiskError<Io::Error>> {
+        let Some(bytes) =
+            self.read_header_bytes_at(u64::from(block_size), block_buf)?
+        else {
+            return Ok(None);
+        };
+        let Some((primary, is_valid)) = check_header(bytes, block_size, Lba(1))
+        else {
+            return Ok(None);
+        };
+        if !is_valid {
+            return Ok(Some(BlockSizeConfidence::Signature));
+        }
+
+        let alternate_lba: Lba = primary.alternate_lba.into();
+        let secondary_bytes =
+            match alternate_lba.to_u64().checked_mul(u64::from(block_size)) {
+                Some(offset) if alternate_lba != Lba(1) => {
+                    self.read_header_bytes_at(offset, block_buf)?
+                }
+                _ => None,
+            };
+        let has_secondary = secondary_bytes.map_or(false, |bytes| {
+            check_header(bytes, block_size, alternate_lba)
+                .map_or(false, |(_, is_valid)| is_valid)
+        });
+
+        Ok(Some(if has_secondary {
+            BlockSizeConfidence::BothHeaders
+        } else {
+            BlockSizeConfidence::PrimaryHeader
+        }))
+    }
+
+    /// Detect the logical block size that a GPT was written with, by
+    /// looking for the primary GPT header in the second block for each
+    /// candidate block size from 512 to 65536 bytes.
+    ///
+    /// This is useful for disk images, where the block size is not
+    /// known. For example, a GPT written on a drive with 4096-byte
+    /// sectors cannot be read as if the sectors were 512 bytes. The
+    /// disk's own [`BlockIo::block_size`] is only used for reading, and
+    /// should be no larger than the smallest block size that might
+    /// have been used; for a disk image, 512 is a good choice.
+    ///
+    /// The candidate with the highest [`BlockSizeConfidence`] is
+    /// returned. If more than one candidate has that confidence, the
+    /// smallest is returned and [`BlockSizeProbe::ambiguous`] is set.
+    /// Returns `None` if no GPT header signature is found.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn probe_block_size(
+        &mut self,
+        mut block_buf: &mut [u8],
+    ) -> Result<Option<BlockSizeProbe>, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+
+        let mut best: Option<BlockSizeProbe> = None;
+        for candidate in CANDIDATE_BLOCK_SIZES {
+            let Some(confidence) =
+                self.probe_candidate(candidate, block_buf)?
+            else {
+                continue;
+            };
+            match &mut best {
+                Some(best) if best.confidence == confidence => {
+                    best.ambiguous = true;
+                }
+                Some(best) if best.confidence > confidence => {}
+                _ => {
+                    best = Some(BlockSizeProbe {
+                        // OK to unwrap: all candidates are valid block
+                        // sizes.
+                        block_size: BlockSize::new(candidate).unwrap(),
+                        confidence,
+                        ambiguous: false,
+                    });
+                }
+            }
+        }
+        Ok(best)
+    }
+}
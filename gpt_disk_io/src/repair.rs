@@ -0,0 +1,353 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::verify::{calculate_header_crc32_from_block, readable_array_layout};
+use crate::{BlockIo, Disk, DiskError};
+use core::mem;
+use gpt_disk_types::{
+    BlockSize, Crc32, GptHeader, GptPartitionEntryArray,
+    GptPartitionEntryArrayLayout, Lba, LbaLe, U32Le,
+};
+
+/// Summary of the changes made by [`Disk::repair_gpt`].
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct GptRepairSummary {
+    /// The primary header was rewritten.
+    pub primary_header_repaired: bool,
+
+    /// The primary partition entry array was rewritten.
+    pub primary_array_repaired: bool,
+
+    /// The secondary header was rewritten.
+    pub secondary_header_repaired: bool,
+
+    /// The secondary partition entry array was rewritten.
+    pub secondary_array_repaired: bool,
+}
+
+impl GptRepairSummary {
+    /// True if anything was written to the disk.
+    #[must_use]
+    pub fn any_repaired(&self) -> bool {
+        self.primary_header_repaired
+            || self.primary_array_repaired
+            || self.secondary_header_repaired
+            || self.secondary_array_repaired
+    }
+}
+
+/// Check whether a header read from `my_lba` is intact: the signature,
+/// size, checksum, and location must be valid, and the partition entry
+/// array must fit on the disk. `block` contains the raw bytes of the
+/// block the header was read from.
+fn is_header_intact(
+    header: &GptHeader,
+    block: &[u8],
+    my_lba: Lba,
+    block_size: BlockSize,
+    num_blocks: u64,
+) -> bool {
+    let Ok(header_size) = usize::try_from(header.header_size.to_u32()) else {
+        return false;
+    };
+    if header_size < mem::size_of::<GptHeader>() || header_size > block.len() {
+        return false;
+    }
+    header.is_signature_valid()
+        && calculate_header_crc32_from_block(block, header_size)
+            == header.header_crc32
+        && header.my_lba.to_u64() == my_lba.to_u64()
+        && header.first_usable_lba.to_u64() <= header.last_usable_lba.to_u64()
+        && readable_array_layout(header, block_size, num_blocks).is_some()
+}
+
+/// Create the header for the other copy of the GPT from the intact
+/// header `good`, with the partition entry array in its usual location.
+///
+/// Returns `None` if the rebuilt copy would not be in its usual place,
+/// or if its array would overlap the usable range.
+fn rebuild_header(
+    good: &GptHeader,
+    array_blocks: u64,
+    num_blocks: u64,
+) -> Option<GptHeader> {
+    let good_is_primary = good.my_lba.to_u64() == 1;
+    let partition_entry_lba = if good_is_primary {
+        good.alternate_lba.to_u64().checked_sub(array_blocks)?
+    } else {
+        2
+    };
+
+    let location_valid = if good_is_primary {
+        good.alternate_lba.to_u64() < num_blocks
+            && partition_entry_lba > good.last_usable_lba.to_u64()
+    } else {
+        good.alternate_lba.to_u64() == 1
+            && partition_entry_lba + array_blocks
+                <= good.first_usable_lba.to_u64()
+    };
+    if !location_valid {
+        return None;
+    }
+
+    let mut rebuilt = GptHeader {
+        my_lba: good.alternate_lba,
+        alternate_lba: good.my_lba,
+        partition_entry_lba: LbaLe::from_u64(partition_entry_lba),
+        ..*good
+    };
+    rebuilt.update_header_crc32();
+    Some(rebuilt)
+}
+
+impl<Io: BlockIo> Disk<Io> {
+    /// Read the partition entry array described by `header` from the
+    /// first of `candidates` at which the array's checksum matches.
+    fn find_intact_partition_entry_array<'buf>(
+        &mut self,
+        header: &GptHeader,
+        candidates: [LbaLe; 2],
+        storage: &'buf mut [u8],
+    ) -> Result<GptPartitionEntryArray<'buf>, DiskError<Io::Error>> {
+        let block_size = self.io.block_size();
+        let num_blocks = self.io.num_blocks()?;
+
+        for start_lba in candidates {
+            let candidate = GptHeader {
+                partition_entry_lba: start_lba,
+                ..*header
+            };
+            let Some(layout) =
+                readable_array_layout(&candidate, block_size, num_blocks)
+            else {
+                continue;
+            };
+            let entry_array =
+                self.read_gpt_partition_entry_array(layout, storage)?;
+            if entry_array.calculate_crc32()
+                == header.partition_entry_array_crc32
+            {
+                // The array is left in `storage`; wrap it again so that
+                // the borrow of `storage` can outlive the loop.
+                return GptPartitionEntryArray::new(
+                    layout, block_size, storage,
+                )
+                .map_err(|_| DiskError::BufferTooSmall);
+            }
+        }
+
+        Err(DiskError::InvalidPartitionEntryArray)
+    }
+
+    /// Read the GPT header at `lba`, and check whether it is intact.
+    fn read_gpt_header_if_intact(
+        &mut self,
+        lba: Lba,
+        block_buf: &mut [u8],
+    ) -> Result<(GptHeader, bool), DiskError<Io::Error>> {
+        let block_size = self.io.block_size();
+        let num_blocks = self.io.num_blocks()?;
+        let header = self.read_gpt_header(lba, block_buf)?;
+        let intact =
+            is_header_intact(&header, block_buf, lba, block_size, num_blocks);
+        Ok((header, intact))
+    }
+
+    /// Check whether the partition entry array described by `header`
+    /// is intact at any of `candidates`.
+    fn has_intact_partition_entry_array(
+        &mut self,
+        header: &GptHeader,
+        candidates: [LbaLe; 2],
+        storage: &mut [u8],
+    ) -> Result<bool, DiskError<Io::Error>> {
+        match self
+            .find_intact_partition_entry_array(header, candidates, storage)
+        {
+            Ok(_) => Ok(true),
+            Err(DiskError::InvalidPartitionEntryArray) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Calculate the CRC32 checksum of the partition entry array on
+    /// disk described by `layout`, reading one block at a time.
+    fn calculate_array_crc32_on_disk(
+        &mut self,
+        layout: &GptPartitionEntryArrayLayout,
+        block_buf: &mut [u8],
+    ) -> Result<Crc32, DiskError<Io::Error>> {
+        let block_size = self.io.block_size();
+        let crc = gpt_disk_types::crc::Crc::<u32>::new(&Crc32::ALGORITHM);
+        let mut digest = crc.digest();
+        let mut remaining = layout
+            .num_bytes_exact_as_usize()
+            .ok_or(DiskError::Overflow)?;
+        let num_blocks =
+            layout.num_blocks(block_size).ok_or(DiskError::Overflow)?;
+        for i in 0..num_blocks {
+            let lba = layout
+                .start_lba
+                .to_u64()
+                .checked_add(i)
+                .ok_or(DiskError::Overflow)?;
+            self.io.read_blocks(Lba(lba), block_buf)?;
+            let len = remaining.min(block_buf.len());
+            digest.update(&block_buf[..len]);
+            remaining -= len;
+        }
+        Ok(Crc32(U32Le(digest.finalize().to_le_bytes())))
+    }
+
+    /// Repair a GPT in which one of the two copies is damaged, by
+    /// rebuilding the damaged copy from the intact one.
+    ///
+    /// A header is intact if its signature, `header_size`, checksum,
+    /// and `my_lba` are valid, and its partition entry array fits on the
+    /// disk. The primary header is preferred if both are intact, unless
+    /// neither partition entry array matches it. The secondary header
+    /// is expected at the primary's `alternate_lba`, or at the last
+    /// block of the disk if the primary is damaged.
+    ///
+    /// The partition entry array is taken from the intact header's
+    /// location if its checksum matches, otherwise from the other
+    /// copy's location. Then each copy is repaired as needed:
+    /// * A damaged or inconsistent header is replaced by a copy of the
+    ///   intact header with `my_lba` and `alternate_lba` swapped, with
+    ///   `partition_entry_lba` pointing to the array's usual location:
+    ///   LBA 2 for the primary array, and immediately before the
+    ///   secondary header for the secondary array. Checksums are
+    ///   recalculated.
+    /// * A partition entry array that does not match its header's
+    ///   checksum is overwritten with the intact array.
+    ///
+    /// Returns [`DiskError::InvalidGptHeader`] if neither header is
+    /// intact, and [`DiskError::InvalidPartitionEntryArray`] if neither
+    /// partition entry array matches the intact header's checksum.
+    /// Nothing is written in either case.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// one block. The `storage` buffer must be at least
+    /// [`layout.num_bytes_rounded_to_block`] in size, where `layout` is
+    /// the intact header's partition entry array layout.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    pub fn repair_gpt(
+        &mut self,
+        mut block_buf: &mut [u8],
+        storage: &mut [u8],
+    ) -> Result<GptRepairSummary, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        let block_size = self.io.block_size();
+        let num_blocks = self.io.num_blocks()?;
+        if num_blocks < 3 {
+            return Err(DiskError::DiskTooSmall);
+        }
+
+        let primary_lba = Lba(1);
+        let (primary, mut primary_intact) =
+            self.read_gpt_header_if_intact(primary_lba, block_buf)?;
+
+        let secondary_lba =
+            if primary_intact && primary.alternate_lba.to_u64() < num_blocks {
+                primary.alternate_lba.into()
+            } else {
+                Lba(num_blocks - 1)
+            };
+        let (secondary, secondary_intact) =
+            self.read_gpt_header_if_intact(secondary_lba, block_buf)?;
+
+        // An interrupted write of the primary copy can leave the old
+        // primary header intact next to a new primary array. If no
+        // array matches the primary header, use the secondary header.
+        if primary_intact && secondary_intact {
+            primary_intact = self.has_intact_partition_entry_array(
+                &primary,
+                [primary.partition_entry_lba, secondary.partition_entry_lba],
+                storage,
+            )?;
+        }
+
+        let (good, other_intact) = if primary_intact {
+            (primary, secondary_intact)
+        } else if secondary_intact {
+            (secondary, false)
+        } else {
+            return Err(DiskError::InvalidGptHeader);
+        };
+        let layout = good
+            .get_partition_entry_array_layout()
+            .map_err(|_| DiskError::InvalidGptHeader)?;
+        let array_blocks =
+            layout.num_blocks(block_size).ok_or(DiskError::Overflow)?;
+
+        let rebuilt = rebuild_header(&good, array_blocks, num_blocks)
+            .ok_or(DiskError::InvalidGptHeader)?;
+        let (expected_primary, expected_secondary) = if primary_intact {
+            (good, rebuilt)
+        } else {
+            (rebuilt, good)
+        };
+
+        let other_array_lba = if other_intact {
+            secondary.partition_entry_lba
+        } else {
+            rebuilt.partition_entry_lba
+        };
+        let mut entry_array = self.find_intact_partition_entry_array(
+            &good,
+            [good.partition_entry_lba, other_array_lba],
+            storage,
+        )?;
+
+        let mut summary = GptRepairSummary::default();
+        for (is_primary, expected, existing, existing_intact) in [
+            (true, expected_primary, primary, primary_intact),
+            (false, expected_secondary, secondary, secondary_intact),
+        ] {
+            // If the existing header is intact, keep its array location.
+            let mut header = expected;
+            if existing_intact {
+                header.partition_entry_lba = existing.partition_entry_lba;
+                header.update_header_crc32();
+            }
+
+            let array_layout = GptPartitionEntryArrayLayout {
+                start_lba: header.partition_entry_lba.into(),
+                ..layout
+            };
+            if self.calculate_array_crc32_on_disk(&array_layout, block_buf)?
+                != header.partition_entry_array_crc32
+            {
+                entry_array.set_start_lba(array_layout.start_lba);
+                self.write_gpt_partition_entry_array(&entry_array)?;
+                if is_primary {
+                    summary.primary_array_repaired = true;
+                } else {
+                    summary.secondary_array_repaired = true;
+                }
+            }
+
+            if !existing_intact || existing != header {
+                self.write_gpt_header(
+                    header.my_lba.into(),
+                    &header,
+                    block_buf,
+                )?;
+                if is_primary {
+                    summary.primary_header_repaired = true;
+                } else {
+                    summary.secondary_header_repaired = true;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::builder::num_reserved_blocks;
+use crate::{BlockIo, Disk, DiskError, GptTable};
+use gpt_disk_types::{GptHeader, Lba, LbaLe, MasterBootRecord};
+
+impl<Io: BlockIo> Disk<Io> {
+    /// Rewrite the GPT for a disk with `num_blocks` blocks, which may
+    /// be different from the current size of the disk. The secondary
+    /// header is placed in the last block, with the secondary partition
+    /// entry array immediately before it. The usable range ends before
+    /// the blocks reserved for the secondary array, as with
+    /// [`GptBuilder::num_reserved_blocks`].
+    ///
+    /// [`GptBuilder::num_reserved_blocks`]: crate::GptBuilder::num_reserved_blocks
+    fn move_secondary_gpt(
+        &mut self,
+        table: &mut GptTable,
+        num_blocks: u64,
+        block_buf: &mut [u8],
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        let reserved_blocks = num_reserved_blocks(
+            table.entry_array.layout(),
+            self.io.block_size(),
+        )
+        .ok_or(DiskError::Overflow)?;
+        let alternate_lba =
+            num_blocks.checked_sub(1).ok_or(DiskError::DiskTooSmall)?;
+        let last_usable_lba = alternate_lba
+            .checked_sub(reserved_blocks)
+            .and_then(|lba| lba.checked_sub(1))
+            .ok_or(DiskError::DiskTooSmall)?;
+        if last_usable_lba < table.header.first_usable_lba.to_u64() {
+            return Err(DiskError::DiskTooSmall);
+        }
+        for index in 0..table.num_entries() {
+            // OK to unwrap: the index is within the array.
+            let entry = table.get_partition_entry(index).unwrap();
+            if entry.is_used() && entry.ending_lba.to_u64() > last_usable_lba {
+                return Err(DiskError::PartitionOutsideDisk { index });
+            }
+        }
+
+        let old_alternate_lba = table.header.alternate_lba.to_u64();
+        table.header.alternate_lba = LbaLe::from_u64(alternate_lba);
+        table.header.last_usable_lba = LbaLe::from_u64(last_usable_lba);
+        table.commit(self, block_buf)?;
+        self.write_mbr(
+            &MasterBootRecord::protective_mbr(num_blocks),
+            block_buf,
+        )?;
+
+        // Erase the old secondary header so that it is not mistaken for
+        // a valid header later. If it is not in the usable range, it was
+        // either overwritten by the new secondary array or is past the
+        // new end of the disk.
+        if old_alternate_lba >= table.header.first_usable_lba.to_u64()
+            && old_alternate_lba <= last_usable_lba
+        {
+            block_buf.fill(0);
+            self.io.write_blocks(Lba(old_alternate_lba), block_buf)?;
+        }
+
+        Ok(table.header)
+    }
+
+    /// Move the secondary header and partition entry array to the end
+    /// of the disk. This is needed after a disk has been grown, for
+    /// example by enlarging a VM image file, and is equivalent to
+    /// `sgdisk -e`.
+    ///
+    /// The primary GPT is read with [`read_gpt_table`]. The secondary
+    /// header is written to the last block of the disk, with the
+    /// secondary partition entry array immediately before it. The
+    /// usable range ends before the blocks reserved for the secondary
+    /// array, which are at least 16,384 bytes. The primary header's
+    /// `alternate_lba` and `last_usable_lba` are updated to match, and
+    /// the protective MBR is rewritten for the new disk size. The old
+    /// secondary header is zeroed out if it is now inside the usable
+    /// range. The updated primary header is returned.
+    ///
+    /// Returns [`DiskError::PartitionOutsideDisk`] if a partition would
+    /// no longer fit in the usable range.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// one block. The `storage` buffer must be at least
+    /// [`layout.num_bytes_rounded_to_block`] in size, where `layout` is
+    /// the primary header's partition entry array layout.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: gpt_disk_types::GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    /// [`read_gpt_table`]: Self::read_gpt_table
+    pub fn relocate_backup_to_end(
+        &mut self,
+        mut block_buf: &mut [u8],
+        storage: &mut [u8],
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        let mut table = self.read_gpt_table(block_buf, storage)?;
+        let num_blocks = self.io.num_blocks()?;
+        self.move_secondary_gpt(&mut table, num_blocks, block_buf)
+    }
+
+    /// Prepare the disk to be shrunk to `num_blocks` blocks, or to the
+    /// smallest size that holds all used partitions if `num_blocks` is
+    /// `None`. The new number of blocks is returned; the caller is
+    /// responsible for actually truncating the disk, for example with
+    /// `File::set_len`.
+    ///
+    /// The secondary header and partition entry array are moved to the
+    /// end of the new disk size, with at least 16,384 bytes reserved
+    /// for the array. The primary header's `alternate_lba` and
+    /// `last_usable_lba` are updated to match, and the protective MBR
+    /// is rewritten for the new size. Partition data is not moved.
+    ///
+    /// Returns [`DiskError::PartitionOutsideDisk`] if a partition
+    /// extends past the usable range of the requested size,
+    /// [`DiskError::DiskTooSmall`] if the requested size cannot hold the
+    /// GPT, and [`DiskError::ShrinkTargetTooLarge`] if the requested
+    /// size is larger than the current size of the disk.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// one block. The `storage` buffer must be at least
+    /// [`layout.num_bytes_rounded_to_block`] in size, where `layout` is
+    /// the primary header's partition entry array layout.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: gpt_disk_types::GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    pub fn shrink_gpt(
+        &mut self,
+        num_blocks: Option<u64>,
+        mut block_buf: &mut [u8],
+        storage: &mut [u8],
+    ) -> Result<u64, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        let mut table = self.read_gpt_table(block_buf, storage)?;
+
+        let num_blocks = if let Some(num_blocks) = num_blocks {
+            num_blocks
+        } else {
+            // The last used block, followed by the blocks reserved for
+            // the secondary array and the header. At least one usable
+            // block is kept.
+            let mut last_used_lba = table.header.first_usable_lba.to_u64();
+            for index in 0..table.num_entries() {
+                // OK to unwrap: the index is within the array.
+                let entry = table.get_partition_entry(index).unwrap();
+                if entry.is_used() {
+                    last_used_lba =
+                        last_used_lba.max(entry.ending_lba.to_u64());
+                }
+            }
+            let reserved_blocks = num_reserved_blocks(
+                table.entry_array.layout(),
+                self.io.block_size(),
+            )
+            .ok_or(DiskError::Overflow)?;
+            last_used_lba
+                .checked_add(reserved_blocks)
+                .and_then(|n| n.checked_add(2))
+                .ok_or(DiskError::Overflow)?
+        };
+        if num_blocks > self.io.num_blocks()? {
+            return Err(DiskError::ShrinkTargetTooLarge { num_blocks });
+        }
+
+        self.move_secondary_gpt(&mut table, num_blocks, block_buf)?;
+        Ok(num_blocks)
+    }
+}
@@ -6,10 +6,35 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::{DiskError, SliceBlockIoError};
+use crate::{
+    DiskError, FaultInjectingBlockIoError, GptTableError, OverlayBlockIoError,
+    PartitionBlockIoError, ReadOnlyBlockIoError, SliceBlockIoError,
+};
 use std::error::Error;
 use std::fmt::{Debug, Display};
 
 impl<Custom> Error for DiskError<Custom> where Custom: Debug + Display {}
 
 impl Error for SliceBlockIoError {}
+
+impl<IoError> Error for PartitionBlockIoError<IoError> where
+    IoError: Debug + Display
+{
+}
+
+impl<IoError> Error for ReadOnlyBlockIoError<IoError> where
+    IoError: Debug + Display
+{
+}
+
+impl<IoError> Error for OverlayBlockIoError<IoError> where
+    IoError: Debug + Display
+{
+}
+
+impl<IoError> Error for FaultInjectingBlockIoError<IoError> where
+    IoError: Debug + Display
+{
+}
+
+impl Error for GptTableError {}
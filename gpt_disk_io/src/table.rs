@@ -0,0 +1,700 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{AsyncBlockIo, AsyncDisk, BlockIo, Disk, DiskError, DiskMode};
+use core::fmt::{self, Debug, Display, Formatter};
+use core::num::NonZeroU64;
+use gpt_disk_types::{
+    BlockSize, GptHeader, GptPartitionAttributes, GptPartitionEntry,
+    GptPartitionEntryArray, GptPartitionEntryArrayLayout, GptPartitionName,
+    GptPartitionType, Lba, LbaLe, LbaRangeInclusive,
+};
+
+/// Convert `range` from `old_block_size` to `new_block_size`, keeping
+/// the same byte range. Returns `None` if the byte range does not start
+/// and end on a block boundary of the new block size.
+fn convert_range(
+    range: LbaRangeInclusive,
+    old_block_size: BlockSize,
+    new_block_size: BlockSize,
+) -> Option<LbaRangeInclusive> {
+    LbaRangeInclusive::from_byte_range(
+        range.to_byte_range(old_block_size)?,
+        new_block_size,
+    )
+}
+
+/// Error type used by [`GptTable`] methods.
+///
+/// If the `std` feature is enabled, this type implements the [`Error`]
+/// trait.
+///
+/// [`Error`]: std::error::Error
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum GptTableError {
+    /// The partition index is outside the partition entry array.
+    IndexOutOfRange,
+
+    /// The partition entry is not in use.
+    EntryNotUsed,
+
+    /// All entries in the partition entry array are in use.
+    NoUnusedEntry,
+
+    /// The partition type is [`GptPartitionType::UNUSED`].
+    UnusedPartitionType,
+
+    /// The partition's ending LBA is less than its starting LBA.
+    InvalidRange,
+
+    /// The partition is not entirely within the usable range of the
+    /// disk.
+    OutsideUsableRange,
+
+    /// The partition overlaps another partition.
+    Overlap {
+        /// Index of the other partition.
+        index: u32,
+    },
+
+    /// The partition does not start and end on a block boundary of the
+    /// new block size. See [`GptTable::convert_block_size`].
+    NotRepresentable {
+        /// Index of the partition.
+        index: u32,
+    },
+
+    /// The partition entry array storage is too small for the new
+    /// block size. See [`GptTable::convert_block_size`].
+    BufferTooSmall,
+}
+
+impl Display for GptTableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IndexOutOfRange => {
+                f.write_str("partition index is out of range")
+            }
+            Self::EntryNotUsed => f.write_str("partition entry is not in use"),
+            Self::NoUnusedEntry => {
+                f.write_str("no unused partition entries are available")
+            }
+            Self::UnusedPartitionType => {
+                f.write_str("partition type must not be UNUSED")
+            }
+            Self::InvalidRange => {
+                f.write_str("partition ending LBA is less than starting LBA")
+            }
+            Self::OutsideUsableRange => {
+                f.write_str("partition is outside the usable range of the disk")
+            }
+            Self::Overlap { index } => {
+                write!(f, "partition overlaps partition {index}")
+            }
+            Self::NotRepresentable { index } => {
+                write!(
+                    f,
+                    "partition {index} is not aligned to the new block size"
+                )
+            }
+            Self::BufferTooSmall => {
+                f.write_str("partition entry array storage is too small")
+            }
+        }
+    }
+}
+
+/// In-memory model of a GPT that can be modified and then written back
+/// to a [`Disk`].
+///
+/// A `GptTable` is created with [`Disk::read_gpt_table`]. The
+/// partition entry array is held in caller-provided storage, so no
+/// memory allocation is required.
+///
+/// Modifications are validated against the rest of the table: used
+/// partitions must stay within the usable range of the disk and must
+/// not overlap each other. Changes are only written to disk by
+/// [`commit`], which writes both copies of the partition entry array
+/// and both headers with updated checksums.
+///
+/// The secondary header and partition entry array are derived from
+/// the primary header: the secondary header is written to the
+/// primary's [`alternate_lba`], and the secondary partition entry
+/// array is placed immediately before it.
+///
+/// [`alternate_lba`]: GptHeader::alternate_lba
+/// [`commit`]: Self::commit
+pub struct GptTable<'buf> {
+    pub(crate) header: GptHeader,
+    pub(crate) entry_array: GptPartitionEntryArray<'buf>,
+}
+
+impl<'buf> GptTable<'buf> {
+    /// Validate the signature and checksum of a primary `header` read
+    /// from disk, and return its partition entry array layout.
+    pub(crate) fn check_header<IoError: Debug + Display>(
+        header: &GptHeader,
+    ) -> Result<GptPartitionEntryArrayLayout, DiskError<IoError>> {
+        if !header.is_signature_valid()
+            || header.calculate_header_crc32() != header.header_crc32
+        {
+            return Err(DiskError::InvalidGptHeader);
+        }
+        header
+            .get_partition_entry_array_layout()
+            .map_err(|_| DiskError::InvalidGptHeader)
+    }
+
+    /// Create a `GptTable` from a `header` that has been checked with
+    /// [`check_header`], validating the checksum of `entry_array`.
+    ///
+    /// [`check_header`]: Self::check_header
+    pub(crate) fn from_parts<IoError: Debug + Display>(
+        header: GptHeader,
+        entry_array: GptPartitionEntryArray<'buf>,
+    ) -> Result<Self, DiskError<IoError>> {
+        if entry_array.calculate_crc32() != header.partition_entry_array_crc32 {
+            return Err(DiskError::InvalidPartitionEntryArray);
+        }
+        Ok(Self {
+            header,
+            entry_array,
+        })
+    }
+
+    /// Get the number of entries in the partition entry array.
+    #[must_use]
+    pub fn num_entries(&self) -> u32 {
+        self.entry_array.layout().num_entries
+    }
+
+    /// Get the range of blocks that can be used for partition data.
+    /// Returns `None` if the header's last usable LBA is less than its
+    /// first usable LBA.
+    #[must_use]
+    pub fn usable_range(&self) -> Option<LbaRangeInclusive> {
+        LbaRangeInclusive::new(
+            self.header.first_usable_lba.into(),
+            self.header.last_usable_lba.into(),
+        )
+    }
+
+    /// Get the partition entry array.
+    #[must_use]
+    pub fn entry_array(&self) -> &GptPartitionEntryArray<'buf> {
+        &self.entry_array
+    }
+
+    /// Get a partition entry. The `index` is zero-based.
+    #[must_use]
+    pub fn get_partition_entry(
+        &self,
+        index: u32,
+    ) -> Option<&GptPartitionEntry> {
+        self.entry_array.get_partition_entry(index)
+    }
+
+    /// Get the primary header, with checksums updated to match the
+    /// current contents of the table.
+    #[must_use]
+    pub fn primary_header(&self) -> GptHeader {
+        let mut header = GptHeader {
+            partition_entry_array_crc32: self.entry_array.calculate_crc32(),
+            ..self.header
+        };
+        header.update_header_crc32();
+        header
+    }
+
+    /// Get the secondary header, with checksums updated to match the
+    /// current contents of the table.
+    ///
+    /// Returns `None` if overflow occurs when calculating the location
+    /// of the secondary partition entry array.
+    #[must_use]
+    pub fn secondary_header(&self, block_size: BlockSize) -> Option<GptHeader> {
+        let primary = self.primary_header();
+        let array_blocks = self.entry_array.layout().num_blocks(block_size)?;
+        let mut header = GptHeader {
+            my_lba: primary.alternate_lba,
+            alternate_lba: primary.my_lba,
+            partition_entry_lba: LbaLe::from_u64(
+                primary.alternate_lba.to_u64().checked_sub(array_blocks)?,
+            ),
+            ..primary
+        };
+        header.update_header_crc32();
+        Some(header)
+    }
+
+    fn get_used_entry_mut(
+        &mut self,
+        index: u32,
+    ) -> Result<&mut GptPartitionEntry, GptTableError> {
+        let entry = self
+            .entry_array
+            .get_partition_entry_mut(index)
+            .ok_or(GptTableError::IndexOutOfRange)?;
+        if entry.is_used() {
+            Ok(entry)
+        } else {
+            Err(GptTableError::EntryNotUsed)
+        }
+    }
+
+    /// Check that `range` is within the usable range of the disk and
+    /// does not overlap any used partition other than the one at
+    /// `ignore_index`.
+    pub(crate) fn check_range(
+        &self,
+        range: LbaRangeInclusive,
+        ignore_index: Option<u32>,
+    ) -> Result<(), GptTableError> {
+        let usable = self
+            .usable_range()
+            .ok_or(GptTableError::OutsideUsableRange)?;
+        if range.start() < usable.start() || range.end() > usable.end() {
+            return Err(GptTableError::OutsideUsableRange);
+        }
+
+        for index in 0..self.num_entries() {
+            if Some(index) == ignore_index {
+                continue;
+            }
+            // OK to unwrap: the index is within the array.
+            let entry = self.entry_array.get_partition_entry(index).unwrap();
+            if !entry.is_used() {
+                continue;
+            }
+            if let Some(other) = entry.lba_range() {
+                if range.start() <= other.end() && other.start() <= range.end()
+                {
+                    return Err(GptTableError::Overlap { index });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add a new partition in the first unused entry of the partition
+    /// entry array. Returns the index of the new entry.
+    ///
+    /// The entry's type must not be [`GptPartitionType::UNUSED`], and
+    /// its LBA range must be within the usable range of the disk
+    /// without overlapping any other partition.
+    pub fn add_partition(
+        &mut self,
+        entry: GptPartitionEntry,
+    ) -> Result<u32, GptTableError> {
+        if !entry.is_used() {
+            return Err(GptTableError::UnusedPartitionType);
+        }
+        let range = entry.lba_range().ok_or(GptTableError::InvalidRange)?;
+        self.check_range(range, None)?;
+
+        let index = (0..self.num_entries())
+            .find(|index| {
+                // OK to unwrap: the index is within the array.
+                !self
+                    .entry_array
+                    .get_partition_entry(*index)
+                    .unwrap()
+                    .is_used()
+            })
+            .ok_or(GptTableError::NoUnusedEntry)?;
+        // OK to unwrap: the index is within the array.
+        *self.entry_array.get_partition_entry_mut(index).unwrap() = entry;
+        Ok(index)
+    }
+
+    /// Remove the partition at `index`. The entry is zeroed out, and
+    /// the removed entry is returned.
+    pub fn remove_partition(
+        &mut self,
+        index: u32,
+    ) -> Result<GptPartitionEntry, GptTableError> {
+        let entry = self.get_used_entry_mut(index)?;
+        let removed = *entry;
+        *entry = GptPartitionEntry::default();
+        Ok(removed)
+    }
+
+    /// Set the name of the partition at `index`.
+    pub fn set_name(
+        &mut self,
+        index: u32,
+        name: GptPartitionName,
+    ) -> Result<(), GptTableError> {
+        self.get_used_entry_mut(index)?.name = name;
+        Ok(())
+    }
+
+    /// Set the type of the partition at `index`. The type must not be
+    /// [`GptPartitionType::UNUSED`]; use [`remove_partition`] instead.
+    ///
+    /// [`remove_partition`]: Self::remove_partition
+    pub fn set_type(
+        &mut self,
+        index: u32,
+        partition_type: GptPartitionType,
+    ) -> Result<(), GptTableError> {
+        if partition_type == GptPartitionType::UNUSED {
+            return Err(GptTableError::UnusedPartitionType);
+        }
+        self.get_used_entry_mut(index)?.partition_type_guid = partition_type;
+        Ok(())
+    }
+
+    /// Set the attributes of the partition at `index`.
+    pub fn set_attributes(
+        &mut self,
+        index: u32,
+        attributes: GptPartitionAttributes,
+    ) -> Result<(), GptTableError> {
+        self.get_used_entry_mut(index)?.attributes = attributes;
+        Ok(())
+    }
+
+    /// Change the ending LBA of the partition at `index`. The starting
+    /// LBA is unchanged. The new range must be within the usable range
+    /// of the disk without overlapping any other partition.
+    ///
+    /// Only the partition entry is changed; no partition data is moved.
+    pub fn resize_partition(
+        &mut self,
+        index: u32,
+        ending_lba: Lba,
+    ) -> Result<(), GptTableError> {
+        let starting_lba = self.get_used_entry_mut(index)?.starting_lba;
+        let range = LbaRangeInclusive::new(starting_lba.into(), ending_lba)
+            .ok_or(GptTableError::InvalidRange)?;
+        self.check_range(range, Some(index))?;
+
+        self.get_used_entry_mut(index)?.ending_lba = ending_lba.into();
+        Ok(())
+    }
+
+    /// Extend the partition at `index` to fill the free space after
+    /// it. The new ending LBA is the largest value that does not reach
+    /// the next partition or pass the last usable LBA. The new ending
+    /// LBA is returned.
+    ///
+    /// If `alignment` is set, the partition is made to end immediately
+    /// before a multiple of `alignment` blocks, so that whatever follows
+    /// it starts on an aligned boundary. The partition is never shrunk;
+    /// if the aligned ending LBA would be less than the current ending
+    /// LBA, the partition is left unchanged.
+    ///
+    /// Returns [`GptTableError::Overlap`] if another partition overlaps
+    /// the partition's current range.
+    ///
+    /// Only the partition entry is changed; checksums are updated when
+    /// the table is written with [`commit`].
+    ///
+    /// [`commit`]: Self::commit
+    pub fn grow_partition_to_fill(
+        &mut self,
+        index: u32,
+        alignment: Option<NonZeroU64>,
+    ) -> Result<Lba, GptTableError> {
+        let entry = self.get_used_entry_mut(index)?;
+        let range = entry.lba_range().ok_or(GptTableError::InvalidRange)?;
+        let usable = self
+            .usable_range()
+            .ok_or(GptTableError::OutsideUsableRange)?;
+        if range.start() < usable.start() || range.end() > usable.end() {
+            return Err(GptTableError::OutsideUsableRange);
+        }
+
+        // Find the block before the next partition.
+        let mut limit = usable.end().to_u64();
+        for other_index in 0..self.num_entries() {
+            // OK to unwrap: the index is within the array.
+            let other =
+                self.entry_array.get_partition_entry(other_index).unwrap();
+            if other_index == index || !other.is_used() {
+                continue;
+            }
+            let other_start = other.starting_lba.to_u64();
+            if other_start > range.end().to_u64() {
+                limit = limit.min(other_start - 1);
+            } else if other.ending_lba.to_u64() >= range.start().to_u64() {
+                return Err(GptTableError::Overlap { index: other_index });
+            }
+        }
+
+        if let Some(alignment) = alignment {
+            let alignment = alignment.get();
+            limit = ((limit + 1) / alignment * alignment).saturating_sub(1);
+        }
+
+        let ending_lba = Lba(limit.max(range.end().to_u64()));
+        self.get_used_entry_mut(index)?.ending_lba = ending_lba.into();
+        Ok(ending_lba)
+    }
+
+    /// Convert the table from `old_block_size` to `new_block_size`, so
+    /// that a disk image can be moved between devices with different
+    /// logical block sizes, such as 512-byte and 4096-byte (4Kn)
+    /// devices.
+    ///
+    /// Every LBA in the header and partition entries is rescaled so
+    /// that it refers to the same byte offset as before. The disk size
+    /// is taken from the header's [`alternate_lba`], rounded down to a
+    /// whole number of new blocks. The partition entry array is placed
+    /// at LBA 2, and the usable range is recalculated to fit between
+    /// the two arrays without growing past the old usable range.
+    ///
+    /// Returns [`GptTableError::NotRepresentable`] if a partition does
+    /// not start and end on a block boundary of the new block size,
+    /// [`GptTableError::OutsideUsableRange`] if a partition is no
+    /// longer within the usable range, and
+    /// [`GptTableError::BufferTooSmall`] if the partition entry array
+    /// storage is smaller than the array rounded up to the new block
+    /// size. The table is unchanged if an error occurs.
+    ///
+    /// Only the in-memory table is changed. Use [`commit`] to write it
+    /// to a disk with the new block size. The protective MBR also
+    /// depends on the block size; use [`Disk::write_protective_mbr`]
+    /// to replace it.
+    ///
+    /// [`alternate_lba`]: GptHeader::alternate_lba
+    /// [`commit`]: Self::commit
+    pub fn convert_block_size(
+        &mut self,
+        old_block_size: BlockSize,
+        new_block_size: BlockSize,
+    ) -> Result<(), GptTableError> {
+        let header = self
+            .converted_header(old_block_size, new_block_size)
+            .ok_or(GptTableError::OutsideUsableRange)?;
+        let usable = LbaRangeInclusive::new(
+            header.first_usable_lba.into(),
+            header.last_usable_lba.into(),
+        )
+        .ok_or(GptTableError::OutsideUsableRange)?;
+
+        // Check every partition before changing anything.
+        for index in 0..self.num_entries() {
+            // OK to unwrap: the index is within the array.
+            let entry = self.entry_array.get_partition_entry(index).unwrap();
+            if !entry.is_used() {
+                continue;
+            }
+            let range = entry.lba_range().ok_or(GptTableError::InvalidRange)?;
+            let range = convert_range(range, old_block_size, new_block_size)
+                .ok_or(GptTableError::NotRepresentable { index })?;
+            if range.start() < usable.start() || range.end() > usable.end() {
+                return Err(GptTableError::OutsideUsableRange);
+            }
+        }
+
+        self.entry_array
+            .set_block_size(new_block_size)
+            .map_err(|_| GptTableError::BufferTooSmall)?;
+        self.entry_array
+            .set_start_lba(header.partition_entry_lba.into());
+        for index in 0..self.num_entries() {
+            // OK to unwrap: the index is within the array.
+            let entry =
+                self.entry_array.get_partition_entry_mut(index).unwrap();
+            if !entry.is_used() {
+                continue;
+            }
+            // OK to unwrap: all ranges were checked above.
+            let range = convert_range(
+                entry.lba_range().unwrap(),
+                old_block_size,
+                new_block_size,
+            )
+            .unwrap();
+            entry.starting_lba = range.start().into();
+            entry.ending_lba = range.end().into();
+        }
+        self.header = header;
+        Ok(())
+    }
+
+    /// Get the primary header converted to `new_block_size`. Returns
+    /// `None` if overflow occurs or the GPT does not fit on the disk.
+    fn converted_header(
+        &self,
+        old_block_size: BlockSize,
+        new_block_size: BlockSize,
+    ) -> Option<GptHeader> {
+        let old_bs = old_block_size.to_u64();
+        let new_bs = new_block_size.to_u64();
+        let disk_bytes = self
+            .header
+            .alternate_lba
+            .to_u64()
+            .checked_add(1)?
+            .checked_mul(old_bs)?;
+        let num_blocks = disk_bytes / new_bs;
+        let array_lba = 2;
+        let array_blocks =
+            self.entry_array.layout().num_blocks(new_block_size)?;
+
+        // Keep the usable range within the same bytes as before, and
+        // between the two partition entry arrays.
+        let old_first_usable_byte =
+            self.header.first_usable_lba.to_u64().checked_mul(old_bs)?;
+        let first_usable_lba = (array_lba + array_blocks)
+            .max(old_first_usable_byte.checked_add(new_bs - 1)? / new_bs);
+        let old_end_usable_byte = self
+            .header
+            .last_usable_lba
+            .to_u64()
+            .checked_add(1)?
+            .checked_mul(old_bs)?;
+        let last_usable_lba = num_blocks
+            .checked_sub(array_blocks)?
+            .checked_sub(2)?
+            .min((old_end_usable_byte / new_bs).checked_sub(1)?);
+        if first_usable_lba > last_usable_lba {
+            return None;
+        }
+
+        Some(GptHeader {
+            my_lba: LbaLe::from_u64(1),
+            alternate_lba: LbaLe::from_u64(num_blocks - 1),
+            first_usable_lba: LbaLe::from_u64(first_usable_lba),
+            last_usable_lba: LbaLe::from_u64(last_usable_lba),
+            partition_entry_lba: LbaLe::from_u64(array_lba),
+            ..self.header
+        })
+    }
+
+    /// Write the table to `disk`.
+    ///
+    /// The secondary partition entry array and header are written
+    /// first, followed by the primary partition entry array and
+    /// header. Both headers get updated checksums.
+    ///
+    /// The disk is not flushed, so the writes may reach the device in
+    /// any order. Use [`commit_transactional`] if the GPT must stay
+    /// consistent when the commit is interrupted.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`commit_transactional`]: Self::commit_transactional
+    pub fn commit<Io: BlockIo>(
+        &mut self,
+        disk: &mut Disk<Io>,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let (primary, secondary) =
+            self.headers_for_commit(disk.io.block_size())?;
+        self.write_copy(disk, &secondary, block_buf)?;
+        self.write_copy(disk, &primary, block_buf)?;
+        self.header = primary;
+        Ok(())
+    }
+
+    /// Write the table to an [`AsyncDisk`]. This is the asynchronous
+    /// version of [`commit`], and writes the same blocks in the same
+    /// order. As with [`commit`], the disk is not flushed.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`commit`]: Self::commit
+    pub async fn commit_async<Io: AsyncBlockIo>(
+        &mut self,
+        disk: &mut AsyncDisk<Io>,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let (primary, secondary) =
+            self.headers_for_commit(disk.io.block_size())?;
+        self.write_copy_async(disk, &secondary, block_buf).await?;
+        self.write_copy_async(disk, &primary, block_buf).await?;
+        self.header = primary;
+        Ok(())
+    }
+
+    /// Get the primary and secondary headers to write in [`commit`].
+    ///
+    /// [`commit`]: Self::commit
+    pub(crate) fn headers_for_commit<E: Debug + Display>(
+        &self,
+        block_size: BlockSize,
+    ) -> Result<(GptHeader, GptHeader), DiskError<E>> {
+        let primary = self.primary_header();
+        let secondary = self
+            .secondary_header(block_size)
+            .ok_or(DiskError::Overflow)?;
+        Ok((primary, secondary))
+    }
+
+    /// Write the partition entry array to the location in `header`,
+    /// followed by `header` itself.
+    pub(crate) fn write_copy<Io: BlockIo>(
+        &mut self,
+        disk: &mut Disk<Io>,
+        header: &GptHeader,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let primary_array_lba = self.entry_array.layout().start_lba;
+        self.entry_array
+            .set_start_lba(header.partition_entry_lba.into());
+        let r = disk.write_gpt_partition_entry_array(&self.entry_array);
+        self.entry_array.set_start_lba(primary_array_lba);
+        r?;
+        disk.write_gpt_header(header.my_lba.into(), header, block_buf)
+    }
+
+    /// Asynchronous version of [`write_copy`].
+    ///
+    /// [`write_copy`]: Self::write_copy
+    pub(crate) async fn write_copy_async<Io: AsyncBlockIo>(
+        &mut self,
+        disk: &mut AsyncDisk<Io>,
+        header: &GptHeader,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let primary_array_lba = self.entry_array.layout().start_lba;
+        self.entry_array
+            .set_start_lba(header.partition_entry_lba.into());
+        let r = disk
+            .write_gpt_partition_entry_array(&self.entry_array)
+            .await;
+        self.entry_array.set_start_lba(primary_array_lba);
+        r?;
+        disk.write_gpt_header(header.my_lba.into(), header, block_buf)
+            .await
+    }
+}
+
+impl<Io: BlockIo, Mode: DiskMode> Disk<Io, Mode> {
+    /// Read the primary GPT header and partition entry array into a
+    /// [`GptTable`].
+    ///
+    /// The header's signature and checksum are validated, as well as
+    /// the checksum of the partition entry array. Returns
+    /// [`DiskError::InvalidGptHeader`] or
+    /// [`DiskError::InvalidPartitionEntryArray`] if validation fails.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// one block. The `storage` buffer must be at least
+    /// [`layout.num_bytes_rounded_to_block`] in size, where `layout` is
+    /// the primary header's partition entry array layout.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: gpt_disk_types::GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    pub fn read_gpt_table<'buf>(
+        &mut self,
+        block_buf: &mut [u8],
+        storage: &'buf mut [u8],
+    ) -> Result<GptTable<'buf>, DiskError<Io::Error>> {
+        let header = self.read_primary_gpt_header(block_buf)?;
+        let layout = GptTable::check_header(&header)?;
+        let entry_array =
+            self.read_gpt_partition_entry_array(layout, storage)?;
+        GptTable::from_parts(header, entry_array)
+    }
+}
@@ -0,0 +1,538 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::disk::clip_block_buf;
+use crate::verify::calculate_header_crc32_from_block;
+use crate::{
+    AsyncBlockIo, AsyncDisk, BlockIo, Disk, DiskError, DiskMode, GptTable,
+};
+use bytemuck::{bytes_of, pod_read_unaligned};
+use core::mem;
+use gpt_disk_types::{BlockSize, GptHeader, Lba};
+
+/// Signature at the start of an intent record block.
+const INTENT_SIGNATURE: [u8; 8] = *b"GPTINTNT";
+
+/// Revision of the intent record format.
+const INTENT_REVISION: u32 = 1;
+
+/// Byte offsets of the fields in an intent record block. The checksum
+/// is at the same offset as in a [`GptHeader`], so the header checksum
+/// function can be reused.
+const INTENT_REVISION_OFFSET: usize = 8;
+const INTENT_PHASE_OFFSET: usize = 12;
+const INTENT_PRIMARY_OFFSET: usize = 24;
+const INTENT_SECONDARY_OFFSET: usize =
+    INTENT_PRIMARY_OFFSET + mem::size_of::<GptHeader>();
+const INTENT_SIZE: usize =
+    INTENT_SECONDARY_OFFSET + mem::size_of::<GptHeader>();
+
+/// How far a [`GptTable::commit_transactional`] got before it was
+/// interrupted. Stored in the intent record.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum GptCommitPhase {
+    /// The commit started. The secondary header and partition entry
+    /// array may be partially written, but the primary copy of the GPT
+    /// is unchanged.
+    Started,
+
+    /// The secondary header and partition entry array were written and
+    /// flushed. The primary copy may be partially written.
+    SecondaryWritten,
+}
+
+impl GptCommitPhase {
+    fn to_u32(self) -> u32 {
+        match self {
+            Self::Started => 1,
+            Self::SecondaryWritten => 2,
+        }
+    }
+
+    fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            1 => Some(Self::Started),
+            2 => Some(Self::SecondaryWritten),
+            _ => None,
+        }
+    }
+}
+
+/// Record of an in-progress [`GptTable::commit_transactional`]. See
+/// [`Disk::read_gpt_commit_intent`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct GptCommitIntent {
+    /// How far the commit got.
+    pub phase: GptCommitPhase,
+
+    /// Primary header being written.
+    pub primary_header: GptHeader,
+
+    /// Secondary header being written.
+    pub secondary_header: GptHeader,
+}
+
+/// Action taken by [`Disk::recover_gpt_commit`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum GptCommitRecovery {
+    /// No intent record was found, so no commit was interrupted.
+    NotNeeded,
+
+    /// The interrupted commit was rolled back. The disk has the GPT
+    /// from before the commit.
+    RolledBack,
+
+    /// The interrupted commit was completed. The disk has the GPT that
+    /// was being committed.
+    Completed,
+}
+
+/// Check whether `lba` is the location of `header`, is in its
+/// partition entry array, or is in its usable range. The array of a
+/// header with an invalid layout is treated as not overlapping.
+fn header_overlaps(
+    header: &GptHeader,
+    lba: Lba,
+    block_size: BlockSize,
+) -> bool {
+    let lba = lba.to_u64();
+    if lba == header.my_lba.to_u64()
+        || (header.first_usable_lba.to_u64()..=header.last_usable_lba.to_u64())
+            .contains(&lba)
+    {
+        return true;
+    }
+
+    let Ok(layout) = header.get_partition_entry_array_layout() else {
+        return false;
+    };
+    let array_start = layout.start_lba.to_u64();
+    let array_blocks = layout.num_blocks(block_size).unwrap_or(u64::MAX);
+    lba >= array_start && lba - array_start < array_blocks
+}
+
+/// Check that `intent_lba` is on a disk of `num_blocks` blocks, is not
+/// the MBR, and does not overlap any of `headers` (see
+/// [`header_overlaps`]). Headers with an invalid signature are ignored.
+fn is_intent_lba_valid(
+    intent_lba: Lba,
+    num_blocks: u64,
+    block_size: BlockSize,
+    headers: [&GptHeader; 4],
+) -> bool {
+    intent_lba.to_u64() != 0
+        && intent_lba.to_u64() < num_blocks
+        && !headers.into_iter().any(|header| {
+            header.is_signature_valid()
+                && header_overlaps(header, intent_lba, block_size)
+        })
+}
+
+/// Fill `block_buf`, which must be exactly one block, with the intent
+/// record for `intent`.
+fn encode_intent(intent: &GptCommitIntent, block_buf: &mut [u8]) {
+    block_buf.fill(0);
+    block_buf[..8].copy_from_slice(&INTENT_SIGNATURE);
+    block_buf[INTENT_REVISION_OFFSET..][..4]
+        .copy_from_slice(&INTENT_REVISION.to_le_bytes());
+    block_buf[INTENT_PHASE_OFFSET..][..4]
+        .copy_from_slice(&intent.phase.to_u32().to_le_bytes());
+    block_buf[INTENT_PRIMARY_OFFSET..INTENT_SECONDARY_OFFSET]
+        .copy_from_slice(bytes_of(&intent.primary_header));
+    block_buf[INTENT_SECONDARY_OFFSET..INTENT_SIZE]
+        .copy_from_slice(bytes_of(&intent.secondary_header));
+    let crc32 = calculate_header_crc32_from_block(block_buf, INTENT_SIZE);
+    block_buf[16..20].copy_from_slice(&crc32.0 .0);
+}
+
+impl<Io: BlockIo, Mode: DiskMode> Disk<Io, Mode> {
+    /// Read the intent record written by
+    /// [`GptTable::commit_transactional`] from `intent_lba`. Returns
+    /// `None` if the block does not contain a valid intent record,
+    /// which means that no commit using that block was interrupted.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn read_gpt_commit_intent(
+        &mut self,
+        intent_lba: Lba,
+        mut block_buf: &mut [u8],
+    ) -> Result<Option<GptCommitIntent>, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        self.io.read_blocks(intent_lba, block_buf)?;
+
+        let read_u32 = |offset: usize| {
+            // OK to unwrap: the slice is four bytes long.
+            u32::from_le_bytes(block_buf[offset..][..4].try_into().unwrap())
+        };
+        if block_buf[..8] != INTENT_SIGNATURE
+            || read_u32(INTENT_REVISION_OFFSET) != INTENT_REVISION
+            || calculate_header_crc32_from_block(block_buf, INTENT_SIZE)
+                .0
+                 .0
+                != block_buf[16..20]
+        {
+            return Ok(None);
+        }
+        let Some(phase) =
+            GptCommitPhase::from_u32(read_u32(INTENT_PHASE_OFFSET))
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(GptCommitIntent {
+            phase,
+            primary_header: pod_read_unaligned(
+                &block_buf[INTENT_PRIMARY_OFFSET..INTENT_SECONDARY_OFFSET],
+            ),
+            secondary_header: pod_read_unaligned(
+                &block_buf[INTENT_SECONDARY_OFFSET..INTENT_SIZE],
+            ),
+        }))
+    }
+}
+
+impl<Io: BlockIo> Disk<Io> {
+    /// Check that `intent_lba` is on the disk and does not overlap the
+    /// MBR, the GPT currently on the disk, or the GPT in `intent`. The
+    /// usable range of either GPT counts as part of it.
+    fn check_intent_lba(
+        &mut self,
+        intent_lba: Lba,
+        intent: &GptCommitIntent,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let num_blocks = self.io.num_blocks()?;
+        if intent_lba.to_u64() >= num_blocks {
+            return Err(DiskError::InvalidIntentLba);
+        }
+
+        let current_primary = self.read_primary_gpt_header(block_buf)?;
+        let current_secondary =
+            if current_primary.alternate_lba.to_u64() < num_blocks {
+                self.read_gpt_header(
+                    current_primary.alternate_lba.into(),
+                    block_buf,
+                )?
+            } else {
+                GptHeader::default()
+            };
+
+        if is_intent_lba_valid(
+            intent_lba,
+            num_blocks,
+            self.io.block_size(),
+            [
+                &intent.primary_header,
+                &intent.secondary_header,
+                &current_primary,
+                &current_secondary,
+            ],
+        ) {
+            Ok(())
+        } else {
+            Err(DiskError::InvalidIntentLba)
+        }
+    }
+
+    /// Write a block of zeros to `lba`.
+    fn zero_block(
+        &mut self,
+        lba: Lba,
+        mut block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        block_buf.fill(0);
+        Ok(self.io.write_blocks(lba, block_buf)?)
+    }
+
+    /// Write an intent record to `intent_lba` and flush the disk.
+    fn write_gpt_commit_intent(
+        &mut self,
+        intent_lba: Lba,
+        intent: &GptCommitIntent,
+        mut block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        encode_intent(intent, block_buf);
+        self.io.write_blocks(intent_lba, block_buf)?;
+        self.flush()
+    }
+
+    /// Zero the intent record at `intent_lba` and flush the disk.
+    fn clear_gpt_commit_intent(
+        &mut self,
+        intent_lba: Lba,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.zero_block(intent_lba, block_buf)?;
+        self.flush()
+    }
+
+    /// Finish or roll back a [`GptTable::commit_transactional`] that
+    /// was interrupted, for example by a power failure. This should be
+    /// called with the same `intent_lba` before the GPT is otherwise
+    /// used.
+    ///
+    /// If the intent record shows that the secondary copy of the GPT
+    /// was completely written, the commit is finished by copying the
+    /// secondary partition entry array to the primary location and
+    /// writing the new primary header. Otherwise, the primary copy is
+    /// unchanged, and the commit is rolled back by rebuilding the
+    /// secondary copy from it with [`Disk::repair_gpt`].
+    ///
+    /// The intent record is cleared afterwards. Nothing is written if
+    /// there is no intent record.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// one block. The `storage` buffer must be large enough to hold the
+    /// partition entry array.
+    pub fn recover_gpt_commit(
+        &mut self,
+        intent_lba: Lba,
+        mut block_buf: &mut [u8],
+        storage: &mut [u8],
+    ) -> Result<GptCommitRecovery, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        let Some(intent) =
+            self.read_gpt_commit_intent(intent_lba, block_buf)?
+        else {
+            return Ok(GptCommitRecovery::NotNeeded);
+        };
+
+        let recovery = match intent.phase {
+            GptCommitPhase::Started => {
+                self.repair_gpt(block_buf, storage)?;
+
+                // If the secondary header was moving, the new header may
+                // have been written at the new location. Remove it so
+                // that it cannot be mistaken for the real one.
+                let new_secondary_lba = intent.secondary_header.my_lba.into();
+                if self.read_gpt_header(new_secondary_lba, block_buf)?
+                    == intent.secondary_header
+                {
+                    self.zero_block(new_secondary_lba, block_buf)?;
+                }
+                GptCommitRecovery::RolledBack
+            }
+            GptCommitPhase::SecondaryWritten => {
+                self.finish_gpt_commit(&intent, block_buf, storage)?;
+                GptCommitRecovery::Completed
+            }
+        };
+        self.flush()?;
+        self.clear_gpt_commit_intent(intent_lba, block_buf)?;
+        Ok(recovery)
+    }
+
+    /// Write the primary copy of the GPT in `intent`, using the
+    /// secondary partition entry array already on disk.
+    fn finish_gpt_commit(
+        &mut self,
+        intent: &GptCommitIntent,
+        block_buf: &mut [u8],
+        storage: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let primary = &intent.primary_header;
+        let secondary = &intent.secondary_header;
+        let layout = secondary
+            .get_partition_entry_array_layout()
+            .map_err(|_| DiskError::InvalidGptHeader)?;
+        let mut array = self.read_gpt_partition_entry_array(layout, storage)?;
+        if array.calculate_crc32() != primary.partition_entry_array_crc32 {
+            return Err(DiskError::InvalidPartitionEntryArray);
+        }
+
+        // The secondary header was flushed before the intent record
+        // was updated, but rewriting it is harmless.
+        self.write_gpt_header(secondary.my_lba.into(), secondary, block_buf)?;
+
+        array.set_start_lba(primary.partition_entry_lba.into());
+        self.write_gpt_partition_entry_array(&array)?;
+        self.write_gpt_header(primary.my_lba.into(), primary, block_buf)
+    }
+}
+
+impl<Io: AsyncBlockIo> AsyncDisk<Io> {
+    /// Asynchronous version of [`Disk::check_intent_lba`].
+    async fn check_intent_lba(
+        &mut self,
+        intent_lba: Lba,
+        intent: &GptCommitIntent,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let num_blocks = self.num_blocks().await?;
+        if intent_lba.to_u64() >= num_blocks {
+            return Err(DiskError::InvalidIntentLba);
+        }
+
+        let current_primary = self.read_primary_gpt_header(block_buf).await?;
+        let current_secondary =
+            if current_primary.alternate_lba.to_u64() < num_blocks {
+                self.read_gpt_header(
+                    current_primary.alternate_lba.into(),
+                    block_buf,
+                )
+                .await?
+            } else {
+                GptHeader::default()
+            };
+
+        if is_intent_lba_valid(
+            intent_lba,
+            num_blocks,
+            self.io.block_size(),
+            [
+                &intent.primary_header,
+                &intent.secondary_header,
+                &current_primary,
+                &current_secondary,
+            ],
+        ) {
+            Ok(())
+        } else {
+            Err(DiskError::InvalidIntentLba)
+        }
+    }
+
+    /// Asynchronous version of [`Disk::write_gpt_commit_intent`].
+    async fn write_gpt_commit_intent(
+        &mut self,
+        intent_lba: Lba,
+        intent: &GptCommitIntent,
+        mut block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        block_buf = clip_block_buf(self.io.block_size(), block_buf)?;
+        encode_intent(intent, block_buf);
+        self.write_blocks(intent_lba, block_buf).await?;
+        self.flush().await
+    }
+
+    /// Asynchronous version of [`Disk::clear_gpt_commit_intent`].
+    async fn clear_gpt_commit_intent(
+        &mut self,
+        intent_lba: Lba,
+        mut block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        block_buf = clip_block_buf(self.io.block_size(), block_buf)?;
+        block_buf.fill(0);
+        self.write_blocks(intent_lba, block_buf).await?;
+        self.flush().await
+    }
+}
+
+impl GptTable<'_> {
+    /// Write the table to `disk` in an order that leaves a valid GPT
+    /// on the disk if the commit is interrupted at any point:
+    ///
+    /// 1. The secondary partition entry array and header are written,
+    ///    then the disk is flushed.
+    /// 2. The primary partition entry array and header are written,
+    ///    then the disk is flushed.
+    ///
+    /// Until step 1 completes the primary copy is intact, and after
+    /// that the secondary copy is intact, so [`Disk::repair_gpt`] can
+    /// always restore a consistent GPT. However, the intact copy may
+    /// be either the old or the new table.
+    ///
+    /// If `intent_lba` is set, an intent record is also written to that
+    /// block before each step, and cleared at the end, so that
+    /// [`Disk::recover_gpt_commit`] can tell which copy to keep. The
+    /// block must not be used for anything else, so it must be one of
+    /// the reserved blocks outside the usable range of both the current
+    /// and the new GPT. [`GptBuilder`] leaves such blocks after the
+    /// primary partition entry array when the array is smaller than
+    /// [`GptBuilder::MIN_PARTITION_ENTRY_ARRAY_BYTES`]. Returns
+    /// [`DiskError::InvalidIntentLba`] if the block is outside the
+    /// disk, overlaps the GPT, or is in the usable range.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`GptBuilder`]: crate::GptBuilder
+    /// [`GptBuilder::MIN_PARTITION_ENTRY_ARRAY_BYTES`]: crate::GptBuilder::MIN_PARTITION_ENTRY_ARRAY_BYTES
+    pub fn commit_transactional<Io: BlockIo>(
+        &mut self,
+        disk: &mut Disk<Io>,
+        intent_lba: Option<Lba>,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let (primary, secondary) =
+            self.headers_for_commit(disk.io.block_size())?;
+        let mut intent = GptCommitIntent {
+            phase: GptCommitPhase::Started,
+            primary_header: primary,
+            secondary_header: secondary,
+        };
+        if let Some(intent_lba) = intent_lba {
+            disk.check_intent_lba(intent_lba, &intent, block_buf)?;
+            disk.write_gpt_commit_intent(intent_lba, &intent, block_buf)?;
+        }
+
+        self.write_copy(disk, &secondary, block_buf)?;
+        disk.flush()?;
+        if let Some(intent_lba) = intent_lba {
+            intent.phase = GptCommitPhase::SecondaryWritten;
+            disk.write_gpt_commit_intent(intent_lba, &intent, block_buf)?;
+        }
+
+        self.write_copy(disk, &primary, block_buf)?;
+        disk.flush()?;
+        if let Some(intent_lba) = intent_lba {
+            disk.clear_gpt_commit_intent(intent_lba, block_buf)?;
+        }
+
+        self.header = primary;
+        Ok(())
+    }
+
+    /// Write the table to an [`AsyncDisk`]. This is the asynchronous
+    /// version of [`commit_transactional`], and writes and flushes the
+    /// same blocks in the same order, including the intent record if
+    /// `intent_lba` is set. An interrupted commit is recovered with
+    /// [`Disk::repair_gpt`] or [`Disk::recover_gpt_commit`].
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`commit_transactional`]: Self::commit_transactional
+    pub async fn commit_transactional_async<Io: AsyncBlockIo>(
+        &mut self,
+        disk: &mut AsyncDisk<Io>,
+        intent_lba: Option<Lba>,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let (primary, secondary) =
+            self.headers_for_commit(disk.io.block_size())?;
+        let mut intent = GptCommitIntent {
+            phase: GptCommitPhase::Started,
+            primary_header: primary,
+            secondary_header: secondary,
+        };
+        if let Some(intent_lba) = intent_lba {
+            disk.check_intent_lba(intent_lba, &intent, block_buf)
+                .await?;
+            disk.write_gpt_commit_intent(intent_lba, &intent, block_buf)
+                .await?;
+        }
+
+        self.write_copy_async(disk, &secondary, block_buf).await?;
+        disk.flush().await?;
+        if let Some(intent_lba) = intent_lba {
+            intent.phase = GptCommitPhase::SecondaryWritten;
+            disk.write_gpt_commit_intent(intent_lba, &intent, block_buf)
+                .await?;
+        }
+
+        self.write_copy_async(disk, &primary, block_buf).await?;
+        disk.flush().await?;
+        if let Some(intent_lba) = intent_lba {
+            disk.clear_gpt_commit_intent(intent_lba, block_buf).await?;
+        }
+
+        self.header = primary;
+        Ok(())
+    }
+}
@@ -0,0 +1,944 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{BlockIo, Disk, DiskError, DiskMode, GptBuilder};
+use bytemuck::pod_read_unaligned;
+use core::fmt::{self, Display, Formatter};
+use core::mem;
+use gpt_disk_types::{
+    BlockSize, Crc32, GptHeader, GptHeaderRevision, GptPartitionEntryArray,
+    GptPartitionEntryArrayLayout, Lba, LbaRangeInclusive, MasterBootRecord,
+    MbrKind, MbrPartitionRecord, U32Le,
+};
+
+#[cfg(feature = "alloc")]
+use {alloc::vec, alloc::vec::Vec};
+
+/// Severity of a [`GptVerifyFinding`] or [`MbrVerifyFinding`].
+///
+/// [`MbrVerifyFinding`]: crate::MbrVerifyFinding
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum VerifySeverity {
+    /// The partition table deviates from common practice, or from a
+    /// recommendation of the UEFI Specification, but is still usable.
+    Warning,
+
+    /// The partition table violates a requirement of the UEFI
+    /// Specification.
+    Error,
+}
+
+impl Display for VerifySeverity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => f.write_str("warning"),
+            Self::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// Which of the two GPT headers a [`GptVerifyFinding`] refers to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum GptHeaderCopy {
+    /// The primary header in the second block of the disk.
+    Primary,
+
+    /// The secondary (backup) header in the last block of the disk.
+    Secondary,
+}
+
+impl Display for GptHeaderCopy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Primary => f.write_str("primary"),
+            Self::Secondary => f.write_str("secondary"),
+        }
+    }
+}
+
+/// Field of a [`GptHeader`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum GptHeaderField {
+    /// [`GptHeader::signature`].
+    Signature,
+
+    /// [`GptHeader::revision`].
+    Revision,
+
+    /// [`GptHeader::header_size`].
+    HeaderSize,
+
+    /// [`GptHeader::header_crc32`].
+    HeaderCrc32,
+
+    /// [`GptHeader::reserved`].
+    Reserved,
+
+    /// [`GptHeader::my_lba`].
+    MyLba,
+
+    /// [`GptHeader::alternate_lba`].
+    AlternateLba,
+
+    /// [`GptHeader::first_usable_lba`].
+    FirstUsableLba,
+
+    /// [`GptHeader::last_usable_lba`].
+    LastUsableLba,
+
+    /// [`GptHeader::disk_guid`].
+    DiskGuid,
+
+    /// [`GptHeader::partition_entry_lba`].
+    PartitionEntryLba,
+
+    /// [`GptHeader::number_of_partition_entries`].
+    NumberOfPartitionEntries,
+
+    /// [`GptHeader::size_of_partition_entry`].
+    SizeOfPartitionEntry,
+
+    /// [`GptHeader::partition_entry_array_crc32`].
+    PartitionEntryArrayCrc32,
+}
+
+impl Display for GptHeaderField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Signature => "signature",
+            Self::Revision => "revision",
+            Self::HeaderSize => "header_size",
+            Self::HeaderCrc32 => "header_crc32",
+            Self::Reserved => "reserved",
+            Self::MyLba => "my_lba",
+            Self::AlternateLba => "alternate_lba",
+            Self::FirstUsableLba => "first_usable_lba",
+            Self::LastUsableLba => "last_usable_lba",
+            Self::DiskGuid => "disk_guid",
+            Self::PartitionEntryLba => "partition_entry_lba",
+            Self::NumberOfPartitionEntries => "number_of_partition_entries",
+            Self::SizeOfPartitionEntry => "size_of_partition_entry",
+            Self::PartitionEntryArrayCrc32 => "partition_entry_array_crc32",
+        })
+    }
+}
+
+/// Problem found by [`Disk::verify_gpt_with`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum GptVerifyIssue {
+    /// A header field has an invalid value. If the signature is
+    /// invalid, no other checks are done on that header.
+    InvalidHeaderField {
+        /// Which header the field is in.
+        header: GptHeaderCopy,
+
+        /// The invalid field.
+        field: GptHeaderField,
+    },
+
+    /// A header field does not agree between the primary and secondary
+    /// headers. For [`GptHeaderField::AlternateLba`], the primary
+    /// header's `alternate_lba` does not match the secondary header's
+    /// `my_lba`. For [`GptHeaderField::MyLba`], the secondary header's
+    /// `alternate_lba` does not match the primary header's `my_lba`.
+    HeaderFieldMismatch {
+        /// The mismatched field.
+        field: GptHeaderField,
+    },
+
+    /// A used partition entry's ending LBA is less than its starting
+    /// LBA.
+    InvalidEntryRange {
+        /// Index of the partition entry.
+        index: u32,
+    },
+
+    /// A used partition entry is not entirely within the usable range
+    /// of the disk.
+    EntryOutsideUsableRange {
+        /// Index of the partition entry.
+        index: u32,
+    },
+
+    /// Two used partition entries overlap.
+    EntryOverlap {
+        /// Index of the first partition entry.
+        index: u32,
+
+        /// Index of the second partition entry.
+        other_index: u32,
+    },
+
+    /// Two used partition entries have the same unique partition GUID.
+    DuplicateUniquePartitionGuid {
+        /// Index of the first partition entry.
+        index: u32,
+
+        /// Index of the second partition entry.
+        other_index: u32,
+    },
+
+    /// The MBR signature is not `0x55, 0xaa`.
+    InvalidMbrSignature,
+
+    /// The MBR does not contain a partition with an OS type of `0xee`.
+    MissingProtectivePartition,
+
+    /// The protective partition's starting LBA is not 1.
+    InvalidProtectivePartitionStart {
+        /// Index of the MBR partition record.
+        index: usize,
+    },
+
+    /// The protective partition does not cover the whole disk.
+    InvalidProtectivePartitionSize {
+        /// Index of the MBR partition record.
+        index: usize,
+    },
+
+    /// The MBR contains a partition other than the protective
+    /// partition, as in a hybrid MBR.
+    ExtraMbrPartition {
+        /// Index of the MBR partition record.
+        index: usize,
+    },
+}
+
+impl Display for GptVerifyIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeaderField { header, field } => {
+                write!(f, "{header} header: invalid {field}")
+            }
+            Self::HeaderFieldMismatch { field } => {
+                write!(f, "primary and secondary headers disagree on {field}")
+            }
+            Self::InvalidEntryRange { index } => {
+                write!(f, "partition {index}: ending LBA before starting LBA")
+            }
+            Self::EntryOutsideUsableRange { index } => {
+                write!(f, "partition {index}: outside the usable range")
+            }
+            Self::EntryOverlap { index, other_index } => {
+                write!(f, "partition {index} overlaps partition {other_index}")
+            }
+            Self::DuplicateUniquePartitionGuid { index, other_index } => {
+                write!(
+                    f,
+                    "partition {index} has the same unique GUID as \
+                     partition {other_index}"
+                )
+            }
+            Self::InvalidMbrSignature => f.write_str("invalid MBR signature"),
+            Self::MissingProtectivePartition => {
+                f.write_str("MBR has no protective partition")
+            }
+            Self::InvalidProtectivePartitionStart { index } => {
+                write!(
+                    f,
+                    "MBR partition {index}: protective partition does not \
+                     start at LBA 1"
+                )
+            }
+            Self::InvalidProtectivePartitionSize { index } => {
+                write!(
+                    f,
+                    "MBR partition {index}: protective partition does not \
+                     cover the disk"
+                )
+            }
+            Self::ExtraMbrPartition { index } => {
+                write!(f, "MBR partition {index}: not a protective partition")
+            }
+        }
+    }
+}
+
+/// A single result of GPT verification. See [`Disk::verify_gpt_with`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct GptVerifyFinding {
+    /// How severe the problem is.
+    pub severity: VerifySeverity,
+
+    /// What the problem is.
+    pub issue: GptVerifyIssue,
+}
+
+impl GptVerifyFinding {
+    fn error(issue: GptVerifyIssue) -> Self {
+        Self {
+            severity: VerifySeverity::Error,
+            issue,
+        }
+    }
+
+    fn warning(issue: GptVerifyIssue) -> Self {
+        Self {
+            severity: VerifySeverity::Warning,
+            issue,
+        }
+    }
+}
+
+impl Display for GptVerifyFinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.issue)
+    }
+}
+
+/// Report of all problems found by [`Disk::verify_gpt`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct GptVerifyReport {
+    findings: Vec<GptVerifyFinding>,
+}
+
+#[cfg(feature = "alloc")]
+impl GptVerifyReport {
+    /// Get all findings, in the order they were found.
+    #[must_use]
+    pub fn findings(&self) -> &[GptVerifyFinding] {
+        &self.findings
+    }
+
+    /// Get an iterator over the findings with
+    /// [`VerifySeverity::Error`].
+    pub fn errors(&self) -> impl Iterator<Item = &GptVerifyFinding> {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == VerifySeverity::Error)
+    }
+
+    /// True if there are no findings with [`VerifySeverity::Error`].
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Display for GptVerifyReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for finding in &self.findings {
+            writeln!(f, "{finding}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Calculate a header's CRC32 checksum from the raw bytes of the block
+/// containing it. Unlike [`GptHeader::calculate_header_crc32`], this
+/// covers all `header_size` bytes, which may be more than the size of
+/// [`GptHeader`].
+pub(crate) fn calculate_header_crc32_from_block(
+    block: &[u8],
+    header_size: usize,
+) -> Crc32 {
+    let crc = gpt_disk_types::crc::Crc::<u32>::new(&Crc32::ALGORITHM);
+    let mut digest = crc.digest();
+    digest.update(&block[..16]);
+    digest.update(&[0u8; 4]); // Zeroes for the `header_crc32` field.
+    digest.update(&block[20..header_size]);
+    Crc32(U32Le(digest.finalize().to_le_bytes()))
+}
+
+/// Get the partition entry array layout of `header` if the array is
+/// located entirely within the disk.
+pub(crate) fn readable_array_layout(
+    header: &GptHeader,
+    block_size: BlockSize,
+    num_blocks: u64,
+) -> Option<GptPartitionEntryArrayLayout> {
+    if !header.is_signature_valid() {
+        return None;
+    }
+    let layout = header.get_partition_entry_array_layout().ok()?;
+    let end = layout
+        .start_lba
+        .to_u64()
+        .checked_add(layout.num_blocks(block_size)?)?;
+    if end <= num_blocks {
+        Some(layout)
+    } else {
+        None
+    }
+}
+
+/// Largest partition entry array that is checked by
+/// [`Disk::verify_gpt_with`]. This is far larger than any real array,
+/// but small enough that a corrupt header cannot cause
+/// [`Disk::verify_gpt`] to allocate an unreasonable amount of memory.
+const MAX_VERIFY_ARRAY_BYTES: u64 = 1024 * 1024;
+
+/// Get the partition entry array layout of `header` if the array is
+/// located entirely within the disk, and is no larger than
+/// [`MAX_VERIFY_ARRAY_BYTES`].
+fn verifiable_array_layout(
+    header: &GptHeader,
+    block_size: BlockSize,
+    num_blocks: u64,
+) -> Option<GptPartitionEntryArrayLayout> {
+    readable_array_layout(header, block_size, num_blocks).filter(|layout| {
+        layout
+            .num_bytes_exact()
+            .map_or(false, |len| len <= MAX_VERIFY_ARRAY_BYTES)
+    })
+}
+
+/// Expected location of a GPT header.
+#[derive(Clone, Copy)]
+pub(crate) struct ExpectedHeader {
+    pub(crate) copy: GptHeaderCopy,
+    pub(crate) my_lba: Lba,
+    pub(crate) alternate_lba: Lba,
+}
+
+/// Check all the fields of a single header. `block` contains the raw
+/// bytes of the block the header was read from.
+pub(crate) fn check_header(
+    header: &GptHeader,
+    block: &[u8],
+    expected: ExpectedHeader,
+    block_size: BlockSize,
+    num_blocks: u64,
+    on_finding: &mut dyn FnMut(GptVerifyFinding),
+) {
+    let mut invalid = |severity, field| {
+        on_finding(GptVerifyFinding {
+            severity,
+            issue: GptVerifyIssue::InvalidHeaderField {
+                header: expected.copy,
+                field,
+            },
+        });
+    };
+    let error = VerifySeverity::Error;
+    let warning = VerifySeverity::Warning;
+
+    if !header.is_signature_valid() {
+        invalid(error, GptHeaderField::Signature);
+        return;
+    }
+
+    if header.revision != GptHeaderRevision::VERSION_1_0 {
+        invalid(warning, GptHeaderField::Revision);
+    }
+
+    let header_size = header.header_size.to_u32();
+    let header_size_valid = usize::try_from(header_size).ok().filter(|size| {
+        *size >= mem::size_of::<GptHeader>() && *size <= block.len()
+    });
+    let header_crc32 = if let Some(header_size) = header_size_valid {
+        calculate_header_crc32_from_block(block, header_size)
+    } else {
+        invalid(error, GptHeaderField::HeaderSize);
+        header.calculate_header_crc32()
+    };
+    if header_crc32 != header.header_crc32 {
+        invalid(error, GptHeaderField::HeaderCrc32);
+    }
+
+    if header.reserved.to_u32() != 0 {
+        invalid(warning, GptHeaderField::Reserved);
+    }
+
+    if header.my_lba.to_u64() != expected.my_lba.to_u64() {
+        invalid(error, GptHeaderField::MyLba);
+    }
+    if header.alternate_lba.to_u64() != expected.alternate_lba.to_u64() {
+        invalid(error, GptHeaderField::AlternateLba);
+    }
+
+    // The usable range must be after the primary header and before
+    // the secondary header.
+    let first_usable = header.first_usable_lba.to_u64();
+    let last_usable = header.last_usable_lba.to_u64();
+    let last_lba = num_blocks.saturating_sub(1);
+    if first_usable < 2 || first_usable > last_usable {
+        invalid(error, GptHeaderField::FirstUsableLba);
+    }
+    if last_usable >= last_lba {
+        invalid(error, GptHeaderField::LastUsableLba);
+    }
+
+    let Ok(layout) = header.get_partition_entry_array_layout() else {
+        invalid(error, GptHeaderField::SizeOfPartitionEntry);
+        return;
+    };
+
+    if layout
+        .num_bytes_exact()
+        .map_or(true, |len| len > MAX_VERIFY_ARRAY_BYTES)
+    {
+        invalid(error, GptHeaderField::NumberOfPartitionEntries);
+    }
+
+    // The UEFI Specification requires at least 16,384 bytes to be
+    // reserved for the partition entry array, between the header and
+    // the usable range.
+    let (reserved_blocks, reserved_field) = match expected.copy {
+        GptHeaderCopy::Primary => (
+            first_usable.saturating_sub(expected.my_lba.to_u64() + 1),
+            GptHeaderField::FirstUsableLba,
+        ),
+        GptHeaderCopy::Secondary => (
+            expected.my_lba.to_u64().saturating_sub(last_usable + 1),
+            GptHeaderField::LastUsableLba,
+        ),
+    };
+    if reserved_blocks.saturating_mul(block_size.to_u64())
+        < GptBuilder::MIN_PARTITION_ENTRY_ARRAY_BYTES
+    {
+        invalid(warning, reserved_field);
+    }
+
+    // The primary array must be between the primary header and the
+    // usable range, and the secondary array must be between the usable
+    // range and the secondary header.
+    let array_start = layout.start_lba.to_u64();
+    let array_end = layout
+        .num_blocks(block_size)
+        .and_then(|n| array_start.checked_add(n))
+        .and_then(|end| end.checked_sub(1));
+    let array_location_valid = match (expected.copy, array_end) {
+        (_, None) => false,
+        (GptHeaderCopy::Primary, Some(array_end)) => {
+            array_start > expected.my_lba.to_u64() && array_end < first_usable
+        }
+        (GptHeaderCopy::Secondary, Some(array_end)) => {
+            array_start > last_usable && array_end < expected.my_lba.to_u64()
+        }
+    };
+    if !array_location_valid {
+        invalid(error, GptHeaderField::PartitionEntryLba);
+    }
+}
+
+/// Check that the fields that must be the same in both headers match.
+fn check_header_pair(
+    primary: &GptHeader,
+    secondary: &GptHeader,
+    on_finding: &mut dyn FnMut(GptVerifyFinding),
+) {
+    let mut mismatch = |is_mismatch, field| {
+        if is_mismatch {
+            on_finding(GptVerifyFinding::error(
+                GptVerifyIssue::HeaderFieldMismatch { field },
+            ));
+        }
+    };
+
+    mismatch(
+        primary.alternate_lba != secondary.my_lba,
+        GptHeaderField::AlternateLba,
+    );
+    mismatch(
+        secondary.alternate_lba != primary.my_lba,
+        GptHeaderField::MyLba,
+    );
+    mismatch(
+        primary.first_usable_lba != secondary.first_usable_lba,
+        GptHeaderField::FirstUsableLba,
+    );
+    mismatch(
+        primary.last_usable_lba != secondary.last_usable_lba,
+        GptHeaderField::LastUsableLba,
+    );
+    mismatch(
+        { primary.disk_guid } != { secondary.disk_guid },
+        GptHeaderField::DiskGuid,
+    );
+    mismatch(
+        primary.number_of_partition_entries
+            != secondary.number_of_partition_entries,
+        GptHeaderField::NumberOfPartitionEntries,
+    );
+    mismatch(
+        primary.size_of_partition_entry != secondary.size_of_partition_entry,
+        GptHeaderField::SizeOfPartitionEntry,
+    );
+    mismatch(
+        primary.partition_entry_array_crc32
+            != secondary.partition_entry_array_crc32,
+        GptHeaderField::PartitionEntryArrayCrc32,
+    );
+}
+
+/// Check the used entries in the partition entry array against the
+/// usable range and against each other.
+fn check_entries(
+    entry_array: &GptPartitionEntryArray,
+    usable: Option<LbaRangeInclusive>,
+    on_finding: &mut dyn FnMut(GptVerifyFinding),
+) {
+    let num_entries = entry_array.layout().num_entries;
+    let get_entry = |index| {
+        // OK to unwrap: the index is within the array.
+        entry_array.get_partition_entry(index).unwrap()
+    };
+
+    for index in 0..num_entries {
+        let entry = get_entry(index);
+        if !entry.is_used() {
+            continue;
+        }
+
+        let Some(range) = entry.lba_range() else {
+            on_finding(GptVerifyFinding::error(
+                GptVerifyIssue::InvalidEntryRange { index },
+            ));
+            continue;
+        };
+
+        let inside_usable = usable.map_or(false, |usable| {
+            range.start() >= usable.start() && range.end() <= usable.end()
+        });
+        if !inside_usable {
+            on_finding(GptVerifyFinding::error(
+                GptVerifyIssue::EntryOutsideUsableRange { index },
+            ));
+        }
+
+        for other_index in (index + 1)..num_entries {
+            let other = get_entry(other_index);
+            if !other.is_used() {
+                continue;
+            }
+
+            if let Some(other_range) = other.lba_range() {
+                if range.start() <= other_range.end()
+                    && other_range.start() <= range.end()
+                {
+                    on_finding(GptVerifyFinding::error(
+                        GptVerifyIssue::EntryOverlap { index, other_index },
+                    ));
+                }
+            }
+
+            if { entry.unique_partition_guid } == {
+                other.unique_partition_guid
+            } {
+                on_finding(GptVerifyFinding::error(
+                    GptVerifyIssue::DuplicateUniquePartitionGuid {
+                        index,
+                        other_index,
+                    },
+                ));
+            }
+        }
+    }
+}
+
+/// Check that the protective partition `record` covers the whole of a
+/// disk with `num_blocks` blocks, except for the MBR.
+pub(crate) fn is_protective_size_valid(
+    record: &MbrPartitionRecord,
+    num_blocks: u64,
+) -> bool {
+    let size = record.size_in_lba.to_u32();
+    // If the disk is too large for the size to be represented, the
+    // UEFI Specification requires `0xffff_ffff`.
+    // `MasterBootRecord::protective_mbr` uses `0xffff_fffe` in that
+    // case, so both are accepted.
+    match u32::try_from(num_blocks.saturating_sub(1)) {
+        Ok(expected_size) => size == expected_size,
+        Err(_) => size >= 0xffff_fffe,
+    }
+}
+
+/// Check that `mbr` is a valid protective MBR for a disk with
+/// `num_blocks` blocks.
+fn check_protective_mbr(
+    mbr: &MasterBootRecord,
+    num_blocks: u64,
+    on_finding: &mut dyn FnMut(GptVerifyFinding),
+) {
+    if !mbr.is_signature_valid() {
+        on_finding(GptVerifyFinding::error(
+            GptVerifyIssue::InvalidMbrSignature,
+        ));
+    }
+
+    // The protective partition of a hybrid MBR only covers the GPT
+    // structures, leaving room for the mirrored partitions. The
+    // mirrored partitions are still reported below.
+    let is_hybrid = mbr.kind() == Some(MbrKind::Hybrid);
+    let mut found_protective = false;
+    for (index, partition) in mbr.partitions.iter().enumerate() {
+        match partition.os_indicator {
+            0 => {}
+            MasterBootRecord::PROTECTIVE_OS_TYPE => {
+                found_protective = true;
+                if partition.starting_lba.to_u32() != 1 {
+                    on_finding(GptVerifyFinding::error(
+                        GptVerifyIssue::InvalidProtectivePartitionStart {
+                            index,
+                        },
+                    ));
+                }
+                if !is_protective_size_valid(partition, num_blocks)
+                    && !is_hybrid
+                {
+                    on_finding(GptVerifyFinding::warning(
+                        GptVerifyIssue::InvalidProtectivePartitionSize {
+                            index,
+                        },
+                    ));
+                }
+            }
+            _ => on_finding(GptVerifyFinding::warning(
+                GptVerifyIssue::ExtraMbrPartition { index },
+            )),
+        }
+    }
+    if !found_protective {
+        on_finding(GptVerifyFinding::error(
+            GptVerifyIssue::MissingProtectivePartition,
+        ));
+    }
+}
+
+impl<Io: BlockIo, Mode: DiskMode> Disk<Io, Mode> {
+    /// Calculate the checksum of each partition entry array in
+    /// `layouts`. `None` layouts are skipped. If `storage` has room for
+    /// both arrays, they are read at once.
+    fn read_array_crcs(
+        &mut self,
+        layouts: [Option<GptPartitionEntryArrayLayout>; 2],
+        storage: &mut [u8],
+    ) -> Result<[Option<Crc32>; 2], DiskError<Io::Error>> {
+        let block_size = self.io.block_size();
+        if let [Some(first), Some(second)] = layouts {
+            let both_len = first
+                .num_bytes_rounded_to_block_as_usize(block_size)
+                .zip(second.num_bytes_rounded_to_block_as_usize(block_size))
+                .and_then(|(first, second)| first.checked_add(second));
+            if both_len.map_or(false, |len| len <= storage.len()) {
+                let (first, second) = self.read_gpt_partition_entry_arrays(
+                    [first, second],
+                    storage,
+                )?;
+                return Ok([
+                    Some(first.calculate_crc32()),
+                    Some(second.calculate_crc32()),
+                ]);
+            }
+        }
+
+        let mut crcs = [None; 2];
+        for (crc, layout) in crcs.iter_mut().zip(layouts) {
+            if let Some(layout) = layout {
+                *crc = Some(
+                    self.read_gpt_partition_entry_array(layout, storage)?
+                        .calculate_crc32(),
+                );
+            }
+        }
+        Ok(crcs)
+    }
+
+    /// Verify the GPT against the requirements of the UEFI
+    /// Specification. Each problem found is passed to `on_finding`;
+    /// if no problems are found, `on_finding` is never called.
+    ///
+    /// The following are checked:
+    /// * The protective MBR's signature and protective partition. In a
+    ///   hybrid MBR, the protective partition is not required to cover
+    ///   the whole disk.
+    /// * Every field of the primary and secondary headers, including
+    ///   the checksum, `my_lba`/`alternate_lba`, the usable range, and
+    ///   the location of the partition entry array.
+    /// * Fields that must match between the two headers.
+    /// * The checksums of both partition entry arrays.
+    /// * Used partition entries: each must be within the usable range,
+    ///   and they must not overlap or share a unique partition GUID.
+    ///   Entries are checked in the first partition entry array that
+    ///   matches its header's checksum, or in the primary array if
+    ///   neither matches.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// one block. The `storage` buffer must be large enough for the
+    /// partition entry array of either header (see
+    /// [`layout.num_bytes_rounded_to_block`]); arrays that do not fit
+    /// on the disk are not read. Neither are arrays larger than 1 MiB,
+    /// which are reported as an invalid
+    /// [`GptHeaderField::NumberOfPartitionEntries`].
+    ///
+    /// If `block_buf` is at least two blocks long, both headers are
+    /// read with a single call to [`BlockIo::read_blocks_vectored`].
+    /// Likewise, if `storage` is large enough for both partition entry
+    /// arrays, both arrays are read with a single call. This reduces
+    /// the number of round trips on devices with high latency.
+    ///
+    /// With the `alloc` feature, `verify_gpt` can be used instead to
+    /// collect the findings into a `GptVerifyReport`.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    pub fn verify_gpt_with(
+        &mut self,
+        block_buf: &mut [u8],
+        storage: &mut [u8],
+        mut on_finding: impl FnMut(GptVerifyFinding),
+    ) -> Result<(), DiskError<Io::Error>> {
+        let on_finding: &mut dyn FnMut(GptVerifyFinding) = &mut on_finding;
+
+        let block_len = self.clip_block_buf_size(block_buf)?.len();
+        let block_size = self.io.block_size();
+        let num_blocks = self.io.num_blocks()?;
+        if num_blocks < 3 {
+            return Err(DiskError::DiskTooSmall);
+        }
+        let last_lba = Lba(num_blocks - 1);
+
+        self.io.read_blocks(Lba(0), &mut block_buf[..block_len])?;
+        let mbr: MasterBootRecord = pod_read_unaligned(
+            &block_buf[..mem::size_of::<MasterBootRecord>()],
+        );
+        check_protective_mbr(&mbr, num_blocks, on_finding);
+
+        let expected_primary = ExpectedHeader {
+            copy: GptHeaderCopy::Primary,
+            my_lba: Lba(1),
+            alternate_lba: last_lba,
+        };
+        let expected_secondary = ExpectedHeader {
+            copy: GptHeaderCopy::Secondary,
+            my_lba: last_lba,
+            alternate_lba: Lba(1),
+        };
+        let mut check = |header: &GptHeader, block: &[u8], expected| {
+            check_header(
+                header, block, expected, block_size, num_blocks, on_finding,
+            );
+        };
+
+        // If there is room for two blocks, read both headers at once.
+        let (primary, secondary) = if let Some(pair_buf) =
+            block_buf.get_mut(..block_len * 2)
+        {
+            let (primary, secondary) = self.read_gpt_headers(pair_buf)?;
+            let (primary_block, secondary_block) = pair_buf.split_at(block_len);
+            check(&primary, primary_block, expected_primary);
+            check(&secondary, secondary_block, expected_secondary);
+            (primary, secondary)
+        } else {
+            let block_buf = &mut block_buf[..block_len];
+            let primary = self.read_gpt_header(Lba(1), block_buf)?;
+            check(&primary, block_buf, expected_primary);
+            let secondary = self.read_gpt_header(last_lba, block_buf)?;
+            check(&secondary, block_buf, expected_secondary);
+            (primary, secondary)
+        };
+
+        if primary.is_signature_valid() && secondary.is_signature_valid() {
+            check_header_pair(&primary, &secondary, on_finding);
+        }
+
+        let array_crcs = self.read_array_crcs(
+            [
+                verifiable_array_layout(&primary, block_size, num_blocks),
+                verifiable_array_layout(&secondary, block_size, num_blocks),
+            ],
+            storage,
+        )?;
+
+        // Check each array's checksum, and find which array to check
+        // the entries of.
+        let mut entries_header: Option<(&GptHeader, bool)> = None;
+        for ((copy, header), array_crc) in [
+            (GptHeaderCopy::Primary, &primary),
+            (GptHeaderCopy::Secondary, &secondary),
+        ]
+        .into_iter()
+        .zip(array_crcs)
+        {
+            let Some(array_crc) = array_crc else {
+                continue;
+            };
+            if array_crc == header.partition_entry_array_crc32 {
+                if entries_header.map_or(true, |(_, crc_valid)| !crc_valid) {
+                    entries_header = Some((header, true));
+                }
+            } else {
+                on_finding(GptVerifyFinding::error(
+                    GptVerifyIssue::InvalidHeaderField {
+                        header: copy,
+                        field: GptHeaderField::PartitionEntryArrayCrc32,
+                    },
+                ));
+                if entries_header.is_none() {
+                    entries_header = Some((header, false));
+                }
+            }
+        }
+
+        if let Some((header, _)) = entries_header {
+            // OK to unwrap: the layout was already checked above.
+            let layout =
+                verifiable_array_layout(header, block_size, num_blocks)
+                    .unwrap();
+            let entry_array =
+                self.read_gpt_partition_entry_array(layout, storage)?;
+            let usable = LbaRangeInclusive::new(
+                header.first_usable_lba.into(),
+                header.last_usable_lba.into(),
+            );
+            check_entries(&entry_array, usable, on_finding);
+        }
+
+        Ok(())
+    }
+
+    /// Verify the GPT against the requirements of the UEFI
+    /// Specification. All problems found are collected into a
+    /// [`GptVerifyReport`]. See [`verify_gpt_with`] for details of what
+    /// is checked.
+    ///
+    /// Buffers for reading the disk are allocated internally. Since
+    /// partition entry arrays larger than 1 MiB are not read, at most
+    /// 2 MiB is allocated for the arrays, regardless of what the
+    /// headers contain.
+    ///
+    /// [`verify_gpt_with`]: Self::verify_gpt_with
+    #[cfg(feature = "alloc")]
+    pub fn verify_gpt(
+        &mut self,
+    ) -> Result<GptVerifyReport, DiskError<Io::Error>> {
+        let block_size = self.io.block_size();
+        let num_blocks = self.io.num_blocks()?;
+        let block_len = block_size.to_usize().ok_or(DiskError::Overflow)?;
+        let mut block_buf =
+            vec![0; block_len.checked_mul(2).ok_or(DiskError::Overflow)?];
+
+        // Find out how much storage is needed to read both partition
+        // entry arrays at once.
+        let mut storage_len: usize = 0;
+        if num_blocks >= 3 {
+            let (primary, secondary) = self.read_gpt_headers(&mut block_buf)?;
+            for header in [primary, secondary] {
+                if let Some(layout) =
+                    verifiable_array_layout(&header, block_size, num_blocks)
+                {
+                    let array_len = layout
+                        .num_bytes_rounded_to_block_as_usize(block_size)
+                        .ok_or(DiskError::Overflow)?;
+                    storage_len = storage_len
+                        .checked_add(array_len)
+                        .ok_or(DiskError::Overflow)?;
+                }
+            }
+        }
+        let mut storage = vec![0; storage_len];
+
+        let mut report = GptVerifyReport::default();
+        self.verify_gpt_with(&mut block_buf, &mut storage, |finding| {
+            report.findings.push(finding);
+        })?;
+        Ok(report)
+    }
+}
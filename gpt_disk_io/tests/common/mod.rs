@@ -8,11 +8,20 @@
 
 use core::fmt::{Debug, Display};
 use core::hash::Hash;
+use gpt_disk_io::{
+    BlockIo, BlockIoAdapter, Disk, GptBuilder, SliceBlockIoError,
+};
 use gpt_disk_types::{
-    guid, Crc32, GptHeader, GptPartitionEntry, GptPartitionType, LbaLe, U32Le,
+    guid, Crc32, GptHeader, GptPartitionEntry, GptPartitionType, LbaLe,
+    MasterBootRecord, MbrPartitionRecord, U32Le,
 };
+use gpt_disk_types::{BlockSize, Lba, LbaRangeInclusive};
+use std::cell::Cell;
 use std::collections::hash_map::DefaultHasher;
 
+#[cfg(feature = "alloc")]
+use gpt_disk_io::FaultInjectingBlockIo;
+
 #[allow(dead_code)]
 pub fn check_derives<T>()
 where
@@ -88,3 +97,404 @@ pub fn create_partition_entry() -> GptPartitionEntry {
         name: "hello world!".parse().unwrap(),
     }
 }
+
+/// Create a partition entry for LBAs 5000..=6000, which is free space
+/// on the test disk.
+#[allow(dead_code)]
+pub fn create_second_partition_entry() -> GptPartitionEntry {
+    GptPartitionEntry {
+        partition_type_guid: GptPartitionType::BASIC_DATA,
+        unique_partition_guid: guid!("a6b1e7c2-3f0d-4d2e-9f43-1b7f5d1e8a20"),
+        starting_lba: LbaLe::from_u64(5000),
+        ending_lba: LbaLe::from_u64(6000),
+        ..create_partition_entry()
+    }
+}
+
+#[allow(dead_code)]
+pub fn range(start: u64, end: u64) -> LbaRangeInclusive {
+    LbaRangeInclusive::new(Lba(start), Lba(end)).unwrap()
+}
+
+/// Check that the GPT on `contents` has no verification findings.
+#[allow(dead_code)]
+pub fn check_gpt_valid(contents: &mut [u8], bs: BlockSize) {
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; 16384];
+    let mut disk = Disk::new(BlockIoAdapter::new(contents, bs)).unwrap();
+    disk.verify_gpt_with(&mut block_buf, &mut array_buf, |finding| {
+        panic!("unexpected finding: {finding}")
+    })
+    .unwrap();
+}
+
+/// Replace the entries of the test disk's partition entry arrays,
+/// updating the checksums of both headers.
+#[allow(dead_code)]
+pub fn set_entries(contents: &mut [u8], entries: &[GptPartitionEntry]) {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+    let mut disk = Disk::new(BlockIoAdapter::new(contents, bs)).unwrap();
+
+    let mut primary = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    let mut secondary = disk.read_secondary_gpt_header(&mut block_buf).unwrap();
+    let layout = primary.get_partition_entry_array_layout().unwrap();
+    let mut array = disk
+        .read_gpt_partition_entry_array(layout, &mut array_buf)
+        .unwrap();
+    for (i, entry) in entries.iter().enumerate() {
+        *array
+            .get_partition_entry_mut(u32::try_from(i).unwrap())
+            .unwrap() = *entry;
+    }
+    disk.write_gpt_partition_entry_array(&array).unwrap();
+    array.set_start_lba(secondary.partition_entry_lba.into());
+    disk.write_gpt_partition_entry_array(&array).unwrap();
+
+    for header in [&mut primary, &mut secondary] {
+        header.partition_entry_array_crc32 = array.calculate_crc32();
+        header.update_header_crc32();
+    }
+    disk.write_primary_gpt_header(&primary, &mut block_buf)
+        .unwrap();
+    disk.write_secondary_gpt_header(&secondary, &mut block_buf)
+        .unwrap();
+    disk.flush().unwrap();
+}
+
+#[allow(dead_code)]
+pub fn mbr_record(
+    os_indicator: u8,
+    start: u32,
+    size: u32,
+) -> MbrPartitionRecord {
+    MbrPartitionRecord {
+        os_indicator,
+        starting_lba: U32Le::from_u32(start),
+        size_in_lba: U32Le::from_u32(size),
+        ..Default::default()
+    }
+}
+
+/// Write an MBR (or extended boot record) with the given partitions
+/// and a valid signature to the 512-byte block at `lba`.
+#[allow(dead_code)]
+pub fn write_mbr_block(
+    contents: &mut [u8],
+    lba: usize,
+    partitions: [MbrPartitionRecord; 4],
+) {
+    let mbr = MasterBootRecord {
+        partitions,
+        signature: [0x55, 0xaa],
+        ..Default::default()
+    };
+    contents[lba * 512..][..512].copy_from_slice(bytemuck::bytes_of(&mbr));
+}
+
+/// Create an MBR disk with a bootable FAT32 partition, an extended
+/// partition containing two logical partitions, and a partition of
+/// unknown type in the last primary slot.
+#[allow(dead_code)]
+pub fn create_mbr_disk() -> Vec<u8> {
+    let mut contents = vec![0; 512 * 8192];
+    let mut fat32 = mbr_record(0x0c, 2048, 1024);
+    fat32.boot_indicator = 0x80;
+    write_mbr_block(
+        &mut contents,
+        0,
+        [
+            fat32,
+            mbr_record(0x0f, 4096, 2048),
+            MbrPartitionRecord::default(),
+            mbr_record(0x42, 7000, 100),
+        ],
+    );
+
+    // The first EBR is at the start of the extended partition. The
+    // logical partition is relative to the EBR, and the link to the
+    // next EBR is relative to the extended partition.
+    write_mbr_block(
+        &mut contents,
+        4096,
+        [
+            mbr_record(0x83, 63, 500),
+            mbr_record(0x05, 1024, 200),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+        ],
+    );
+    write_mbr_block(
+        &mut contents,
+        5120,
+        [
+            mbr_record(0x82, 2, 100),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+        ],
+    );
+    contents
+}
+
+/// Block IO that records each read and write as `(lba, num_blocks)`.
+#[allow(dead_code)]
+pub struct RecordingBlockIo<'a> {
+    pub inner: BlockIoAdapter<&'a mut [u8]>,
+    pub reads: Vec<(u64, usize)>,
+    pub writes: Vec<(u64, usize)>,
+    pub flushes: usize,
+
+    /// Each call to `read_blocks_vectored`. The individual reads are
+    /// also recorded in `reads`.
+    pub vectored_reads: Vec<Vec<(u64, usize)>>,
+
+    /// Each call to `write_blocks_vectored`. The individual writes
+    /// are also recorded in `writes`.
+    pub vectored_writes: Vec<Vec<(u64, usize)>>,
+
+    /// Shared count of reads, for when the IO is owned by a `Disk`.
+    pub read_count: Option<&'a Cell<usize>>,
+}
+
+impl<'a> RecordingBlockIo<'a> {
+    #[allow(dead_code)]
+    pub fn new(storage: &'a mut [u8]) -> Self {
+        Self {
+            inner: BlockIoAdapter::new(storage, BlockSize::BS_512),
+            reads: Vec::new(),
+            writes: Vec::new(),
+            flushes: 0,
+            vectored_reads: Vec::new(),
+            vectored_writes: Vec::new(),
+            read_count: None,
+        }
+    }
+}
+
+impl BlockIo for RecordingBlockIo<'_> {
+    type Error = SliceBlockIoError;
+
+    fn block_size(&self) -> BlockSize {
+        self.inner.block_size()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        self.inner.num_blocks()
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.reads.push((start_lba.0, dst.len() / 512));
+        if let Some(read_count) = self.read_count {
+            read_count.set(read_count.get() + 1);
+        }
+        self.inner.read_blocks(start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.writes.push((start_lba.0, src.len() / 512));
+        self.inner.write_blocks(start_lba, src)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flushes += 1;
+        self.inner.flush()
+    }
+
+    fn read_blocks_vectored(
+        &mut self,
+        requests: &mut [(Lba, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        self.vectored_reads.push(
+            requests
+                .iter()
+                .map(|(lba, dst)| (lba.0, dst.len() / 512))
+                .collect(),
+        );
+        for (lba, dst) in requests {
+            self.read_blocks(*lba, dst)?;
+        }
+        Ok(())
+    }
+
+    fn write_blocks_vectored(
+        &mut self,
+        requests: &[(Lba, &[u8])],
+    ) -> Result<(), Self::Error> {
+        self.vectored_writes.push(
+            requests
+                .iter()
+                .map(|(lba, src)| (lba.0, src.len() / 512))
+                .collect(),
+        );
+        for (lba, src) in requests {
+            self.write_blocks(*lba, src)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check whether the GPT header at `lba` and its partition entry array
+/// are intact.
+#[allow(dead_code)]
+fn is_gpt_copy_intact<Io: BlockIo>(disk: &mut Disk<Io>, lba: Lba) -> bool {
+    let mut block_buf = vec![0; 512];
+    let Ok(header) = disk.read_gpt_header(lba, &mut block_buf) else {
+        return false;
+    };
+    if !header.is_signature_valid()
+        || header.calculate_header_crc32() != header.header_crc32
+        || header.my_lba.to_u64() != lba.to_u64()
+    {
+        return false;
+    }
+    let Ok(layout) = header.get_partition_entry_array_layout() else {
+        return false;
+    };
+    let mut array_buf = vec![0; 512 * 32];
+    disk.read_gpt_partition_entry_array(layout, &mut array_buf)
+        .map_or(false, |array| {
+            array.calculate_crc32() == header.partition_entry_array_crc32
+        })
+}
+
+/// Check whether `contents` has at least one intact copy of the GPT:
+/// a header with a valid signature and checksum, and a partition entry
+/// array that matches the header. The secondary header is looked for
+/// at the primary header's `alternate_lba` and at the end of the disk.
+#[allow(dead_code)]
+pub fn has_intact_gpt(contents: &[u8]) -> bool {
+    let num_blocks = u64::try_from(contents.len() / 512).unwrap();
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents, BlockSize::BS_512)).unwrap();
+    let mut block_buf = vec![0; 512];
+    let primary = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    [Lba(1), primary.alternate_lba.into(), Lba(num_blocks - 1)]
+        .into_iter()
+        .filter(|lba| lba.to_u64() < num_blocks)
+        .any(|lba| is_gpt_copy_intact(&mut disk, lba))
+}
+
+/// Run `op` on a disk containing a copy of `original`, then simulate a
+/// power loss at every point during `op`, including in the middle of
+/// multi-block writes and after writes that were reordered between
+/// flushes. Each resulting disk state must contain at least
+/// one intact copy of the GPT; `check` is then called on it for any
+/// additional checks. Returns the number of states checked.
+#[cfg(feature = "alloc")]
+#[allow(dead_code)]
+pub fn check_crash_safety(
+    original: &[u8],
+    op: impl FnOnce(&mut Disk<&mut FaultInjectingBlockIo<BlockIoAdapter<Vec<u8>>>>),
+    mut check: impl FnMut(&mut [u8]),
+) -> usize {
+    let mut io = FaultInjectingBlockIo::new(BlockIoAdapter::new(
+        original.to_vec(),
+        BlockSize::BS_512,
+    ));
+    let mut disk = Disk::new(&mut io).unwrap();
+    op(&mut disk);
+    drop(disk);
+
+    let mut num_states = 0;
+    io.replay_crashes(original, |state| {
+        assert!(has_intact_gpt(state), "no intact GPT in state {num_states}");
+        check(state);
+        num_states += 1;
+    });
+    num_states
+}
+
+struct SparseChunk {
+    offset: usize,
+    data: [u8; 16],
+}
+
+impl SparseChunk {
+    const fn new(offset: usize, data: [u8; 16]) -> Self {
+        Self { offset, data }
+    }
+}
+
+#[rustfmt::skip]
+const SPARSE_DISK: &[SparseChunk] = &[
+// Test data generated as follows:
+//
+// truncate --size 4MiB disk.bin
+// sgdisk disk.bin \
+//   --disk-guid=57a7feb6-8cd5-4922-b7bd-c78b0914e870 \
+//   --new=1:2048:4096 \
+//   --change-name='1:hello world!' \
+//   --partition-guid=1:37c75ffd-8932-467a-9c56-8cf1f0456b12 \
+//   --typecode=1:ccf0994f-f7e0-4e26-a011-843e38aa2eac
+// hexdump -ve '"SparseChunk::new(0x%_ax, [" 16/1 "%u," "]),\n"' disk.bin \
+//   | grep -v '\[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,\]'
+SparseChunk::new(0x1c0, [2,0,238,130,2,0,1,0,0,0,255,31,0,0,0,0,]),
+SparseChunk::new(0x1f0, [0,0,0,0,0,0,0,0,0,0,0,0,0,0,85,170,]),
+SparseChunk::new(0x200, [69,70,73,32,80,65,82,84,0,0,1,0,92,0,0,0,]),
+SparseChunk::new(0x210, [67,120,135,164,0,0,0,0,1,0,0,0,0,0,0,0,]),
+SparseChunk::new(0x220, [255,31,0,0,0,0,0,0,34,0,0,0,0,0,0,0,]),
+SparseChunk::new(0x230, [222,31,0,0,0,0,0,0,182,254,167,87,213,140,34,73,]),
+SparseChunk::new(0x240, [183,189,199,139,9,20,232,112,2,0,0,0,0,0,0,0,]),
+SparseChunk::new(0x250, [128,0,0,0,128,0,0,0,255,173,6,146,0,0,0,0,]),
+SparseChunk::new(0x400, [79,153,240,204,224,247,38,78,160,17,132,62,56,170,46,172,]),
+SparseChunk::new(0x410, [253,95,199,55,50,137,122,70,156,86,140,241,240,69,107,18,]),
+SparseChunk::new(0x420, [0,8,0,0,0,0,0,0,0,16,0,0,0,0,0,0,]),
+SparseChunk::new(0x430, [0,0,0,0,0,0,0,0,104,0,101,0,108,0,108,0,]),
+SparseChunk::new(0x440, [111,0,32,0,119,0,111,0,114,0,108,0,100,0,33,0,]),
+SparseChunk::new(0x3fbe00, [79,153,240,204,224,247,38,78,160,17,132,62,56,170,46,172,]),
+SparseChunk::new(0x3fbe10, [253,95,199,55,50,137,122,70,156,86,140,241,240,69,107,18,]),
+SparseChunk::new(0x3fbe20, [0,8,0,0,0,0,0,0,0,16,0,0,0,0,0,0,]),
+SparseChunk::new(0x3fbe30, [0,0,0,0,0,0,0,0,104,0,101,0,108,0,108,0,]),
+SparseChunk::new(0x3fbe40, [111,0,32,0,119,0,111,0,114,0,108,0,100,0,33,0,]),
+SparseChunk::new(0x3ffe00, [69,70,73,32,80,65,82,84,0,0,1,0,92,0,0,0,]),
+SparseChunk::new(0x3ffe10, [19,76,235,219,0,0,0,0,255,31,0,0,0,0,0,0,]),
+SparseChunk::new(0x3ffe20, [1,0,0,0,0,0,0,0,34,0,0,0,0,0,0,0,]),
+SparseChunk::new(0x3ffe30, [222,31,0,0,0,0,0,0,182,254,167,87,213,140,34,73,]),
+SparseChunk::new(0x3ffe40, [183,189,199,139,9,20,232,112,223,31,0,0,0,0,0,0,]),
+SparseChunk::new(0x3ffe50, [128,0,0,0,128,0,0,0,255,173,6,146,0,0,0,0,]),
+];
+
+#[allow(dead_code)]
+pub fn load_test_disk() -> Vec<u8> {
+    let mut disk = vec![0; 4 * 1024 * 1024];
+    for chunk in SPARSE_DISK {
+        let end = chunk.offset + chunk.data.len();
+        disk[chunk.offset..end].copy_from_slice(&chunk.data);
+    }
+    disk
+}
+
+/// Create a disk like [`load_test_disk`], but with only 64 partition
+/// entries. Each array is 16 blocks, so LBAs 18..34 and 8159..8175 are
+/// reserved but unused.
+#[allow(dead_code)]
+pub fn load_test_disk_with_reserved_blocks() -> Vec<u8> {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0; 512];
+    let mut array_buf = vec![0; 512 * 16];
+    let mut contents = vec![0; 4 * 1024 * 1024];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    disk.format_gpt(
+        &GptBuilder::new(guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870"))
+            .num_partition_entries(64),
+        &mut block_buf,
+    )
+    .unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    table.add_partition(create_partition_entry()).unwrap();
+    table.commit(&mut disk, &mut block_buf).unwrap();
+    drop(disk);
+    contents
+}
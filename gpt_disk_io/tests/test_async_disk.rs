@@ -0,0 +1,448 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{
+    create_partition_entry, create_primary_header,
+    create_second_partition_entry, create_secondary_header, load_test_disk,
+    load_test_disk_with_reserved_blocks, RecordingBlockIo,
+};
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+use gpt_disk_io::{
+    AsyncBlockIo, AsyncBlockIoAdapter, AsyncDisk, BlockIo, BlockIoAdapter,
+    Disk, DiskError, SliceBlockIoError,
+};
+use gpt_disk_types::{BlockSize, GptPartitionEntryArray, Lba};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::Wake;
+
+const BS: usize = 512;
+
+/// Waker that counts how many times it was woken.
+#[derive(Default)]
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Run `future` to completion on the current thread, returning its
+/// output and the number of times it was woken.
+fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return (output, counter.0.load(Ordering::Relaxed));
+        }
+    }
+}
+
+/// Async block IO that returns `Pending` once before completing each
+/// operation, as a device with a request queue would.
+struct PendingBlockIo<Io> {
+    inner: AsyncBlockIoAdapter<Io>,
+    ready: bool,
+}
+
+impl<Io: BlockIo> PendingBlockIo<Io> {
+    fn new(io: Io) -> Self {
+        Self {
+            inner: AsyncBlockIoAdapter::new(io),
+            ready: false,
+        }
+    }
+
+    fn poll<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        op: impl FnOnce(&mut AsyncBlockIoAdapter<Io>, &mut Context<'_>) -> Poll<T>,
+    ) -> Poll<T> {
+        if self.ready {
+            self.ready = false;
+            op(&mut self.inner, cx)
+        } else {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+impl<Io: BlockIo> AsyncBlockIo for PendingBlockIo<Io> {
+    type Error = Io::Error;
+
+    fn block_size(&self) -> BlockSize {
+        self.inner.block_size()
+    }
+
+    fn poll_num_blocks(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<u64, Self::Error>> {
+        self.poll(cx, |io, cx| io.poll_num_blocks(cx))
+    }
+
+    fn poll_read_blocks(
+        &mut self,
+        cx: &mut Context<'_>,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Poll<Result<(), Self::Error>> {
+        self.poll(cx, |io, cx| io.poll_read_blocks(cx, start_lba, dst))
+    }
+
+    fn poll_write_blocks(
+        &mut self,
+        cx: &mut Context<'_>,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Poll<Result<(), Self::Error>> {
+        self.poll(cx, |io, cx| io.poll_write_blocks(cx, start_lba, src))
+    }
+
+    fn poll_flush(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.poll(cx, |io, cx| io.poll_flush(cx))
+    }
+}
+
+#[test]
+fn test_async_disk_read() {
+    let mut contents = load_test_disk();
+    let io = PendingBlockIo::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    ));
+    let mut disk = AsyncDisk::new(io).unwrap();
+
+    let (result, num_wakes) = block_on(async {
+        let mut block_buf = vec![0; BS];
+        let mut array_buf = vec![0; BS * 32];
+
+        let mbr = disk.read_mbr(&mut block_buf).await?;
+        assert_eq!(mbr.partitions[0].os_indicator, 0xee);
+
+        let primary = disk.read_primary_gpt_header(&mut block_buf).await?;
+        assert_eq!(primary, create_primary_header());
+        let secondary = disk.read_secondary_gpt_header(&mut block_buf).await?;
+        assert_eq!(secondary, create_secondary_header());
+
+        let layout = primary.get_partition_entry_array_layout().unwrap();
+        let array = disk
+            .read_gpt_partition_entry_array(layout, &mut array_buf)
+            .await?;
+        assert_eq!(
+            array.get_partition_entry(0),
+            Some(&create_partition_entry())
+        );
+
+        let mut iter =
+            disk.gpt_partition_entry_array_iter(layout, &mut block_buf)?;
+        let mut entries = Vec::new();
+        while let Some(entry) = iter.next_entry().await {
+            entries.push(entry?);
+        }
+        assert_eq!(entries.len(), 128);
+        assert_eq!(entries[0], create_partition_entry());
+        assert!(entries[1..].iter().all(|entry| !entry.is_used()));
+
+        let table = disk.read_gpt_table(&mut block_buf, &mut array_buf).await?;
+        assert_eq!(table.primary_header(), primary);
+        Ok::<_, DiskError<SliceBlockIoError>>(())
+    });
+    result.unwrap();
+
+    // Each operation was pending once, and woke the task.
+    assert!(num_wakes > 5);
+}
+
+#[test]
+fn test_async_disk_write() {
+    let bs = BlockSize::BS_512;
+    let primary = create_primary_header();
+    let secondary = create_secondary_header();
+    let layouts = [primary, secondary]
+        .map(|header| header.get_partition_entry_array_layout().unwrap());
+
+    // Write the GPT with `Disk`.
+    let mut expected = vec![0; 4 * 1024 * 1024];
+    {
+        let mut block_buf = vec![0; BS];
+        let mut disk =
+            Disk::new(BlockIoAdapter::new(expected.as_mut_slice(), bs))
+                .unwrap();
+        disk.write_protective_mbr(&mut block_buf).unwrap();
+        disk.write_primary_gpt_header(&primary, &mut block_buf)
+            .unwrap();
+        disk.write_secondary_gpt_header(&secondary, &mut block_buf)
+            .unwrap();
+        let mut bytes = vec![0; BS * 32];
+        let mut array =
+            GptPartitionEntryArray::new(layouts[0], bs, &mut bytes).unwrap();
+        *array.get_partition_entry_mut(0).unwrap() = create_partition_entry();
+        disk.write_gpt_partition_entry_array(&array).unwrap();
+        array.set_start_lba(layouts[1].start_lba);
+        disk.write_gpt_partition_entry_array(&array).unwrap();
+    }
+
+    // Writing the same GPT with `AsyncDisk` gives the same result.
+    let mut contents = vec![0u8; expected.len()];
+    let io =
+        PendingBlockIo::new(BlockIoAdapter::new(contents.as_mut_slice(), bs));
+    let mut disk = AsyncDisk::new(io).unwrap();
+    let (result, _) = block_on(async {
+        let mut block_buf = vec![0; BS];
+        disk.write_protective_mbr(&mut block_buf).await?;
+        disk.write_primary_gpt_header(&primary, &mut block_buf)
+            .await?;
+        disk.write_secondary_gpt_header(&secondary, &mut block_buf)
+            .await?;
+        let mut bytes = vec![0; BS * 32];
+        let mut array =
+            GptPartitionEntryArray::new(layouts[0], bs, &mut bytes).unwrap();
+        *array.get_partition_entry_mut(0).unwrap() = create_partition_entry();
+        disk.write_gpt_partition_entry_array(&array).await?;
+        array.set_start_lba(layouts[1].start_lba);
+        disk.write_gpt_partition_entry_array(&array).await?;
+        disk.flush().await
+    });
+    result.unwrap();
+    assert!(contents == expected);
+}
+
+#[test]
+fn test_async_disk_read_vectored() {
+    let primary = create_primary_header();
+    let secondary = create_secondary_header();
+    let layouts = [primary, secondary]
+        .map(|header| header.get_partition_entry_array_layout().unwrap());
+
+    // The default vectored read completes one request at a time.
+    let mut contents = load_test_disk();
+    let io = PendingBlockIo::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    ));
+    let mut disk = AsyncDisk::new(io).unwrap();
+    let (result, num_wakes) = block_on(async {
+        let mut block_buf = vec![0; BS * 2];
+        assert_eq!(
+            disk.read_gpt_headers(&mut block_buf).await?,
+            (primary, secondary)
+        );
+        assert!(matches!(
+            disk.read_gpt_headers(&mut block_buf[..BS]).await,
+            Err(DiskError::BufferTooSmall)
+        ));
+
+        let mut arrays_buf = vec![0; BS * 64];
+        let (primary_array, secondary_array) = disk
+            .read_gpt_partition_entry_arrays(layouts, &mut arrays_buf)
+            .await?;
+        assert_eq!(
+            primary_array.get_partition_entry(0),
+            Some(&create_partition_entry())
+        );
+        assert_eq!(primary_array.storage(), secondary_array.storage());
+        Ok::<_, DiskError<SliceBlockIoError>>(())
+    });
+    result.unwrap();
+
+    // Each `read_gpt_headers` call gets the number of blocks, and each
+    // of the four requests is read separately.
+    assert_eq!(num_wakes, 6);
+
+    // The adapter passes all of the requests on together.
+    let mut contents = load_test_disk();
+    let io = AsyncBlockIoAdapter::new(RecordingBlockIo::new(&mut contents));
+    let mut disk = AsyncDisk::new(io).unwrap();
+    let (result, _) = block_on(async {
+        let mut block_buf = vec![0; BS * 2];
+        disk.read_gpt_headers(&mut block_buf).await?;
+        let mut arrays_buf = vec![0; BS * 64];
+        disk.read_gpt_partition_entry_arrays(layouts, &mut arrays_buf)
+            .await?;
+        Ok::<_, DiskError<SliceBlockIoError>>(())
+    });
+    result.unwrap();
+    assert_eq!(
+        disk.into_inner().inner().vectored_reads,
+        [vec![(1, 1), (8191, 1)], vec![(2, 32), (8159, 32)]]
+    );
+}
+
+#[test]
+fn test_async_disk_commit() {
+    let mut block_buf = vec![0; BS];
+    let mut array_buf = vec![0; BS * 32];
+
+    // Remove the partition with `Disk`.
+    let mut expected = load_test_disk();
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        expected.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    table.remove_partition(0).unwrap();
+    table.commit(&mut disk, &mut block_buf).unwrap();
+    drop(disk);
+
+    // Doing the same with `AsyncDisk` writes the same blocks in the
+    // same order.
+    let mut contents = load_test_disk();
+    let io = PendingBlockIo::new(RecordingBlockIo::new(&mut contents));
+    let mut disk = AsyncDisk::new(io).unwrap();
+    let (result, _) = block_on(async {
+        let mut table =
+            disk.read_gpt_table(&mut block_buf, &mut array_buf).await?;
+        table.remove_partition(0).unwrap();
+        table.commit_async(&mut disk, &mut block_buf).await?;
+        assert_eq!(table.primary_header().header_crc32, {
+            let header = disk.read_primary_gpt_header(&mut block_buf).await?;
+            header.header_crc32
+        });
+        Ok::<_, DiskError<SliceBlockIoError>>(())
+    });
+    result.unwrap();
+    let io = disk.into_inner().inner.into_inner();
+    assert_eq!(io.writes, [(8159, 32), (8191, 1), (2, 32), (1, 1)]);
+    assert!(contents == expected);
+}
+
+#[test]
+fn test_async_disk_commit_transactional() {
+    let mut block_buf = vec![0; BS];
+    let mut array_buf = vec![0; BS * 16];
+    let intent_lba = Lba(20);
+
+    // Add a partition with `Disk`.
+    let mut expected = load_test_disk_with_reserved_blocks();
+    let mut expected_io = RecordingBlockIo::new(&mut expected);
+    let mut disk = Disk::new(&mut expected_io).unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    table
+        .add_partition(create_second_partition_entry())
+        .unwrap();
+    table
+        .commit_transactional(&mut disk, Some(intent_lba), &mut block_buf)
+        .unwrap();
+    drop(disk);
+    let (expected_writes, expected_flushes) =
+        (expected_io.writes.clone(), expected_io.flushes);
+
+    // Doing the same with `AsyncDisk` writes and flushes the same
+    // blocks in the same order.
+    let mut contents = load_test_disk_with_reserved_blocks();
+    let io = PendingBlockIo::new(RecordingBlockIo::new(&mut contents));
+    let mut disk = AsyncDisk::new(io).unwrap();
+    let (result, _) = block_on(async {
+        let mut table =
+            disk.read_gpt_table(&mut block_buf, &mut array_buf).await?;
+        table
+            .add_partition(create_second_partition_entry())
+            .unwrap();
+
+        // The intent record can't be in the partition entry array.
+        assert!(matches!(
+            table
+                .commit_transactional_async(
+                    &mut disk,
+                    Some(Lba(2)),
+                    &mut block_buf
+                )
+                .await,
+            Err(DiskError::InvalidIntentLba)
+        ));
+
+        table
+            .commit_transactional_async(
+                &mut disk,
+                Some(intent_lba),
+                &mut block_buf,
+            )
+            .await?;
+        disk.flush().await
+    });
+    result.unwrap();
+    let io = disk.into_inner().inner.into_inner();
+    assert_eq!(io.writes, expected_writes);
+    // The extra flush is the one above; `Disk` flushes when dropped.
+    assert_eq!(io.flushes, expected_flushes);
+    assert!(contents == expected);
+}
+
+#[test]
+fn test_async_disk_errors() {
+    let mut contents = load_test_disk();
+    contents.truncate(BS * 34);
+    let io = AsyncBlockIoAdapter::new(BlockIoAdapter::new(
+        contents.as_slice(),
+        BlockSize::BS_512,
+    ));
+    let mut disk = AsyncDisk::new(io).unwrap();
+
+    let (result, num_wakes) = block_on(async {
+        let mut small_buf = vec![0; BS - 1];
+        assert!(matches!(
+            disk.read_mbr(&mut small_buf).await,
+            Err(DiskError::BufferTooSmall)
+        ));
+
+        // Writes to a read-only slice fail.
+        let mut block_buf = vec![0; BS];
+        let header = disk.read_primary_gpt_header(&mut block_buf).await?;
+        assert_eq!(
+            disk.write_primary_gpt_header(&header, &mut block_buf)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "attempted to write to a read-only byte slice"
+        );
+
+        // Iteration stops after an error. The array starts at LBA 2,
+        // so with four entries per block the error occurs when reading
+        // entry 128.
+        let mut layout = header.get_partition_entry_array_layout().unwrap();
+        layout.num_entries = 200;
+        let mut iter =
+            disk.gpt_partition_entry_array_iter(layout, &mut block_buf)?;
+        let mut num_entries = 0;
+        while let Some(entry) = iter.next_entry().await {
+            if entry.is_err() {
+                break;
+            }
+            num_entries += 1;
+        }
+        assert_eq!(num_entries, 128);
+        assert!(iter.next_entry().await.is_none());
+        Ok::<_, DiskError<SliceBlockIoError>>(())
+    });
+    result.unwrap();
+
+    // The adapter never returns `Pending`.
+    assert_eq!(num_wakes, 0);
+    assert_eq!(disk.into_inner().inner().storage().len(), BS * 34);
+}
@@ -9,13 +9,123 @@
 mod common;
 
 use common::check_derives;
-use gpt_disk_types::{BlockSize, Lba, LbaLe, LbaRangeInclusive, U64Le};
+use gpt_disk_types::{
+    AlignmentPlanner, BlockSize, Lba, LbaLe, LbaRangeInclusive, U64Le,
+};
 
 #[test]
 fn test_lba() {
     check_derives::<Lba>();
 }
 
+#[test]
+fn test_lba_alignment() {
+    let bs512 = BlockSize::BS_512;
+    let bs4096 = BlockSize::BS_4096;
+    let mib = 1024 * 1024;
+
+    assert!(Lba(2048).is_aligned(bs512, mib));
+    assert!(!Lba(34).is_aligned(bs512, mib));
+    assert!(Lba(256).is_aligned(bs4096, mib));
+    assert!(Lba(8).is_aligned(bs512, 4096));
+    assert!(!Lba(63).is_aligned(bs512, 4096));
+
+    // Alignments smaller than the block size are always satisfied.
+    assert!(Lba(63).is_aligned(bs4096, 512));
+
+    // Alignment that is not a multiple of the block size.
+    assert!(Lba(3).is_aligned(bs512, 1536));
+    assert!(!Lba(1).is_aligned(bs512, 1536));
+    assert!(Lba(125).is_aligned(bs512, 1000));
+    assert!(!Lba(1536).is_aligned(bs512, 1000));
+
+    // Zero means no alignment.
+    assert!(Lba(63).is_aligned(bs512, 0));
+
+    assert_eq!(Lba(34).align_up(bs512, mib), Some(Lba(2048)));
+    assert_eq!(Lba(2048).align_up(bs512, mib), Some(Lba(2048)));
+    assert_eq!(Lba(u64::MAX).align_up(bs512, mib), None);
+    assert_eq!(Lba(4095).align_down(bs512, mib), Lba(2048));
+    assert_eq!(Lba(4096).align_down(bs512, mib), Lba(4096));
+}
+
+#[test]
+fn test_lba_range_align() {
+    let bs = BlockSize::BS_512;
+    let mib = 1024 * 1024;
+    let range = |start, end| LbaRangeInclusive::new(Lba(start), Lba(end));
+
+    assert_eq!(range(34, 8158).unwrap().align(bs, mib), range(2048, 6143));
+    assert_eq!(range(2048, 4095).unwrap().align(bs, mib), range(2048, 4095));
+    assert_eq!(range(34, 4000).unwrap().align(bs, mib), None);
+    assert_eq!(range(34, u64::MAX).unwrap().align(bs, mib), None);
+}
+
+#[test]
+fn test_alignment_planner_new() {
+    let bs = BlockSize::BS_512;
+    let range = |start, end| LbaRangeInclusive::new(Lba(start), Lba(end));
+    let planner =
+        AlignmentPlanner::new(bs, 1024 * 1024, range(34, 8158).unwrap());
+    let used = [range(2048, 3071).unwrap(), range(4096, 6143).unwrap()];
+
+    // Nothing used.
+    assert_eq!(planner.plan_new([], 1000), range(2048, 4095));
+    assert_eq!(planner.plan_new([], 2049), range(2048, 6143));
+
+    // The gap between the used ranges has no aligned start, so the
+    // partition is placed after the second used range. The rounded-up
+    // size doesn't fit, so the exact size is used.
+    assert_eq!(planner.plan_new(used, 1000), range(6144, 7143));
+    assert_eq!(planner.plan_new(used, 2015), range(6144, 8158));
+    assert_eq!(planner.plan_new(used, 2016), None);
+
+    // With a smaller alignment, the gap has aligned starts, and the
+    // size is rounded up to the alignment.
+    let small = AlignmentPlanner::new(bs, 64 * 1024, range(34, 8158).unwrap());
+    let used = [range(34, 3071).unwrap(), range(4096, 6143).unwrap()];
+    assert_eq!(small.plan_new(used, 100), range(3072, 3199));
+
+    // Used ranges that are not aligned.
+    let used = [range(34, 2100).unwrap()];
+    assert_eq!(planner.plan_new(used, 10), range(4096, 6143));
+
+    // No alignment.
+    let planner = AlignmentPlanner::new(bs, 0, range(34, 8158).unwrap());
+    assert_eq!(planner.plan_new(used, 10), range(2101, 2110));
+}
+
+#[test]
+fn test_alignment_planner_move() {
+    let bs = BlockSize::BS_512;
+    let range = |start, end| LbaRangeInclusive::new(Lba(start), Lba(end));
+    let planner =
+        AlignmentPlanner::new(bs, 1024 * 1024, range(34, 8158).unwrap());
+
+    // The partition's own range is ignored, so it can move to an
+    // aligned location that overlaps it.
+    let current = range(100, 2147).unwrap();
+    let other = range(6144, 8000).unwrap();
+    assert_eq!(
+        planner.plan_move([current, other], current),
+        range(2048, 4095)
+    );
+
+    // The size is not rounded up.
+    let current = range(7000, 7999).unwrap();
+    let used = [range(34, 2047).unwrap(), current];
+    assert_eq!(planner.plan_move(used, current), range(2048, 3047));
+
+    // Moving down to a location that overlaps the current range.
+    let used = [range(34, 6143).unwrap(), current];
+    assert_eq!(planner.plan_move(used, current), range(6144, 7143));
+
+    // No room anywhere else.
+    let current = range(34, 6143).unwrap();
+    let used = [current, range(6144, 8158).unwrap()];
+    assert_eq!(planner.plan_move(used, current), None);
+}
+
 #[test]
 fn test_lba_le() {
     check_derives::<LbaLe>();
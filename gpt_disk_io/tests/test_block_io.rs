@@ -96,6 +96,17 @@ where
     assert_eq!(buf[512], 3);
     assert_eq!(buf[1023], 4);
 
+    // Read two locations in one call.
+    let mut first = vec![0; 512];
+    let mut second = vec![0; 512];
+    bio.read_blocks_vectored(&mut [
+        (Lba(1), first.as_mut_slice()),
+        (Lba(0), second.as_mut_slice()),
+    ])
+    .expect("read_blocks_vectored failed");
+    assert_eq!(first[0], 3);
+    assert_eq!(second[0], 1);
+
     bio.take_storage()
 }
 
@@ -143,6 +154,16 @@ where
     expected[1024] = 11;
     expected[1535] = 12;
     assert_eq!(get_bytes(&bio), expected);
+
+    // Write two locations in one call.
+    bio.write_blocks_vectored(&[(Lba(2), &[13; 512]), (Lba(0), &[14; 512])])
+        .unwrap();
+    bio.flush().unwrap();
+
+    // Check write output.
+    expected[1024..].fill(13);
+    expected[..512].fill(14);
+    assert_eq!(get_bytes(&bio), expected);
 }
 
 fn check_read_and_write<S, G>(storage: S, get_bytes: G)
@@ -0,0 +1,175 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::create_primary_header;
+use gpt_disk_io::{BlockIoAdapter, Disk, DiskError, GptBuilder};
+use gpt_disk_types::{
+    guid, BlockSize, GptHeader, GptPartitionEntryArray, GptPartitionEntrySize,
+    LbaLe, MasterBootRecord, U32Le,
+};
+
+fn builder() -> GptBuilder {
+    GptBuilder::new(guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870"))
+}
+
+#[test]
+fn test_build_headers() {
+    let (primary, secondary) =
+        builder().build_headers(BlockSize::BS_512, 8192).unwrap();
+
+    // Everything except the checksums should match the sgdisk-created
+    // test disk.
+    let expected_primary = GptHeader {
+        header_crc32: primary.header_crc32,
+        partition_entry_array_crc32: primary.partition_entry_array_crc32,
+        ..create_primary_header()
+    };
+    assert_eq!(primary, expected_primary);
+    assert_eq!(primary.calculate_header_crc32(), primary.header_crc32);
+
+    assert_eq!(secondary.my_lba, LbaLe::from_u64(8191));
+    assert_eq!(secondary.alternate_lba, LbaLe::from_u64(1));
+    assert_eq!(secondary.partition_entry_lba, LbaLe::from_u64(8159));
+    assert_eq!(secondary.calculate_header_crc32(), secondary.header_crc32);
+
+    // The array checksum matches an all-zero array.
+    let layout = primary.get_partition_entry_array_layout().unwrap();
+    let bs = BlockSize::BS_512;
+    let mut bytes =
+        vec![0; layout.num_bytes_rounded_to_block_as_usize(bs).unwrap()];
+    let array = GptPartitionEntryArray::new(layout, bs, &mut bytes).unwrap();
+    assert_eq!(primary.partition_entry_array_crc32, array.calculate_crc32());
+    assert_eq!(
+        secondary.partition_entry_array_crc32,
+        array.calculate_crc32()
+    );
+}
+
+#[test]
+fn test_build_headers_custom_layout() {
+    let (primary, secondary) = builder()
+        .num_partition_entries(4)
+        .partition_entry_size(GptPartitionEntrySize::new(256).unwrap())
+        .build_headers(BlockSize::BS_4096, 100)
+        .unwrap();
+
+    assert_eq!(primary.number_of_partition_entries, U32Le::from_u32(4));
+    assert_eq!(primary.size_of_partition_entry, U32Le::from_u32(256));
+    // The array fits in one block, but 16,384 bytes are reserved.
+    assert_eq!(primary.first_usable_lba, LbaLe::from_u64(6));
+    assert_eq!(primary.last_usable_lba, LbaLe::from_u64(94));
+    assert_eq!(primary.alternate_lba, LbaLe::from_u64(99));
+    assert_eq!(secondary.partition_entry_lba, LbaLe::from_u64(98));
+}
+
+#[test]
+fn test_num_reserved_blocks() {
+    let bs512 = BlockSize::BS_512;
+    let bs4096 = BlockSize::BS_4096;
+    assert_eq!(builder().num_reserved_blocks(bs512), Some(32));
+    assert_eq!(builder().num_reserved_blocks(bs4096), Some(4));
+
+    // Small arrays still reserve the minimum.
+    let small = builder().num_partition_entries(4);
+    assert_eq!(small.num_reserved_blocks(bs512), Some(32));
+    let (primary, _) = small.build_headers(bs512, 8192).unwrap();
+    assert_eq!(primary.first_usable_lba, LbaLe::from_u64(34));
+    assert_eq!(primary.last_usable_lba, LbaLe::from_u64(8158));
+
+    // Large arrays reserve the whole array.
+    let large = builder().num_partition_entries(256);
+    assert_eq!(large.num_reserved_blocks(bs512), Some(64));
+}
+
+#[test]
+fn test_build_headers_too_small() {
+    // 1 MBR + 2 headers + 2*32 array blocks leaves no usable blocks.
+    assert!(builder().build_headers(BlockSize::BS_512, 67).is_none());
+    assert!(builder().build_headers(BlockSize::BS_512, 68).is_some());
+    assert!(builder().build_headers(BlockSize::BS_512, 0).is_none());
+}
+
+#[test]
+fn test_format_gpt() {
+    let bs = BlockSize::BS_512;
+    let mut storage = vec![0xff; 4 * 1024 * 1024];
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(storage.as_mut_slice(), bs)).unwrap();
+
+    let primary = disk.format_gpt(&builder(), &mut block_buf).unwrap();
+    assert_eq!(
+        disk.read_primary_gpt_header(&mut block_buf).unwrap(),
+        primary
+    );
+
+    let secondary = disk.read_secondary_gpt_header(&mut block_buf).unwrap();
+    let (_, expected_secondary) = builder().build_headers(bs, 8192).unwrap();
+    assert_eq!(secondary, expected_secondary);
+
+    // Both arrays are zeroed out.
+    for header in [primary, secondary] {
+        let layout = header.get_partition_entry_array_layout().unwrap();
+        let mut bytes =
+            vec![0; layout.num_bytes_rounded_to_block_as_usize(bs).unwrap()];
+        let array = disk
+            .read_gpt_partition_entry_array(layout, &mut bytes)
+            .unwrap();
+        assert!(array.storage().iter().all(|b| *b == 0));
+        assert_eq!(header.partition_entry_array_crc32, array.calculate_crc32());
+    }
+    disk.flush().unwrap();
+    drop(disk);
+
+    let mbr = MasterBootRecord::protective_mbr(8192);
+    assert_eq!(&storage[..512], bytemuck::bytes_of(&mbr));
+}
+
+#[test]
+fn test_format_gpt_small_array() {
+    let bs = BlockSize::BS_512;
+    let mut storage = vec![0xff; 4 * 1024 * 1024];
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(storage.as_mut_slice(), bs)).unwrap();
+
+    let builder = builder().num_partition_entries(4);
+    let primary = disk.format_gpt(&builder, &mut block_buf).unwrap();
+    assert_eq!(primary.first_usable_lba, LbaLe::from_u64(34));
+
+    // The layout passes verification, which requires 16,384 bytes to be
+    // reserved for each array.
+    let mut num_findings = 0;
+    disk.verify_gpt_with(&mut block_buf, &mut array_buf, |_| {
+        num_findings += 1;
+    })
+    .unwrap();
+    assert_eq!(num_findings, 0);
+    drop(disk);
+
+    // All reserved blocks are zeroed, not just the array blocks.
+    assert!(storage[512 * 2..512 * 34].iter().all(|b| *b == 0));
+    assert!(storage[512 * 8159..512 * 8191].iter().all(|b| *b == 0));
+}
+
+#[test]
+fn test_format_gpt_too_small() {
+    let bs = BlockSize::BS_512;
+    let mut storage = vec![0; 512 * 64];
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(storage.as_mut_slice(), bs)).unwrap();
+
+    assert!(matches!(
+        disk.format_gpt(&builder(), &mut block_buf),
+        Err(DiskError::DiskTooSmall)
+    ));
+}
@@ -0,0 +1,239 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::RecordingBlockIo;
+use gpt_disk_io::{BlockIo, CacheSlot, CacheWriteMode, CachedBlockIo};
+use gpt_disk_types::Lba;
+
+#[cfg(feature = "alloc")]
+use {common::load_test_disk, gpt_disk_io::Disk, std::cell::Cell};
+
+const BS: usize = 512;
+
+/// Create a disk where every byte of block `n` has the value `n`.
+fn create_disk() -> Vec<u8> {
+    (0..16u8).flat_map(|n| [n; BS]).collect()
+}
+
+fn read(io: &mut impl BlockIo, lba: u64, num_blocks: usize) -> Vec<u8> {
+    let mut buf = vec![0; num_blocks * BS];
+    io.read_blocks(Lba(lba), &mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn test_cached_block_io_read() {
+    let mut contents = create_disk();
+    let mut data = [0; BS * 2];
+    let mut slots = [CacheSlot::default(); 2];
+    let mut cache = CachedBlockIo::new(
+        RecordingBlockIo::new(&mut contents),
+        CacheWriteMode::WriteThrough,
+        data.as_mut_slice(),
+        slots.as_mut_slice(),
+    );
+    assert_eq!(cache.capacity(), 2);
+    assert_eq!(cache.mode(), CacheWriteMode::WriteThrough);
+
+    assert_eq!(read(&mut cache, 1, 1), [1; BS]);
+    assert_eq!(read(&mut cache, 1, 1), [1; BS]);
+    assert_eq!(cache.inner().reads, [(1, 1)]);
+
+    // Uncached blocks around a cached block are read in separate
+    // runs.
+    let expected: Vec<u8> = (0..4u8).flat_map(|n| [n; BS]).collect();
+    assert_eq!(read(&mut cache, 0, 4), expected);
+    assert_eq!(cache.inner().reads, [(1, 1), (0, 1), (2, 2)]);
+
+    // The last two blocks read are cached. Reading block 2 makes block
+    // 3 the least recently used, so reading block 4 evicts it.
+    read(&mut cache, 2, 1);
+    read(&mut cache, 4, 1);
+    read(&mut cache, 2, 1);
+    read(&mut cache, 3, 1);
+    assert_eq!(cache.inner().reads[3..], [(4, 1), (3, 1)]);
+}
+
+#[test]
+fn test_cached_block_io_write_through() {
+    let mut contents = create_disk();
+    let mut cache = CachedBlockIo::new(
+        RecordingBlockIo::new(&mut contents),
+        CacheWriteMode::WriteThrough,
+        [0; BS * 2],
+        [CacheSlot::default(); 2],
+    );
+
+    cache.write_blocks(Lba(5), &[0xaa; BS]).unwrap();
+    assert_eq!(cache.inner().writes, [(5, 1)]);
+    assert_eq!(cache.inner().inner.storage()[5 * BS], 0xaa);
+
+    // The written block is cached.
+    assert_eq!(read(&mut cache, 5, 1), [0xaa; BS]);
+    assert!(cache.inner().reads.is_empty());
+
+    cache.flush().unwrap();
+    assert_eq!(cache.inner().writes.len(), 1);
+    assert_eq!(cache.inner().flushes, 1);
+}
+
+#[test]
+fn test_cached_block_io_write_back() {
+    let mut contents = create_disk();
+    let mut cache = CachedBlockIo::new(
+        RecordingBlockIo::new(&mut contents),
+        CacheWriteMode::WriteBack,
+        [0; BS * 2],
+        [CacheSlot::default(); 2],
+    );
+
+    cache.write_blocks(Lba(5), &[0xaa; BS]).unwrap();
+    cache.write_blocks(Lba(5), &[0xbb; BS]).unwrap();
+    cache.write_blocks(Lba(6), &[0xcc; BS]).unwrap();
+    assert!(cache.inner().writes.is_empty());
+    assert_eq!(cache.inner().inner.storage()[5 * BS], 5);
+    assert_eq!(read(&mut cache, 5, 2)[..BS], [0xbb; BS]);
+    assert!(cache.inner().reads.is_empty());
+
+    // Evicting a dirty block writes it. Block 5 was used least
+    // recently.
+    read(&mut cache, 0, 1);
+    assert_eq!(cache.inner().writes, [(5, 1)]);
+    assert_eq!(cache.inner().inner.storage()[5 * BS], 0xbb);
+
+    cache.flush().unwrap();
+    assert_eq!(cache.inner().writes, [(5, 1), (6, 1)]);
+    assert_eq!(cache.inner().inner.storage()[6 * BS], 0xcc);
+    assert_eq!(cache.inner().flushes, 1);
+
+    // Nothing is dirty, so a second flush does not write.
+    cache.flush().unwrap();
+    assert_eq!(cache.inner().writes.len(), 2);
+
+    // Writes larger than the cache go directly to the device, and
+    // update cached blocks.
+    cache.write_blocks(Lba(5), &[0xdd; BS * 3]).unwrap();
+    assert_eq!(cache.inner().writes[2..], [(5, 3)]);
+    assert_eq!(read(&mut cache, 6, 1), [0xdd; BS]);
+    assert_eq!(cache.inner().reads.len(), 1);
+
+    // Dirty blocks are written when the cache is dropped.
+    cache.write_blocks(Lba(9), &[0xee; BS]).unwrap();
+    drop(cache);
+    assert_eq!(contents[9 * BS], 0xee);
+}
+
+#[test]
+fn test_cached_block_io_vectored() {
+    let mut contents = create_disk();
+    let mut cache = CachedBlockIo::new(
+        RecordingBlockIo::new(&mut contents),
+        CacheWriteMode::WriteThrough,
+        [0; BS * 4],
+        [CacheSlot::default(); 4],
+    );
+
+    // Uncached blocks are read together.
+    let mut buf1 = vec![0; BS];
+    let mut buf2 = vec![0; BS * 2];
+    cache
+        .read_blocks_vectored(&mut [(Lba(1), &mut buf1), (Lba(5), &mut buf2)])
+        .unwrap();
+    assert_eq!(cache.inner().vectored_reads, [[(1, 1), (5, 2)]]);
+    assert_eq!(buf1, [1; BS]);
+    assert_eq!(buf2[BS..], [6; BS]);
+
+    // Cached blocks are not read again.
+    cache
+        .read_blocks_vectored(&mut [(Lba(1), &mut buf1), (Lba(7), &mut buf2)])
+        .unwrap();
+    assert_eq!(cache.inner().vectored_reads.len(), 1);
+    assert_eq!(cache.inner().reads[2..], [(7, 2)]);
+    assert_eq!(buf2[..BS], [7; BS]);
+
+    // In write-through mode, writes are passed on together and update
+    // the cache.
+    cache
+        .write_blocks_vectored(&[(Lba(1), &[0xaa; BS]), (Lba(9), &[0xbb; BS])])
+        .unwrap();
+    assert_eq!(cache.inner().vectored_writes, [[(1, 1), (9, 1)]]);
+    assert_eq!(read(&mut cache, 1, 1), [0xaa; BS]);
+    assert_eq!(cache.inner().reads.len(), 3);
+}
+
+#[test]
+fn test_cached_block_io_vectored_write_back() {
+    let mut contents = create_disk();
+    let mut cache = CachedBlockIo::new(
+        RecordingBlockIo::new(&mut contents),
+        CacheWriteMode::WriteBack,
+        [0; BS * 4],
+        [CacheSlot::default(); 4],
+    );
+
+    // Dirty blocks are served from the cache.
+    cache
+        .write_blocks_vectored(&[(Lba(1), &[0xaa; BS]), (Lba(9), &[0xbb; BS])])
+        .unwrap();
+    assert!(cache.inner().writes.is_empty());
+    let mut buf1 = vec![0; BS];
+    let mut buf2 = vec![0; BS * 2];
+    cache
+        .read_blocks_vectored(&mut [(Lba(1), &mut buf1), (Lba(8), &mut buf2)])
+        .unwrap();
+    assert_eq!(buf1, [0xaa; BS]);
+    assert_eq!(buf2[..BS], [8; BS]);
+    assert_eq!(buf2[BS..], [0xbb; BS]);
+    assert!(cache.inner().vectored_reads.is_empty());
+    assert_eq!(cache.inner().reads, [(8, 1)]);
+}
+
+#[test]
+fn test_cached_block_io_zero_capacity() {
+    let mut contents = create_disk();
+    let mut cache = CachedBlockIo::new(
+        RecordingBlockIo::new(&mut contents),
+        CacheWriteMode::WriteBack,
+        [0; BS - 1],
+        [CacheSlot::default(); 2],
+    );
+    assert_eq!(cache.capacity(), 0);
+
+    read(&mut cache, 1, 1);
+    read(&mut cache, 1, 1);
+    cache.write_blocks(Lba(1), &[0xaa; BS]).unwrap();
+    assert_eq!(cache.inner().reads, [(1, 1), (1, 1)]);
+    assert_eq!(cache.inner().writes, [(1, 1)]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_cached_block_io_disk() {
+    let mut contents = load_test_disk();
+    let read_count = Cell::new(0);
+    let mut io = RecordingBlockIo::new(&mut contents);
+    io.read_count = Some(&read_count);
+    let cache = CachedBlockIo::with_capacity(io, CacheWriteMode::WriteBack, 64);
+    assert_eq!(cache.capacity(), 64);
+    let mut disk = Disk::new(cache).unwrap();
+    let mut block_buf = vec![0; BS];
+    let mut array_buf = vec![0; BS * 32];
+
+    let table = disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    let header = table.primary_header();
+    let num_reads = read_count.get();
+
+    // Reading the table again is served from the cache.
+    let table = disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    assert_eq!(table.primary_header(), header);
+    assert_eq!(read_count.get(), num_reads);
+    let report = disk.verify_gpt().unwrap();
+    assert!(report.findings().is_empty());
+}
@@ -0,0 +1,404 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "alloc")]
+
+mod common;
+
+use common::{
+    check_crash_safety, check_gpt_valid, create_mbr_disk,
+    create_second_partition_entry, has_intact_gpt, load_test_disk,
+    load_test_disk_with_reserved_blocks,
+};
+use gpt_disk_io::{
+    BlockIo, BlockIoAdapter, BlockIoEvent, Disk, FaultInjectingBlockIo,
+    FaultInjectingBlockIoError, GptBuilder, GptCommitRecovery,
+    GptPartitionMoveProgress,
+};
+use gpt_disk_types::{guid, BlockSize, Guid, Lba, LbaRangeInclusive, MbrKind};
+
+const BS: usize = 512;
+
+#[test]
+fn test_fault_injecting_block_io() {
+    let original = vec![0; BS * 4];
+    let mut io = FaultInjectingBlockIo::new(BlockIoAdapter::new(
+        original.clone(),
+        BlockSize::BS_512,
+    ));
+    io.write_blocks(Lba(1), &[1; BS * 3]).unwrap();
+    io.flush().unwrap();
+    io.write_blocks(Lba(0), &[2; BS]).unwrap();
+    assert_eq!(
+        io.events(),
+        [
+            BlockIoEvent::Write {
+                start_lba: Lba(1),
+                data: vec![1; BS * 3]
+            },
+            BlockIoEvent::Flush,
+            BlockIoEvent::Write {
+                start_lba: Lba(0),
+                data: vec![2; BS]
+            },
+        ]
+    );
+
+    // The original state, three states for the torn three-block write,
+    // and one state for the single-block write.
+    let mut states = Vec::new();
+    io.replay_crashes(&original, |state| {
+        states.push(state.chunks(BS).map(|block| block[0]).collect::<Vec<_>>());
+        // Changes made by the callback do not affect later states.
+        state.fill(0xff);
+    });
+    assert_eq!(
+        states,
+        [
+            [0, 0, 0, 0],
+            [0, 1, 0, 0],
+            [0, 1, 1, 0],
+            [0, 1, 1, 1],
+            [2, 1, 1, 1],
+        ]
+    );
+    assert_eq!(io.inner().storage()[..BS], [2; BS]);
+
+    // Inject a fault after one more event.
+    io.set_fail_after(Some(4));
+    io.flush().unwrap();
+    let err = io.write_blocks(Lba(0), &[3; BS]).unwrap_err();
+    assert_eq!(err, FaultInjectingBlockIoError::InjectedFault);
+    assert_eq!(err.to_string(), "injected fault");
+    assert_eq!(io.flush(), Err(FaultInjectingBlockIoError::InjectedFault));
+    assert_eq!(io.events().len(), 4);
+
+    // Reads still work.
+    let mut buf = vec![0; BS];
+    io.read_blocks(Lba(0), &mut buf).unwrap();
+    assert_eq!(buf, [2; BS]);
+
+    io.clear_events();
+    io.set_fail_after(None);
+    io.write_blocks(Lba(0), &[3; BS]).unwrap();
+    assert_eq!(io.events().len(), 1);
+    assert_eq!(io.into_inner().storage()[0], 3);
+
+    // Writes between flushes can land in any order.
+    let mut io = FaultInjectingBlockIo::new(BlockIoAdapter::new(
+        original.clone(),
+        BlockSize::BS_512,
+    ));
+    io.write_blocks(Lba(0), &[1; BS]).unwrap();
+    io.write_blocks(Lba(1), &[2; BS * 2]).unwrap();
+    let mut states = Vec::new();
+    io.replay_crashes(&original, |state| {
+        states.push(state.chunks(BS).map(|block| block[0]).collect::<Vec<_>>());
+    });
+    assert_eq!(
+        states,
+        [
+            [0, 0, 0, 0],
+            [0, 2, 0, 0],
+            [1, 0, 0, 0],
+            [1, 2, 0, 0],
+            [0, 2, 2, 0],
+            [1, 2, 2, 0],
+        ]
+    );
+}
+
+/// Add a partition to the GPT on `disk`, and commit it with either
+/// `GptTable::commit` or `GptTable::commit_transactional`.
+fn add_partition<Io: BlockIo>(disk: &mut Disk<Io>, transactional: bool) {
+    let mut block_buf = vec![0; BS];
+    let mut array_buf = vec![0; BS * 32];
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    table
+        .add_partition(create_second_partition_entry())
+        .unwrap();
+    if transactional {
+        table
+            .commit_transactional(disk, None, &mut block_buf)
+            .unwrap();
+    } else {
+        table.commit(disk, &mut block_buf).unwrap();
+    }
+}
+
+#[test]
+fn test_crash_table_commit() {
+    // `commit` does not flush between the two copies of the GPT, so
+    // the writes can land in an order that leaves neither copy intact.
+    let original = load_test_disk();
+    let mut io = FaultInjectingBlockIo::new(BlockIoAdapter::new(
+        original.clone(),
+        BlockSize::BS_512,
+    ));
+    let mut disk = Disk::new(&mut io).unwrap();
+    add_partition(&mut disk, false);
+    drop(disk);
+    let mut num_states = 0;
+    let mut num_broken = 0;
+    io.replay_crashes(&original, |state| {
+        num_states += 1;
+        if !has_intact_gpt(state) {
+            num_broken += 1;
+        }
+    });
+    assert!(num_broken > 0);
+
+    // `commit_transactional` flushes the secondary copy before writing
+    // the primary copy, so one copy is always intact. It also has
+    // fewer states, since fewer writes can be reordered.
+    let num_transactional_states =
+        check_crash_safety(&original, |disk| add_partition(disk, true), |_| {});
+    assert!(num_transactional_states < num_states);
+}
+
+#[test]
+fn test_crash_commit_transactional() {
+    let original = load_test_disk_with_reserved_blocks();
+    let intent_lba = Lba(20);
+    let mut recoveries = Vec::new();
+    check_crash_safety(
+        &original,
+        |disk| {
+            let mut block_buf = vec![0; BS];
+            let mut array_buf = vec![0; BS * 32];
+            let mut table =
+                disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+            table
+                .add_partition(create_second_partition_entry())
+                .unwrap();
+            table
+                .commit_transactional(disk, Some(intent_lba), &mut block_buf)
+                .unwrap();
+        },
+        |state| {
+            // After recovery, both copies of the GPT match.
+            let mut block_buf = vec![0; BS];
+            let mut array_buf = vec![0; BS * 32];
+            let mut disk =
+                Disk::new(BlockIoAdapter::new(&mut *state, BlockSize::BS_512))
+                    .unwrap();
+            recoveries.push(
+                disk.recover_gpt_commit(
+                    intent_lba,
+                    &mut block_buf,
+                    &mut array_buf,
+                )
+                .unwrap(),
+            );
+            drop(disk);
+            check_gpt_valid(state, BlockSize::BS_512);
+        },
+    );
+
+    // Recovery is not needed before the first intent record is written
+    // or after it is cleared.
+    assert_eq!(recoveries.first(), Some(&GptCommitRecovery::NotNeeded));
+    assert_eq!(recoveries.last(), Some(&GptCommitRecovery::NotNeeded));
+    assert!(recoveries.contains(&GptCommitRecovery::RolledBack));
+    assert!(recoveries.contains(&GptCommitRecovery::Completed));
+}
+
+#[test]
+fn test_crash_repair_gpt() {
+    // Corrupt the primary header.
+    let mut original = load_test_disk();
+    original[BS] = 0;
+    assert!(has_intact_gpt(&original));
+
+    check_crash_safety(
+        &original,
+        |disk| {
+            let mut block_buf = vec![0; BS];
+            let mut array_buf = vec![0; BS * 32];
+            let summary =
+                disk.repair_gpt(&mut block_buf, &mut array_buf).unwrap();
+            assert!(summary.primary_header_repaired);
+        },
+        |_| {},
+    );
+}
+
+#[test]
+fn test_crash_relocate_backup_to_end() {
+    let mut original = load_test_disk();
+    original.resize(8 * 1024 * 1024, 0);
+
+    check_crash_safety(
+        &original,
+        |disk| {
+            let mut block_buf = vec![0; BS];
+            let mut array_buf = vec![0; BS * 32];
+            disk.relocate_backup_to_end(&mut block_buf, &mut array_buf)
+                .unwrap();
+        },
+        |_| {},
+    );
+}
+
+#[test]
+fn test_crash_shrink_gpt() {
+    let original = load_test_disk();
+
+    check_crash_safety(
+        &original,
+        |disk| {
+            let mut block_buf = vec![0; BS];
+            let mut array_buf = vec![0; BS * 32];
+            disk.shrink_gpt(None, &mut block_buf, &mut array_buf)
+                .unwrap();
+        },
+        |_| {},
+    );
+}
+
+#[test]
+fn test_crash_move_partition() {
+    // Fill the partition with data that differs in each block.
+    let mut original = load_test_disk();
+    for (i, block) in original[2048 * BS..4097 * BS].chunks_mut(BS).enumerate()
+    {
+        block.fill(u8::try_from(i % 251).unwrap());
+    }
+    let source = LbaRangeInclusive::new(Lba(2048), Lba(4096)).unwrap();
+
+    // The destination does not overlap the source, so the move can
+    // always be resumed from the start.
+    let progress = GptPartitionMoveProgress {
+        index: 0,
+        source,
+        destination_start: Lba(5000),
+        blocks_copied: 0,
+    };
+    check_crash_safety(
+        &original,
+        |disk| {
+            let mut chunk_buf = vec![0; BS * 512];
+            let mut array_buf = vec![0; BS * 32];
+            disk.move_partition(
+                0,
+                Lba(5000),
+                &mut chunk_buf,
+                &mut array_buf,
+                |_| {},
+            )
+            .unwrap();
+        },
+        |state| {
+            let mut chunk_buf = vec![0; BS * 512];
+            let mut array_buf = vec![0; BS * 32];
+            let mut disk =
+                Disk::new(BlockIoAdapter::new(&mut *state, BlockSize::BS_512))
+                    .unwrap();
+            disk.resume_partition_move(
+                &progress,
+                &mut chunk_buf,
+                &mut array_buf,
+                |_| {},
+            )
+            .unwrap();
+            drop(disk);
+            check_gpt_valid(state, BlockSize::BS_512);
+            assert_eq!(
+                state[5000 * BS..7049 * BS],
+                original[2048 * BS..4097 * BS]
+            );
+        },
+    );
+}
+
+/// Convert the MBR disk on `disk` to GPT.
+fn convert_mbr_to_gpt<Io: BlockIo>(disk: &mut Disk<Io>) {
+    let mut block_buf = vec![0; BS];
+    let mut array_buf = vec![0; BS * 32];
+    disk.convert_mbr_to_gpt(
+        &GptBuilder::new(guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870")),
+        || Guid::from_bytes([1; 16]),
+        &mut block_buf,
+        &mut array_buf,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_crash_convert_mbr_to_gpt() {
+    let original = create_mbr_disk();
+    let mut expected = original.clone();
+    convert_mbr_to_gpt(
+        &mut Disk::new(BlockIoAdapter::new(
+            expected.as_mut_slice(),
+            BlockSize::BS_512,
+        ))
+        .unwrap(),
+    );
+
+    let mut io = FaultInjectingBlockIo::new(BlockIoAdapter::new(
+        original.clone(),
+        BlockSize::BS_512,
+    ));
+    convert_mbr_to_gpt(&mut Disk::new(&mut io).unwrap());
+
+    // Until the protective MBR is written, the old MBR is still valid
+    // and the conversion can be run again. After that, the GPT is
+    // complete.
+    io.replay_crashes(&original, |state| {
+        let mut disk =
+            Disk::new(BlockIoAdapter::new(&mut *state, BlockSize::BS_512))
+                .unwrap();
+        let mbr = disk.read_mbr(&mut [0; BS]).unwrap();
+        if mbr.kind() == Some(MbrKind::Legacy) {
+            convert_mbr_to_gpt(&mut disk);
+        }
+        drop(disk);
+        assert!(state == expected.as_slice());
+    });
+}
+
+#[test]
+fn test_crash_grow_partition_to_fill() {
+    let original = load_test_disk();
+    let mut ends = Vec::new();
+    check_crash_safety(
+        &original,
+        |disk| {
+            let mut block_buf = vec![0; BS];
+            let mut array_buf = vec![0; BS * 32];
+            let mut table =
+                disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+            table.grow_partition_to_fill(0, None).unwrap();
+            table
+                .commit_transactional(disk, None, &mut block_buf)
+                .unwrap();
+        },
+        |state| {
+            // After repair, the partition has either its old or its new
+            // size.
+            let mut block_buf = vec![0; BS];
+            let mut array_buf = vec![0; BS * 32];
+            let mut disk =
+                Disk::new(BlockIoAdapter::new(&mut *state, BlockSize::BS_512))
+                    .unwrap();
+            disk.repair_gpt(&mut block_buf, &mut array_buf).unwrap();
+            let table =
+                disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+            ends.push(
+                table.get_partition_entry(0).unwrap().ending_lba.to_u64(),
+            );
+            drop(disk);
+            check_gpt_valid(state, BlockSize::BS_512);
+        },
+    );
+    assert_eq!(ends.first(), Some(&4096));
+    assert_eq!(ends.last(), Some(&8158));
+    assert!(ends.iter().all(|end| [4096, 8158].contains(end)));
+}
@@ -0,0 +1,191 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{mbr_record, range, write_mbr_block};
+use gpt_disk_io::{
+    BlockIoAdapter, Disk, DiskError, MbrLogicalPartition, SliceBlockIoError,
+};
+use gpt_disk_types::{BlockSize, Lba, LbaRangeInclusive, MbrPartitionRecord};
+
+const EXTENDED_START: u64 = 1000;
+const EXTENDED_END: u64 = 4999;
+
+type Item = Result<MbrLogicalPartition, DiskError<SliceBlockIoError>>;
+
+/// Write an EBR at `EXTENDED_START + offset` containing `record` and
+/// `link`.
+fn write_ebr(
+    contents: &mut [u8],
+    offset: u64,
+    record: MbrPartitionRecord,
+    link: MbrPartitionRecord,
+) {
+    let lba = usize::try_from(EXTENDED_START + offset).unwrap();
+    write_mbr_block(
+        contents,
+        lba,
+        [
+            record,
+            link,
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+        ],
+    );
+}
+
+fn link(offset: u32) -> MbrPartitionRecord {
+    mbr_record(0x05, offset, 10)
+}
+
+fn collect(contents: &mut [u8]) -> Vec<Item> {
+    let mut block_buf = vec![0u8; 512];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents, BlockSize::BS_512)).unwrap();
+    let extended =
+        LbaRangeInclusive::new(Lba(EXTENDED_START), Lba(EXTENDED_END)).unwrap();
+    disk.mbr_logical_partition_iter(extended, &mut block_buf)
+        .unwrap()
+        .collect()
+}
+
+#[test]
+fn test_ebr_chain() {
+    let mut contents = vec![0; 512 * 5000];
+    write_ebr(&mut contents, 0, mbr_record(0x83, 63, 100), link(500));
+    // An EBR without a partition is skipped.
+    write_ebr(
+        &mut contents,
+        500,
+        MbrPartitionRecord::default(),
+        link(1000),
+    );
+    write_ebr(
+        &mut contents,
+        1000,
+        mbr_record(0x07, 1, 2999),
+        MbrPartitionRecord::default(),
+    );
+
+    let partitions: Vec<_> = collect(&mut contents)
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        partitions,
+        [
+            MbrLogicalPartition {
+                ebr_lba: Lba(1000),
+                record: mbr_record(0x83, 63, 100),
+                range: range(1063, 1162),
+            },
+            MbrLogicalPartition {
+                ebr_lba: Lba(2000),
+                record: mbr_record(0x07, 1, 2999),
+                range: range(2001, 4999),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_ebr_chain_empty() {
+    let mut contents = vec![0; 512 * 5000];
+    write_ebr(
+        &mut contents,
+        0,
+        MbrPartitionRecord::default(),
+        MbrPartitionRecord::default(),
+    );
+    assert!(collect(&mut contents).is_empty());
+}
+
+#[test]
+fn test_ebr_chain_loop() {
+    // Link back to the first EBR.
+    let mut contents = vec![0; 512 * 5000];
+    write_ebr(&mut contents, 0, mbr_record(0x83, 1, 10), link(0));
+    let items = collect(&mut contents);
+    assert_eq!(items.len(), 2);
+    assert!(items[0].is_ok());
+    assert!(matches!(
+        items[1],
+        Err(DiskError::EbrLoop { lba: Lba(1000) })
+    ));
+
+    // Loop that does not include the first EBR: 0 -> 100 -> 200 ->
+    // 300 -> 100.
+    let mut contents = vec![0; 512 * 5000];
+    write_ebr(&mut contents, 0, mbr_record(0x83, 1, 10), link(100));
+    write_ebr(&mut contents, 100, mbr_record(0x83, 1, 10), link(200));
+    write_ebr(&mut contents, 200, mbr_record(0x83, 1, 10), link(300));
+    write_ebr(&mut contents, 300, mbr_record(0x83, 1, 10), link(100));
+    let items = collect(&mut contents);
+    let (last, partitions) = items.split_last().unwrap();
+    assert!(matches!(last, Err(DiskError::EbrLoop { .. })));
+    assert!(partitions.iter().all(Result::is_ok));
+    // The loop is detected within a few passes.
+    assert!(partitions.len() < 16);
+}
+
+#[test]
+fn test_ebr_chain_invalid() {
+    // Link past the end of the extended partition.
+    let mut contents = vec![0; 512 * 6000];
+    write_ebr(&mut contents, 0, mbr_record(0x83, 1, 10), link(4000));
+    let items = collect(&mut contents);
+    assert_eq!(items.len(), 2);
+    assert!(items[0].is_ok());
+    assert!(matches!(
+        items[1],
+        Err(DiskError::EbrOutOfRange { lba: Lba(1000) })
+    ));
+
+    // Logical partition past the end of the extended partition.
+    let mut contents = vec![0; 512 * 5000];
+    write_ebr(
+        &mut contents,
+        0,
+        mbr_record(0x83, 1, 4000),
+        MbrPartitionRecord::default(),
+    );
+    assert!(matches!(
+        collect(&mut contents)[..],
+        [Err(DiskError::EbrOutOfRange { lba: Lba(1000) })]
+    ));
+
+    // Link that is not an extended partition type.
+    let mut contents = vec![0; 512 * 5000];
+    write_ebr(
+        &mut contents,
+        0,
+        mbr_record(0x83, 1, 10),
+        mbr_record(0x83, 100, 10),
+    );
+    let items = collect(&mut contents);
+    assert_eq!(items.len(), 2);
+    assert!(matches!(items[1], Err(DiskError::InvalidMbr)));
+
+    // Second EBR has an invalid signature. Iteration stops after the
+    // error.
+    let mut contents = vec![0; 512 * 5000];
+    write_ebr(&mut contents, 0, mbr_record(0x83, 1, 10), link(100));
+    write_ebr(&mut contents, 100, mbr_record(0x83, 1, 10), link(200));
+    write_ebr(
+        &mut contents,
+        200,
+        mbr_record(0x83, 1, 10),
+        MbrPartitionRecord::default(),
+    );
+    contents[512 * 1100 + 510] = 0;
+    let items = collect(&mut contents);
+    assert_eq!(items.len(), 2);
+    assert!(items[0].is_ok());
+    assert!(matches!(items[1], Err(DiskError::InvalidMbr)));
+}
@@ -0,0 +1,171 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{create_partition_entry, range};
+use gpt_disk_types::{
+    BlockSize, GptAllocPolicy, GptPartitionAllocator, GptPartitionEntry,
+    GptPartitionEntryArray, GptPartitionEntryArrayLayout, Lba, LbaLe,
+};
+
+/// Create an array with a used entry for each of `ranges`.
+fn create_array<'a>(
+    storage: &'a mut [u8],
+    ranges: &[(u64, u64)],
+) -> GptPartitionEntryArray<'a> {
+    storage.fill(0);
+    let layout = GptPartitionEntryArrayLayout {
+        start_lba: Lba(2),
+        entry_size: Default::default(),
+        num_entries: 128,
+    };
+    let mut array =
+        GptPartitionEntryArray::new(layout, BlockSize::BS_512, storage)
+            .unwrap();
+    for (i, (start, end)) in ranges.iter().enumerate() {
+        *array
+            .get_partition_entry_mut(u32::try_from(i).unwrap())
+            .unwrap() = GptPartitionEntry {
+            starting_lba: LbaLe::from_u64(*start),
+            ending_lba: LbaLe::from_u64(*end),
+            ..create_partition_entry()
+        };
+    }
+    array
+}
+
+#[test]
+fn test_free_ranges() {
+    let mut storage = vec![0; 512 * 32];
+
+    // Empty array.
+    let array = create_array(&mut storage, &[]);
+    assert_eq!(
+        array.free_ranges(Lba(34), Lba(8158)).collect::<Vec<_>>(),
+        [range(34, 8158)]
+    );
+
+    // Unsorted and overlapping entries, and an entry outside the usable
+    // range.
+    let array = create_array(
+        &mut storage,
+        &[(5000, 5999), (100, 199), (150, 299), (34, 49), (8100, 9000)],
+    );
+    assert_eq!(
+        array.free_ranges(Lba(34), Lba(8158)).collect::<Vec<_>>(),
+        [range(50, 99), range(300, 4999), range(6000, 8099)]
+    );
+
+    // Full.
+    let array = create_array(&mut storage, &[(34, 8158)]);
+    assert_eq!(array.free_ranges(Lba(34), Lba(8158)).count(), 0);
+
+    // Unused entries and entries with an invalid range are ignored.
+    let mut array = create_array(&mut storage, &[(100, 199), (300, 200)]);
+    array
+        .get_partition_entry_mut(0)
+        .unwrap()
+        .partition_type_guid = Default::default();
+    assert_eq!(
+        array.free_ranges(Lba(34), Lba(8158)).collect::<Vec<_>>(),
+        [range(34, 8158)]
+    );
+
+    // Ranges that extend to the maximum LBA.
+    let array = create_array(&mut storage, &[(100, u64::MAX)]);
+    assert_eq!(
+        array
+            .free_ranges(Lba(34), Lba(u64::MAX))
+            .collect::<Vec<_>>(),
+        [range(34, 99)]
+    );
+    let array = create_array(&mut storage, &[(100, 199)]);
+    assert_eq!(
+        array
+            .free_ranges(Lba(34), Lba(u64::MAX))
+            .collect::<Vec<_>>(),
+        [range(34, 99), range(200, u64::MAX)]
+    );
+}
+
+#[test]
+fn test_allocator() {
+    let bs = BlockSize::BS_512;
+    let mut storage = vec![0; 512 * 32];
+    // Free ranges: 34..=2047, 3072..=4095, and 6144..=8158.
+    let array = create_array(&mut storage, &[(2048, 3071), (4096, 6143)]);
+    let (first, last) = (Lba(34), Lba(8158));
+
+    let allocator = GptPartitionAllocator::new(bs);
+
+    // The first range has no 1MiB-aligned start.
+    assert_eq!(
+        allocator.allocate(&array, first, last, 1000),
+        Some(range(6144, 7143))
+    );
+    assert_eq!(allocator.allocate(&array, first, last, 3000), None);
+    assert_eq!(allocator.allocate(&array, first, last, 0), None);
+
+    let allocator = allocator.alignment(0);
+    assert_eq!(
+        allocator.allocate(&array, first, last, 1000),
+        Some(range(34, 1033))
+    );
+
+    // Best fit picks the smallest range that fits.
+    let allocator = allocator.policy(GptAllocPolicy::BestFit);
+    assert_eq!(
+        allocator.allocate(&array, first, last, 1000),
+        Some(range(3072, 4071))
+    );
+    assert_eq!(
+        allocator.allocate(&array, first, last, 1500),
+        Some(range(34, 1533))
+    );
+    assert_eq!(
+        allocator.allocate(&array, first, last, 2015),
+        Some(range(6144, 8158))
+    );
+
+    // Last fit places the partition at the end of the last range.
+    let allocator = allocator.policy(GptAllocPolicy::LastFit);
+    assert_eq!(
+        allocator.allocate(&array, first, last, 1000),
+        Some(range(7159, 8158))
+    );
+    let allocator = allocator.alignment(4096);
+    assert_eq!(
+        allocator.allocate(&array, first, last, 1000),
+        Some(range(7152, 8151))
+    );
+}
+
+#[test]
+fn test_allocator_alignment_not_multiple_of_block_size() {
+    let mut storage = vec![0; 512 * 32];
+    let array = create_array(&mut storage, &[(2048, 3071), (4096, 6143)]);
+    let (first, last) = (Lba(34), Lba(8158));
+
+    // Placement agrees with `Lba::is_aligned` for alignments that are
+    // not a multiple of the block size.
+    for (bs, alignment, first_fit, last_fit) in [
+        (BlockSize::BS_512, 1000, range(125, 1124), range(7125, 8124)),
+        (BlockSize::BS_4096, 6144, range(36, 1035), range(7158, 8157)),
+    ] {
+        let allocator = GptPartitionAllocator::new(bs).alignment(alignment);
+        let placed = allocator.allocate(&array, first, last, 1000).unwrap();
+        assert_eq!(placed, first_fit);
+        assert!(placed.start().is_aligned(bs, alignment));
+
+        let allocator = allocator.policy(GptAllocPolicy::LastFit);
+        let placed = allocator.allocate(&array, first, last, 1000).unwrap();
+        assert_eq!(placed, last_fit);
+        assert!(placed.start().is_aligned(bs, alignment));
+    }
+}
@@ -10,7 +10,8 @@ mod common;
 
 use common::check_derives;
 use gpt_disk_types::{
-    Chs, DiskGeometry, Lba, MasterBootRecord, MbrPartitionRecord, U32Le,
+    Chs, DiskGeometry, Lba, LbaRangeInclusive, MasterBootRecord, MbrKind,
+    MbrPartitionRecord, U32Le,
 };
 
 #[test]
@@ -98,3 +99,74 @@ signature: 0x3412
         .to_string()
         .starts_with("MasterBootRecord { boot_strap_code: <non-zero>,"));
 }
+
+#[test]
+fn test_mbr_partition_record() {
+    let mut record = MbrPartitionRecord {
+        os_indicator: 0x83,
+        starting_lba: U32Le::from_u32(63),
+        size_in_lba: U32Le::from_u32(100),
+        ..Default::default()
+    };
+    assert!(record.is_used());
+    assert!(!record.is_extended());
+    assert_eq!(
+        record.lba_range(Lba(0)),
+        LbaRangeInclusive::new(Lba(63), Lba(162))
+    );
+    assert_eq!(
+        record.lba_range(Lba(1000)),
+        LbaRangeInclusive::new(Lba(1063), Lba(1162))
+    );
+    assert_eq!(record.lba_range(Lba(u64::MAX)), None);
+
+    for os_indicator in [0x05, 0x0f, 0x85] {
+        record.os_indicator = os_indicator;
+        assert!(record.is_extended());
+    }
+
+    record.os_indicator = 0;
+    assert!(!record.is_used());
+
+    record.os_indicator = 0x83;
+    record.size_in_lba = U32Le::from_u32(0);
+    assert!(!record.is_used());
+    assert_eq!(record.lba_range(Lba(0)), None);
+}
+
+#[test]
+fn test_mbr_signature() {
+    let mut mbr = MasterBootRecord::default();
+    assert!(!mbr.is_signature_valid());
+    mbr.signature = [0x55, 0xaa];
+    assert!(mbr.is_signature_valid());
+    assert!(MasterBootRecord::protective_mbr(8192).is_signature_valid());
+}
+
+#[test]
+fn test_mbr_kind() {
+    let mut mbr = MasterBootRecord::protective_mbr(8192);
+    assert_eq!(mbr.kind(), Some(MbrKind::Protective));
+
+    mbr.partitions[1] = MbrPartitionRecord {
+        os_indicator: 0x0c,
+        starting_lba: U32Le::from_u32(2048),
+        size_in_lba: U32Le::from_u32(2048),
+        ..Default::default()
+    };
+    assert_eq!(mbr.kind(), Some(MbrKind::Hybrid));
+
+    mbr.partitions[0] = MbrPartitionRecord::default();
+    assert_eq!(mbr.kind(), Some(MbrKind::Legacy));
+
+    // Unused records are ignored.
+    mbr.partitions[1].size_in_lba = U32Le::from_u32(0);
+    assert_eq!(mbr.kind(), Some(MbrKind::Legacy));
+
+    mbr.signature = [0; 2];
+    assert_eq!(mbr.kind(), None);
+
+    assert_eq!(MbrKind::Protective.to_string(), "protective");
+    assert_eq!(MbrKind::Hybrid.to_string(), "hybrid");
+    assert_eq!(MbrKind::Legacy.to_string(), "legacy");
+}
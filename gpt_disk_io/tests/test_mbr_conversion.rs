@@ -0,0 +1,570 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use bytemuck::bytes_of;
+use common::{create_mbr_disk, mbr_record, write_mbr_block};
+use gpt_disk_io::{
+    BlockIoAdapter, Disk, DiskError, GptBuilder, GptTableError,
+    GptVerifyFinding, GptVerifyIssue, SliceBlockIoError, VerifySeverity,
+};
+use gpt_disk_types::{
+    guid, BlockSize, Chs, DiskGeometry, GptHeader, GptPartitionAttributes,
+    GptPartitionEntry, GptPartitionType, Guid, Lba, LbaLe, LbaRangeInclusive,
+    MasterBootRecord, MbrKind, MbrPartitionRecord, U32Le,
+};
+
+#[cfg(feature = "alloc")]
+use gpt_disk_io::{BlockIoEvent, FaultInjectingBlockIo};
+
+const BS: usize = 512;
+const NUM_BLOCKS: usize = 8192;
+const DISK_GUID: Guid = guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870");
+
+fn convert(
+    contents: &mut [u8],
+) -> Result<GptHeader, DiskError<SliceBlockIoError>> {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; BS];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut disk = Disk::new(BlockIoAdapter::new(contents, bs)).unwrap();
+    let mut next_guid = 0u8;
+    disk.convert_mbr_to_gpt(
+        &GptBuilder::new(DISK_GUID),
+        || {
+            next_guid += 1;
+            Guid::from_bytes([next_guid; 16])
+        },
+        &mut block_buf,
+        &mut array_buf,
+    )
+}
+
+#[test]
+fn test_convert_mbr_to_gpt() {
+    let mut contents = create_mbr_disk();
+    let header = convert(&mut contents).unwrap();
+    assert_eq!({ header.disk_guid }, DISK_GUID);
+    assert_eq!(header.first_usable_lba.to_u64(), 34);
+    assert_eq!(header.last_usable_lba.to_u64(), 8158);
+
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; BS];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    disk.verify_gpt_with(&mut block_buf, &mut array_buf, |finding| {
+        panic!("unexpected finding: {finding}")
+    })
+    .unwrap();
+    assert_eq!(
+        disk.read_mbr(&mut block_buf).unwrap(),
+        MasterBootRecord::protective_mbr(8192)
+    );
+
+    let table = disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    let check_entry = |index, partition_type, start, end, guid_byte| {
+        let entry = table.get_partition_entry(index).unwrap();
+        assert_eq!({ entry.partition_type_guid }, partition_type);
+        assert_eq!(
+            entry.lba_range(),
+            LbaRangeInclusive::new(Lba(start), Lba(end))
+        );
+        assert_eq!(
+            { entry.unique_partition_guid },
+            Guid::from_bytes([guid_byte; 16])
+        );
+    };
+
+    // Primary partitions keep their index, the extended partition is
+    // not converted, and logical partitions start at index 4.
+    check_entry(0, GptPartitionType::BASIC_DATA, 2048, 3071, 1);
+    assert!(!table.get_partition_entry(1).unwrap().is_used());
+    assert!(!table.get_partition_entry(2).unwrap().is_used());
+    check_entry(3, GptPartitionType::BASIC_DATA, 7000, 7099, 4);
+    check_entry(4, GptPartitionType::LINUX_FILESYSTEM, 4159, 4658, 2);
+    check_entry(5, GptPartitionType::LINUX_SWAP, 5122, 5221, 3);
+    assert!(!table.get_partition_entry(6).unwrap().is_used());
+
+    // Only the bootable partition is legacy BIOS bootable.
+    let attrs = |index| table.get_partition_entry(index).unwrap().attributes;
+    assert!(attrs(0).legacy_bios_bootable());
+    assert!(!attrs(3).legacy_bios_bootable());
+    assert!(!attrs(4).legacy_bios_bootable());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_convert_mbr_to_gpt_write_order() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; BS];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut contents = create_mbr_disk();
+    let mut io = FaultInjectingBlockIo::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        bs,
+    ));
+    let mut disk = Disk::new(&mut io).unwrap();
+    disk.convert_mbr_to_gpt(
+        &GptBuilder::new(DISK_GUID),
+        || DISK_GUID,
+        &mut block_buf,
+        &mut array_buf,
+    )
+    .unwrap();
+    drop(disk);
+
+    // The protective MBR is only written once the GPT is flushed.
+    let events = io.events();
+    let mbr_index = events
+        .iter()
+        .position(|event| {
+            matches!(
+                event,
+                BlockIoEvent::Write {
+                    start_lba: Lba(0),
+                    ..
+                }
+            )
+        })
+        .unwrap();
+    assert!(mbr_index > 0);
+    assert_eq!(events[mbr_index - 1], BlockIoEvent::Flush);
+    assert!(events[mbr_index + 1..]
+        .iter()
+        .all(|event| *event == BlockIoEvent::Flush));
+}
+
+#[test]
+fn test_convert_mbr_to_gpt_invalid_mbr() {
+    // Missing signature.
+    let mut contents = create_mbr_disk();
+    contents[510] = 0;
+    let expected = contents.clone();
+    assert!(matches!(convert(&mut contents), Err(DiskError::InvalidMbr)));
+    assert_eq!(contents, expected);
+
+    // Already GPT.
+    let mut contents = create_mbr_disk();
+    convert(&mut contents).unwrap();
+    let expected = contents.clone();
+    assert!(matches!(convert(&mut contents), Err(DiskError::InvalidMbr)));
+    assert_eq!(contents, expected);
+
+    // EBR missing signature.
+    let mut contents = create_mbr_disk();
+    contents[5120 * BS + 510] = 0;
+    let expected = contents.clone();
+    assert!(matches!(convert(&mut contents), Err(DiskError::InvalidMbr)));
+    assert_eq!(contents, expected);
+
+    // EBR chain loops back to the same EBR.
+    let mut contents = create_mbr_disk();
+    write_mbr_block(
+        &mut contents,
+        5120,
+        [
+            MbrPartitionRecord::default(),
+            mbr_record(0x05, 1024, 200),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+        ],
+    );
+    let expected = contents.clone();
+    assert!(matches!(
+        convert(&mut contents),
+        Err(DiskError::EbrLoop { lba: Lba(5120) })
+    ));
+    assert_eq!(contents, expected);
+
+    // Logical partition extends past the end of the extended partition.
+    let mut contents = create_mbr_disk();
+    write_mbr_block(
+        &mut contents,
+        5120,
+        [
+            mbr_record(0x82, 2, 2000),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+        ],
+    );
+    let expected = contents.clone();
+    assert!(matches!(
+        convert(&mut contents),
+        Err(DiskError::EbrOutOfRange { lba: Lba(5120) })
+    ));
+    assert_eq!(contents, expected);
+}
+
+#[test]
+fn test_convert_mbr_to_gpt_no_room() {
+    // A partition starts inside the primary partition entry array.
+    let mut contents = create_mbr_disk();
+    write_mbr_block(
+        &mut contents,
+        0,
+        [
+            mbr_record(0x83, 1, 1000),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+        ],
+    );
+    let expected = contents.clone();
+    assert!(matches!(
+        convert(&mut contents),
+        Err(DiskError::Table(GptTableError::OutsideUsableRange))
+    ));
+    assert_eq!(contents, expected);
+
+    // A partition covers the secondary partition entry array.
+    let mut contents = create_mbr_disk();
+    write_mbr_block(
+        &mut contents,
+        0,
+        [
+            mbr_record(0x83, 2048, 6144),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+        ],
+    );
+    let expected = contents.clone();
+    assert!(matches!(
+        convert(&mut contents),
+        Err(DiskError::Table(GptTableError::OutsideUsableRange))
+    ));
+    assert_eq!(contents, expected);
+
+    // Overlapping partitions.
+    let mut contents = create_mbr_disk();
+    write_mbr_block(
+        &mut contents,
+        0,
+        [
+            mbr_record(0x83, 2048, 1024),
+            mbr_record(0x83, 3000, 1024),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+        ],
+    );
+    let expected = contents.clone();
+    assert!(matches!(
+        convert(&mut contents),
+        Err(DiskError::Table(GptTableError::Overlap { index: 0 }))
+    ));
+    assert_eq!(contents, expected);
+}
+
+/// Create a GPT disk with partitions of the given types and ranges.
+fn create_gpt_disk(partitions: &[(GptPartitionType, u64, u64)]) -> Vec<u8> {
+    let bs = BlockSize::BS_512;
+    let mut contents = vec![0; BS * NUM_BLOCKS];
+    let mut block_buf = vec![0u8; BS];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    disk.format_gpt(&GptBuilder::new(DISK_GUID), &mut block_buf)
+        .unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    for (i, (partition_type, start, end)) in (1..).zip(partitions) {
+        table
+            .add_partition(GptPartitionEntry {
+                partition_type_guid: *partition_type,
+                unique_partition_guid: Guid::from_bytes([i; 16]),
+                starting_lba: LbaLe::from_u64(*start),
+                ending_lba: LbaLe::from_u64(*end),
+                ..Default::default()
+            })
+            .unwrap();
+    }
+    table.commit(&mut disk, &mut block_buf).unwrap();
+    drop(disk);
+    contents
+}
+
+fn convert_to_mbr(
+    contents: &mut [u8],
+    wipe_gpt: bool,
+) -> Result<MasterBootRecord, DiskError<SliceBlockIoError>> {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; BS];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut disk = Disk::new(BlockIoAdapter::new(contents, bs)).unwrap();
+    disk.convert_gpt_to_mbr(wipe_gpt, &mut block_buf, &mut array_buf)
+}
+
+fn chs(lba: u64) -> Chs {
+    Chs::from_lba(Lba(lba), DiskGeometry::UNKNOWN).unwrap()
+}
+
+#[test]
+fn test_convert_gpt_to_mbr_primary() {
+    let mut contents = create_gpt_disk(&[
+        (GptPartitionType::LINUX_FILESYSTEM, 4096, 8158),
+        (GptPartitionType::EFI_SYSTEM, 2048, 4095),
+    ]);
+    let mbr = convert_to_mbr(&mut contents, false).unwrap();
+    assert!(mbr.is_signature_valid());
+
+    // Partitions are in order of starting LBA.
+    assert_eq!(
+        mbr.partitions,
+        [
+            MbrPartitionRecord {
+                boot_indicator: 0,
+                start_chs: chs(2048),
+                os_indicator: 0xef,
+                end_chs: chs(4095),
+                starting_lba: U32Le::from_u32(2048),
+                size_in_lba: U32Le::from_u32(2048),
+            },
+            MbrPartitionRecord {
+                boot_indicator: 0,
+                start_chs: chs(4096),
+                os_indicator: 0x83,
+                end_chs: chs(8158),
+                starting_lba: U32Le::from_u32(4096),
+                size_in_lba: U32Le::from_u32(4063),
+            },
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+        ]
+    );
+    assert_eq!(contents[..512], *bytes_of(&mbr));
+
+    // The GPT is still present.
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; BS];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    assert!(disk.read_gpt_table(&mut block_buf, &mut array_buf).is_ok());
+}
+
+#[test]
+fn test_convert_gpt_to_mbr_logical() {
+    let mut contents = create_gpt_disk(&[
+        (GptPartitionType::EFI_SYSTEM, 34, 99),
+        (GptPartitionType::BASIC_DATA, 100, 199),
+        (GptPartitionType::LINUX_FILESYSTEM, 300, 399),
+        (GptPartitionType::LINUX_SWAP, 401, 500),
+        (GptPartitionType::LINUX_LVM, 502, 600),
+        (GptPartitionType::LINUX_RAID, 602, 700),
+    ]);
+    let mbr = convert_to_mbr(&mut contents, true).unwrap();
+
+    // The extended partition covers the EBRs and logical partitions.
+    let extended = mbr.partitions[3];
+    assert_eq!(extended.os_indicator, 0x0f);
+    assert_eq!(
+        extended.lba_range(Lba(0)),
+        LbaRangeInclusive::new(Lba(400), Lba(700))
+    );
+
+    // The GPT is wiped.
+    assert!(contents[BS..34 * BS].iter().all(|b| *b == 0));
+    assert!(contents[8159 * BS..].iter().all(|b| *b == 0));
+
+    // Converting back to GPT gives the same partitions. Logical
+    // partitions start at index 4.
+    convert(&mut contents).unwrap();
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; BS];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let table = disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    let expected = [
+        (0, GptPartitionType::EFI_SYSTEM, 34, 99),
+        (1, GptPartitionType::BASIC_DATA, 100, 199),
+        (2, GptPartitionType::LINUX_FILESYSTEM, 300, 399),
+        (4, GptPartitionType::LINUX_SWAP, 401, 500),
+        (5, GptPartitionType::LINUX_LVM, 502, 600),
+        (6, GptPartitionType::LINUX_RAID, 602, 700),
+    ];
+    for (index, partition_type, start, end) in expected {
+        let entry = table.get_partition_entry(index).unwrap();
+        assert_eq!({ entry.partition_type_guid }, partition_type);
+        assert_eq!(
+            entry.lba_range(),
+            LbaRangeInclusive::new(Lba(start), Lba(end))
+        );
+    }
+    assert!(!table.get_partition_entry(3).unwrap().is_used());
+}
+
+#[test]
+fn test_convert_gpt_to_mbr_bootable() {
+    let mut contents =
+        create_gpt_disk(&[(GptPartitionType::BASIC_DATA, 2048, 4095)]);
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; BS];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    let mut attributes = GptPartitionAttributes::default();
+    attributes.update_legacy_bios_bootable(true);
+    table.set_attributes(0, attributes).unwrap();
+    table.commit(&mut disk, &mut block_buf).unwrap();
+    drop(disk);
+
+    let mbr = convert_to_mbr(&mut contents, false).unwrap();
+    assert_eq!(mbr.partitions[0].boot_indicator, 0x80);
+    assert_eq!(mbr.partitions[0].os_indicator, 0x07);
+}
+
+#[test]
+fn test_convert_gpt_to_mbr_incompatible() {
+    // No MBR equivalent for the partition type.
+    let mut contents = create_gpt_disk(&[
+        (GptPartitionType::BASIC_DATA, 2048, 4095),
+        (GptPartitionType::CHROME_OS_KERNEL, 4096, 8158),
+    ]);
+    let expected = contents.clone();
+    assert!(matches!(
+        convert_to_mbr(&mut contents, true),
+        Err(DiskError::PartitionNotMbrCompatible { index: 1 })
+    ));
+    assert_eq!(contents, expected);
+
+    // No room for the EBR of the first logical partition.
+    let mut contents = create_gpt_disk(&[
+        (GptPartitionType::BASIC_DATA, 100, 199),
+        (GptPartitionType::BASIC_DATA, 200, 299),
+        (GptPartitionType::BASIC_DATA, 300, 399),
+        (GptPartitionType::BASIC_DATA, 400, 499),
+        (GptPartitionType::BASIC_DATA, 600, 699),
+    ]);
+    let expected = contents.clone();
+    assert!(matches!(
+        convert_to_mbr(&mut contents, true),
+        Err(DiskError::PartitionNotMbrCompatible { index: 3 })
+    ));
+    assert_eq!(contents, expected);
+}
+
+fn write_hybrid_mbr(
+    contents: &mut [u8],
+    indices: &[u32],
+) -> Result<MasterBootRecord, DiskError<SliceBlockIoError>> {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; BS];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut disk = Disk::new(BlockIoAdapter::new(contents, bs)).unwrap();
+    disk.write_hybrid_mbr(indices, &mut block_buf, &mut array_buf)
+}
+
+#[test]
+fn test_write_hybrid_mbr() {
+    let mut contents = create_gpt_disk(&[
+        (GptPartitionType::EFI_SYSTEM, 2048, 4095),
+        (GptPartitionType::LINUX_FILESYSTEM, 4096, 8158),
+    ]);
+    let mbr = write_hybrid_mbr(&mut contents, &[1, 0]).unwrap();
+    assert_eq!(mbr.kind(), Some(MbrKind::Hybrid));
+    assert_eq!(
+        mbr.partitions,
+        [
+            MbrPartitionRecord {
+                boot_indicator: 0,
+                start_chs: chs(1),
+                os_indicator: 0xee,
+                end_chs: chs(33),
+                starting_lba: U32Le::from_u32(1),
+                size_in_lba: U32Le::from_u32(33),
+            },
+            MbrPartitionRecord {
+                boot_indicator: 0,
+                start_chs: chs(4096),
+                os_indicator: 0x83,
+                end_chs: chs(8158),
+                starting_lba: U32Le::from_u32(4096),
+                size_in_lba: U32Le::from_u32(4063),
+            },
+            MbrPartitionRecord {
+                boot_indicator: 0,
+                start_chs: chs(2048),
+                os_indicator: 0xef,
+                end_chs: chs(4095),
+                starting_lba: U32Le::from_u32(2048),
+                size_in_lba: U32Le::from_u32(2048),
+            },
+            MbrPartitionRecord::default(),
+        ]
+    );
+    assert_eq!(contents[..512], *bytes_of(&mbr));
+
+    // The verifier reports the mirrored partitions, but accepts the
+    // smaller protective partition.
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; BS];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let mut findings = Vec::new();
+    disk.verify_gpt_with(&mut block_buf, &mut array_buf, |finding| {
+        findings.push(finding);
+    })
+    .unwrap();
+    drop(disk);
+    let warning = |issue| GptVerifyFinding {
+        severity: VerifySeverity::Warning,
+        issue,
+    };
+    assert_eq!(
+        findings,
+        [
+            warning(GptVerifyIssue::ExtraMbrPartition { index: 1 }),
+            warning(GptVerifyIssue::ExtraMbrPartition { index: 2 }),
+        ]
+    );
+
+    // A hybrid MBR cannot be converted to GPT.
+    assert!(matches!(convert(&mut contents), Err(DiskError::InvalidMbr)));
+}
+
+#[test]
+fn test_write_hybrid_mbr_invalid() {
+    let mut contents = create_gpt_disk(&[
+        (GptPartitionType::EFI_SYSTEM, 2048, 2099),
+        (GptPartitionType::BASIC_DATA, 2100, 2199),
+        (GptPartitionType::BASIC_DATA, 2200, 2299),
+        (GptPartitionType::CHROME_OS_KERNEL, 2300, 2399),
+    ]);
+    let expected = contents.clone();
+
+    // Too many partitions.
+    assert!(matches!(
+        write_hybrid_mbr(&mut contents, &[0, 1, 2, 3]),
+        Err(DiskError::PartitionNotMbrCompatible { index: 3 })
+    ));
+
+    // No MBR equivalent for the partition type.
+    assert!(matches!(
+        write_hybrid_mbr(&mut contents, &[3]),
+        Err(DiskError::PartitionNotMbrCompatible { index: 3 })
+    ));
+
+    // Invalid entries.
+    assert!(matches!(
+        write_hybrid_mbr(&mut contents, &[4]),
+        Err(DiskError::Table(GptTableError::EntryNotUsed))
+    ));
+    assert!(matches!(
+        write_hybrid_mbr(&mut contents, &[128]),
+        Err(DiskError::Table(GptTableError::IndexOutOfRange))
+    ));
+
+    assert_eq!(contents, expected);
+}
@@ -0,0 +1,247 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{load_test_disk, mbr_record, write_mbr_block};
+use gpt_disk_io::{
+    BlockIoAdapter, Disk, MbrVerifyFinding, MbrVerifyIssue, PartitionScheme,
+    VerifySeverity,
+};
+use gpt_disk_types::{BlockSize, Chs, DiskGeometry, Lba, MbrPartitionRecord};
+
+const NUM_BLOCKS: usize = 8192;
+
+/// Create a partition record with CHS addresses that match its LBA
+/// range.
+fn record_with_chs(
+    os_indicator: u8,
+    start: u32,
+    size: u32,
+) -> MbrPartitionRecord {
+    let chs = |lba| Chs::from_lba(Lba(lba), DiskGeometry::UNKNOWN).unwrap();
+    let mut record = mbr_record(os_indicator, start, size);
+    record.start_chs = chs(u64::from(start));
+    record.end_chs = chs(u64::from(start + size - 1));
+    record
+}
+
+fn verify(contents: &mut [u8]) -> Vec<MbrVerifyFinding> {
+    let mut block_buf = vec![0u8; 512];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents, BlockSize::BS_512)).unwrap();
+    let mut findings = Vec::new();
+    disk.verify_mbr_with(DiskGeometry::UNKNOWN, &mut block_buf, |f| {
+        findings.push(f);
+    })
+    .unwrap();
+    findings
+}
+
+fn detect(contents: &mut [u8]) -> PartitionScheme {
+    let mut block_buf = vec![0u8; 512];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents, BlockSize::BS_512)).unwrap();
+    disk.detect_partition_scheme(&mut block_buf).unwrap()
+}
+
+fn error(issue: MbrVerifyIssue) -> MbrVerifyFinding {
+    MbrVerifyFinding {
+        severity: VerifySeverity::Error,
+        issue,
+    }
+}
+
+#[test]
+fn test_verify_mbr_valid() {
+    let mut contents = vec![0; 512 * NUM_BLOCKS];
+    let mut bootable = record_with_chs(0x0c, 2048, 2048);
+    bootable.boot_indicator = 0x80;
+    // Unused records are ignored, even if marked bootable.
+    let unused = MbrPartitionRecord {
+        boot_indicator: 0x80,
+        ..MbrPartitionRecord::default()
+    };
+    write_mbr_block(
+        &mut contents,
+        0,
+        [
+            bootable,
+            record_with_chs(0x83, 4096, 4096),
+            unused,
+            MbrPartitionRecord::default(),
+        ],
+    );
+    assert_eq!(verify(&mut contents), []);
+
+    // The protective MBR of a GPT disk is also valid.
+    assert_eq!(verify(&mut load_test_disk()), []);
+}
+
+#[test]
+fn test_verify_mbr_invalid() {
+    let mut contents = vec![0; 512 * NUM_BLOCKS];
+    assert_eq!(
+        verify(&mut contents),
+        [error(MbrVerifyIssue::InvalidSignature)]
+    );
+
+    let mut first = record_with_chs(0x0c, 2048, 2048);
+    first.boot_indicator = 0x80;
+    let mut second = record_with_chs(0x83, 4000, 1000);
+    second.boot_indicator = 0x80;
+    let mut third = record_with_chs(0x83, 6000, 2193);
+    third.boot_indicator = 0x80;
+    write_mbr_block(
+        &mut contents,
+        0,
+        [first, second, third, mbr_record(0x07, 100, 100)],
+    );
+    let warning = |issue| MbrVerifyFinding {
+        severity: VerifySeverity::Warning,
+        issue,
+    };
+    assert_eq!(
+        verify(&mut contents),
+        [
+            error(MbrVerifyIssue::PartitionOverlap {
+                index: 0,
+                other_index: 1
+            }),
+            error(MbrVerifyIssue::MultipleBootablePartitions { index: 1 }),
+            error(MbrVerifyIssue::MultipleBootablePartitions { index: 2 }),
+            error(MbrVerifyIssue::PartitionOutsideDisk { index: 2 }),
+            warning(MbrVerifyIssue::ChsMismatch { index: 3 }),
+        ]
+    );
+
+    assert_eq!(
+        error(MbrVerifyIssue::PartitionOverlap {
+            index: 0,
+            other_index: 1
+        })
+        .to_string(),
+        "error: MBR partition 0 overlaps MBR partition 1"
+    );
+    assert_eq!(
+        warning(MbrVerifyIssue::ChsMismatch { index: 3 }).to_string(),
+        "warning: MBR partition 3: CHS does not match LBA"
+    );
+}
+
+#[test]
+fn test_verify_mbr_chs_out_of_range() {
+    // CHS addresses past the maximum cylinder are accepted if they are
+    // set to the maximum cylinder.
+    let mut contents = vec![0; 512];
+    let mut record = mbr_record(0x83, 0x0100_0000, 100);
+    record.start_chs = Chs([0xfe, 0xff, 0xff]);
+    record.end_chs = Chs([0xff, 0xff, 0xff]);
+    let mut mbr = [MbrPartitionRecord::default(); 4];
+    mbr[0] = record;
+    write_mbr_block(&mut contents, 0, mbr);
+
+    let mut findings = Vec::new();
+    let mut block_buf = vec![0u8; 512];
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+    disk.verify_mbr_with(DiskGeometry::UNKNOWN, &mut block_buf, |f| {
+        findings.push(f.issue);
+    })
+    .unwrap();
+    assert_eq!(
+        findings,
+        [MbrVerifyIssue::PartitionOutsideDisk { index: 0 }]
+    );
+}
+
+#[test]
+fn test_detect_partition_scheme() {
+    let mut contents = load_test_disk();
+    assert_eq!(detect(&mut contents), PartitionScheme::Gpt);
+
+    // Invalid MBR signature.
+    contents[510] = 0;
+    assert_eq!(
+        detect(&mut contents),
+        PartitionScheme::GptWithInvalidProtectiveMbr
+    );
+
+    // Only the secondary header is valid.
+    contents[512] = 0;
+    assert_eq!(
+        detect(&mut contents),
+        PartitionScheme::GptWithInvalidProtectiveMbr
+    );
+
+    // No valid GPT header.
+    let last = contents.len() - 512;
+    contents[last] = 0;
+    assert_eq!(detect(&mut contents), PartitionScheme::Unknown);
+
+    // A legacy MBR takes precedence over the GPT.
+    let mut contents = load_test_disk();
+    write_mbr_block(
+        &mut contents,
+        0,
+        [
+            record_with_chs(0x83, 2048, 2048),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+            MbrPartitionRecord::default(),
+        ],
+    );
+    assert_eq!(detect(&mut contents), PartitionScheme::Mbr);
+
+    // Protective partition with the wrong start or size.
+    for (start, size) in [(2, 8190), (1, 4096)] {
+        let mut contents = load_test_disk();
+        write_mbr_block(
+            &mut contents,
+            0,
+            [
+                mbr_record(0xee, start, size),
+                MbrPartitionRecord::default(),
+                MbrPartitionRecord::default(),
+                MbrPartitionRecord::default(),
+            ],
+        );
+        assert_eq!(
+            detect(&mut contents),
+            PartitionScheme::GptWithInvalidProtectiveMbr
+        );
+    }
+
+    // The protective partition of a hybrid MBR does not need to cover
+    // the whole disk, but must still start at LBA 1.
+    for (start, expected) in [
+        (1, PartitionScheme::Gpt),
+        (2, PartitionScheme::GptWithInvalidProtectiveMbr),
+    ] {
+        let mut contents = load_test_disk();
+        write_mbr_block(
+            &mut contents,
+            0,
+            [
+                mbr_record(0xee, start, 2046),
+                record_with_chs(0x83, 2048, 2048),
+                MbrPartitionRecord::default(),
+                MbrPartitionRecord::default(),
+            ],
+        );
+        assert_eq!(detect(&mut contents), expected);
+    }
+
+    assert_eq!(
+        PartitionScheme::GptWithInvalidProtectiveMbr.to_string(),
+        "GPT with invalid protective MBR"
+    );
+}
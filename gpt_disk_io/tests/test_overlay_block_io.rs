@@ -0,0 +1,172 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "alloc")]
+
+mod common;
+
+use common::{load_test_disk, range, RecordingBlockIo};
+use gpt_disk_io::{
+    BlockIo, BlockIoAdapter, Disk, OverlayBlockIo, OverlayBlockIoError,
+    OverlayCommitOrder,
+};
+use gpt_disk_types::{BlockSize, Lba};
+
+const BS: usize = 512;
+
+#[test]
+fn test_overlay_block_io_dry_run() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0; BS];
+    let mut array_buf = vec![0; BS * 32];
+
+    // The base is read-only, so any write that reached it would fail.
+    let contents = load_test_disk();
+    let mut overlay =
+        OverlayBlockIo::new(BlockIoAdapter::new(contents.as_slice(), bs));
+
+    let mut disk = Disk::new(&mut overlay).unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    table.remove_partition(0).unwrap();
+    table.commit(&mut disk, &mut block_buf).unwrap();
+
+    // The change is visible through the overlay.
+    let table = disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    assert!(!table.get_partition_entry(0).unwrap().is_used());
+    assert!(disk.verify_gpt().unwrap().findings().is_empty());
+    drop(disk);
+
+    // Both headers and both partition entry arrays were written, but
+    // only the first block of each array was changed.
+    assert_eq!(overlay.num_modified_blocks(), 66);
+    assert_eq!(
+        overlay.diff().unwrap(),
+        [range(1, 2), range(8159, 8159), range(8191, 8191)]
+    );
+    assert_eq!(overlay.base().storage(), &contents);
+
+    overlay.discard();
+    assert_eq!(overlay.num_modified_blocks(), 0);
+    assert!(overlay.diff().unwrap().is_empty());
+    let mut disk = Disk::new(&mut overlay).unwrap();
+    let table = disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    assert!(table.get_partition_entry(0).unwrap().is_used());
+}
+
+#[test]
+fn test_overlay_block_io_read_write() {
+    let mut contents: Vec<u8> = (0..8u8).flat_map(|n| [n; BS]).collect();
+    let base = BlockIoAdapter::new(contents.as_mut_slice(), BlockSize::BS_512);
+    let mut overlay = OverlayBlockIo::new(base);
+    assert_eq!(overlay.num_blocks().unwrap(), 8);
+
+    overlay.write_blocks(Lba(2), &[0xaa; BS * 2]).unwrap();
+    overlay.write_blocks(Lba(3), &[0xbb; BS]).unwrap();
+    let mut buf = vec![0; BS * 4];
+    overlay.read_blocks(Lba(1), &mut buf).unwrap();
+    assert_eq!(buf[..BS], [1; BS]);
+    assert_eq!(buf[BS..BS * 2], [0xaa; BS]);
+    assert_eq!(buf[BS * 2..BS * 3], [0xbb; BS]);
+    assert_eq!(buf[BS * 3..], [4; BS]);
+
+    let modified: Vec<_> = overlay
+        .modified_blocks()
+        .map(|(lba, data)| (lba, data[0]))
+        .collect();
+    assert_eq!(modified, [(Lba(2), 0xaa), (Lba(3), 0xbb)]);
+
+    // Writing the same data as the base does not show up in the diff.
+    overlay.write_blocks(Lba(5), &[5; BS]).unwrap();
+    assert_eq!(overlay.num_modified_blocks(), 3);
+    assert_eq!(overlay.diff().unwrap(), [range(2, 3)]);
+
+    // Writes past the end of the base fail.
+    let err = overlay.write_blocks(Lba(7), &[0; BS * 2]).unwrap_err();
+    assert_eq!(
+        err,
+        OverlayBlockIoError::OutOfBounds {
+            start_lba: Lba(7),
+            length_in_bytes: BS * 2,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "out of bounds: start_lba=7, length_in_bytes=1024"
+    );
+    assert_eq!(overlay.num_modified_blocks(), 3);
+
+    drop(overlay);
+    assert_eq!(contents[2 * BS], 2);
+}
+
+#[test]
+fn test_overlay_block_io_vectored() {
+    let mut contents: Vec<u8> = (0..8u8).flat_map(|n| [n; BS]).collect();
+    let mut overlay = OverlayBlockIo::new(RecordingBlockIo::new(&mut contents));
+
+    overlay
+        .write_blocks_vectored(&[(Lba(1), &[0xaa; BS]), (Lba(6), &[0xbb; BS])])
+        .unwrap();
+    assert_eq!(overlay.num_modified_blocks(), 2);
+
+    // Reads are passed on to the base together, and written blocks
+    // replace the data from the base.
+    let mut buf1 = vec![0; BS * 2];
+    let mut buf2 = vec![0; BS];
+    overlay
+        .read_blocks_vectored(&mut [(Lba(0), &mut buf1), (Lba(6), &mut buf2)])
+        .unwrap();
+    assert_eq!(overlay.base().vectored_reads, [[(0, 2), (6, 1)]]);
+    assert_eq!(buf1[..BS], [0; BS]);
+    assert_eq!(buf1[BS..], [0xaa; BS]);
+    assert_eq!(buf2, [0xbb; BS]);
+
+    // Nothing is stored if any request is out of bounds.
+    assert!(matches!(
+        overlay.write_blocks_vectored(&[
+            (Lba(2), &[0xcc; BS]),
+            (Lba(7), &[0xcc; BS * 2])
+        ]),
+        Err(OverlayBlockIoError::OutOfBounds { .. })
+    ));
+    assert_eq!(overlay.num_modified_blocks(), 2);
+    assert!(overlay.base().writes.is_empty());
+}
+
+#[test]
+fn test_overlay_block_io_commit() {
+    let mut contents = vec![0; BS * 8];
+    let mut overlay = OverlayBlockIo::new(RecordingBlockIo::new(&mut contents));
+    for lba in [1, 6, 3] {
+        overlay.write_blocks(Lba(lba), &[1; BS]).unwrap();
+    }
+
+    // Commit a single block first.
+    assert!(overlay.commit_block(Lba(3)).unwrap());
+    assert!(!overlay.commit_block(Lba(3)).unwrap());
+    assert!(!overlay.commit_block(Lba(4)).unwrap());
+    assert_eq!(overlay.base().writes, [(3, 1)]);
+    assert_eq!(overlay.base().flushes, 0);
+
+    overlay.commit(OverlayCommitOrder::Descending).unwrap();
+    assert_eq!(overlay.base().writes, [(3, 1), (6, 1), (1, 1)]);
+    assert_eq!(overlay.base().flushes, 1);
+    assert_eq!(overlay.num_modified_blocks(), 0);
+
+    overlay.write_blocks(Lba(7), &[2; BS]).unwrap();
+    overlay.write_blocks(Lba(0), &[2; BS]).unwrap();
+    overlay.commit(OverlayCommitOrder::Ascending).unwrap();
+    assert_eq!(overlay.base().writes[3..], [(0, 1), (7, 1)]);
+
+    let base = overlay.into_base();
+    let storage = base.inner.storage();
+    for (lba, expected) in [(0, 2), (1, 1), (2, 0), (3, 1), (6, 1), (7, 2)] {
+        assert_eq!(storage[lba * BS], expected);
+    }
+}
@@ -10,7 +10,9 @@ mod common;
 
 use common::check_derives;
 use gpt_disk_types::{
-    BlockSize, GptPartitionEntryArrayLayout, GptPartitionEntrySize, Lba,
+    BlockSize, GptPartitionAlignment, GptPartitionEntryArray,
+    GptPartitionEntryArrayError, GptPartitionEntryArrayLayout,
+    GptPartitionEntrySize, GptPartitionType, Lba, LbaLe,
 };
 
 #[test]
@@ -43,3 +45,67 @@ fn test_partition_entry_array_layout() {
     );
     assert_eq!(layout.num_bytes_exact_as_usize().unwrap(), 256 * 128);
 }
+
+#[test]
+fn test_partition_entry_array_check_alignment() {
+    let bs = BlockSize::BS_512;
+    let layout = GptPartitionEntryArrayLayout {
+        start_lba: Lba(2),
+        entry_size: GptPartitionEntrySize::new(128).unwrap(),
+        num_entries: 4,
+    };
+    let mut storage = vec![0; 512];
+    let mut array =
+        GptPartitionEntryArray::new(layout, bs, &mut storage).unwrap();
+    for (index, start) in [(0, 2048), (2, 63)] {
+        let entry = array.get_partition_entry_mut(index).unwrap();
+        entry.partition_type_guid = GptPartitionType::BASIC_DATA;
+        entry.starting_lba = LbaLe::from_u64(start);
+    }
+
+    let aligned = GptPartitionAlignment {
+        physical_sector: true,
+        one_mib: true,
+        boundary: true,
+    };
+    let misaligned = GptPartitionAlignment {
+        physical_sector: false,
+        one_mib: false,
+        boundary: false,
+    };
+    assert_eq!(
+        array.check_alignment(bs, 4096, 4096).collect::<Vec<_>>(),
+        [(0, aligned), (2, misaligned)]
+    );
+}
+
+#[test]
+fn test_partition_entry_array_set_block_size() {
+    let layout = GptPartitionEntryArrayLayout {
+        start_lba: Lba(2),
+        entry_size: GptPartitionEntrySize::new(128).unwrap(),
+        num_entries: 48,
+    };
+    // Stale bytes after the array.
+    let mut storage = vec![0xff; 12288];
+    storage[..6144].fill(0);
+    let mut array =
+        GptPartitionEntryArray::new(layout, BlockSize::BS_4096, &mut storage)
+            .unwrap();
+    assert_eq!(array.storage().len(), 8192);
+
+    // The padding is zeroed.
+    array.set_block_size(BlockSize::BS_512).unwrap();
+    assert_eq!(array.storage().len(), 6144);
+    array.set_block_size(BlockSize::BS_4096).unwrap();
+    assert_eq!(array.storage().len(), 8192);
+    assert!(array.storage().iter().all(|b| *b == 0));
+
+    // The storage buffer is too small for the block size.
+    assert_eq!(
+        array.set_block_size(BlockSize::new(16384).unwrap()),
+        Err(GptPartitionEntryArrayError::BufferTooSmall)
+    );
+    assert_eq!(array.storage().len(), 8192);
+    assert_eq!(storage[8192..], [0xff; 4096]);
+}
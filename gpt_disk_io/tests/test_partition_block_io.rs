@@ -0,0 +1,164 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{load_test_disk, RecordingBlockIo};
+use gpt_disk_io::{
+    BlockIo, BlockIoAdapter, Disk, PartitionBlockIo, PartitionBlockIoError,
+    SliceBlockIoError,
+};
+use gpt_disk_types::{BlockSize, Lba, LbaRangeInclusive};
+
+const BS: usize = 512;
+
+#[test]
+fn test_partition_block_io() {
+    let mut contents = load_test_disk();
+    let mut io =
+        BlockIoAdapter::new(contents.as_mut_slice(), BlockSize::BS_512);
+
+    // Find the partition's range via a `Disk` that borrows `io`.
+    let mut block_buf = vec![0; BS];
+    let mut disk = Disk::new(&mut io).unwrap();
+    let header = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    let layout = header.get_partition_entry_array_layout().unwrap();
+    let entry = disk
+        .gpt_partition_entry_array_iter(layout, &mut block_buf)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    drop(disk);
+    let range = entry.lba_range().unwrap();
+    assert_eq!(range, LbaRangeInclusive::new(Lba(2048), Lba(4096)).unwrap());
+
+    let mut part = PartitionBlockIo::new(&mut io, range);
+    assert_eq!(part.range(), range);
+    assert_eq!(part.block_size(), BlockSize::BS_512);
+    assert_eq!(part.num_blocks().unwrap(), 2049);
+
+    // Block zero of the partition is block 2048 of the disk.
+    part.write_blocks(Lba(0), &[0xaa; BS]).unwrap();
+    part.write_blocks(Lba(2047), &[0xbb; BS * 2]).unwrap();
+    let mut buf = vec![0; BS * 2];
+    part.read_blocks(Lba(2047), &mut buf).unwrap();
+    assert_eq!(buf, [0xbb; BS * 2]);
+    part.flush().unwrap();
+    assert_eq!(contents[2048 * BS], 0xaa);
+    assert_eq!(contents[2047 * BS], 0);
+    assert_eq!(contents[4096 * BS], 0xbb);
+    assert_eq!(contents[4097 * BS], 0);
+}
+
+#[test]
+fn test_partition_block_io_bounds() {
+    let mut contents = vec![0; BS * 8];
+    let io = BlockIoAdapter::new(contents.as_mut_slice(), BlockSize::BS_512);
+    let range = LbaRangeInclusive::new(Lba(2), Lba(5)).unwrap();
+    let mut part = PartitionBlockIo::new(io, range);
+
+    // Accesses that cross the end of the range fail.
+    let mut buf = vec![0; BS * 2];
+    let err = part.read_blocks(Lba(3), &mut buf).unwrap_err();
+    assert_eq!(
+        err,
+        PartitionBlockIoError::OutOfBounds {
+            start_lba: Lba(3),
+            length_in_bytes: BS * 2,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "out of bounds: start_lba=3, length_in_bytes=1024"
+    );
+    assert!(part.write_blocks(Lba(4), &[1; BS]).is_err());
+    assert_eq!(
+        part.write_blocks(Lba(u64::MAX), &[1; BS]),
+        Err(PartitionBlockIoError::Overflow)
+    );
+
+    // Zero-length accesses at the end of the range are allowed.
+    part.read_blocks(Lba(4), &mut []).unwrap();
+
+    let io = part.into_inner();
+    assert_eq!(io.storage().iter().filter(|b| **b != 0).count(), 0);
+}
+
+#[test]
+fn test_partition_block_io_past_end_of_disk() {
+    // A range that extends past the end of the underlying device
+    // reports errors from the device.
+    let mut contents = vec![0; BS * 4];
+    let io = BlockIoAdapter::new(contents.as_mut_slice(), BlockSize::BS_512);
+    let range = LbaRangeInclusive::new(Lba(2), Lba(9)).unwrap();
+    let mut part = PartitionBlockIo::new(io, range);
+    assert_eq!(part.num_blocks().unwrap(), 8);
+
+    let mut buf = vec![0; BS];
+    part.read_blocks(Lba(1), &mut buf).unwrap();
+    let err = part.read_blocks(Lba(2), &mut buf).unwrap_err();
+    assert_eq!(
+        err,
+        PartitionBlockIoError::Io(SliceBlockIoError::OutOfBounds {
+            start_lba: Lba(4),
+            length_in_bytes: BS,
+        })
+    );
+}
+
+#[test]
+fn test_partition_block_io_vectored() {
+    let mut contents: Vec<u8> = (0..64u8).flat_map(|n| [n; BS]).collect();
+    let range = LbaRangeInclusive::new(Lba(2), Lba(33)).unwrap();
+    let mut part =
+        PartitionBlockIo::new(RecordingBlockIo::new(&mut contents), range);
+
+    // Reads are translated and passed on together, and the caller's
+    // LBAs are unchanged.
+    let mut buf1 = vec![0; BS];
+    let mut buf2 = vec![0; BS * 2];
+    let mut requests = [(Lba(0), buf1.as_mut_slice()), (Lba(30), &mut buf2)];
+    part.read_blocks_vectored(&mut requests).unwrap();
+    assert_eq!(requests[0].0, Lba(0));
+    assert_eq!(requests[1].0, Lba(30));
+    assert_eq!(part.inner().vectored_reads, [[(2, 1), (32, 2)]]);
+    assert_eq!(buf1, [2; BS]);
+    assert_eq!(buf2[..BS], [32; BS]);
+    assert_eq!(buf2[BS..], [33; BS]);
+
+    // Writes are passed on in batches.
+    let blocks: Vec<Vec<u8>> = (0..20u8).map(|n| vec![0xa0 + n; BS]).collect();
+    let requests: Vec<(Lba, &[u8])> = (0..20)
+        .map(|i| (Lba(i), blocks[usize::try_from(i).unwrap()].as_slice()))
+        .collect();
+    part.write_blocks_vectored(&requests).unwrap();
+    let batch_lens: Vec<usize> =
+        part.inner().vectored_writes.iter().map(Vec::len).collect();
+    assert_eq!(batch_lens, [16, 4]);
+    assert_eq!(part.inner().vectored_writes[1][0], (18, 1));
+    assert_eq!(part.inner().inner.storage()[21 * BS], 0xa0 + 19);
+
+    // If any request is out of bounds, nothing is read or written.
+    let mut buf3 = vec![0; BS];
+    assert_eq!(
+        part.read_blocks_vectored(&mut [
+            (Lba(0), buf1.as_mut_slice()),
+            (Lba(32), &mut buf3)
+        ]),
+        Err(PartitionBlockIoError::OutOfBounds {
+            start_lba: Lba(32),
+            length_in_bytes: BS,
+        })
+    );
+    assert!(part
+        .write_blocks_vectored(&[(Lba(0), &[1; BS]), (Lba(31), &[1; BS * 2])])
+        .is_err());
+    assert_eq!(part.inner().vectored_reads.len(), 1);
+    assert_eq!(part.inner().vectored_writes.len(), 2);
+}
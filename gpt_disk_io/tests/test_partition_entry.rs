@@ -10,8 +10,9 @@ mod common;
 
 use common::check_derives;
 use gpt_disk_types::{
-    GptPartitionAttributes, GptPartitionEntry, GptPartitionName,
-    GptPartitionType, Guid, U16Le, U64Le,
+    BlockSize, GptPartitionAlignment, GptPartitionAttributes,
+    GptPartitionEntry, GptPartitionName, GptPartitionType, Guid, LbaLe, U16Le,
+    U64Le,
 };
 
 #[test]
@@ -34,6 +35,60 @@ fn test_partition_type() {
     );
 }
 
+#[test]
+fn test_partition_type_from_mbr_os_indicator() {
+    for os_indicator in [0x01, 0x04, 0x06, 0x07, 0x0b, 0x0c, 0x0e] {
+        assert_eq!(
+            GptPartitionType::from_mbr_os_indicator(os_indicator),
+            Some(GptPartitionType::BASIC_DATA)
+        );
+    }
+    assert_eq!(
+        GptPartitionType::from_mbr_os_indicator(0x83),
+        Some(GptPartitionType::LINUX_FILESYSTEM)
+    );
+    assert_eq!(
+        GptPartitionType::from_mbr_os_indicator(0x82),
+        Some(GptPartitionType::LINUX_SWAP)
+    );
+    assert_eq!(
+        GptPartitionType::from_mbr_os_indicator(0xef),
+        Some(GptPartitionType::EFI_SYSTEM)
+    );
+
+    // Unused, extended, protective, and unknown types.
+    for os_indicator in [0x00, 0x05, 0x0f, 0x85, 0xee, 0x42] {
+        assert_eq!(GptPartitionType::from_mbr_os_indicator(os_indicator), None);
+    }
+}
+
+#[test]
+fn test_partition_type_to_mbr_os_indicator() {
+    assert_eq!(
+        GptPartitionType::BASIC_DATA.to_mbr_os_indicator(),
+        Some(0x07)
+    );
+    assert_eq!(
+        GptPartitionType::EFI_SYSTEM.to_mbr_os_indicator(),
+        Some(0xef)
+    );
+    assert_eq!(GptPartitionType::UNUSED.to_mbr_os_indicator(), None);
+    assert_eq!(
+        GptPartitionType::CHROME_OS_KERNEL.to_mbr_os_indicator(),
+        None
+    );
+
+    // Round trip.
+    for os_indicator in [0x07, 0x27, 0x82, 0x83, 0x8e, 0xa5, 0xaf, 0xef, 0xfd] {
+        assert_eq!(
+            GptPartitionType::from_mbr_os_indicator(os_indicator)
+                .unwrap()
+                .to_mbr_os_indicator(),
+            Some(os_indicator)
+        );
+    }
+}
+
 #[test]
 fn test_required_partition_attribute() {
     check_derives::<GptPartitionAttributes>();
@@ -146,3 +201,46 @@ fn test_partition_name() {
 fn test_partition_entry() {
     check_derives::<GptPartitionEntry>();
 }
+
+#[test]
+fn test_partition_entry_check_alignment() {
+    check_derives::<GptPartitionAlignment>();
+
+    let entry = |start| GptPartitionEntry {
+        starting_lba: LbaLe::from_u64(start),
+        ..Default::default()
+    };
+    let bs = BlockSize::BS_512;
+
+    let alignment = entry(2048).check_alignment(bs, 4096, 2048 * 512);
+    assert_eq!(
+        alignment,
+        GptPartitionAlignment {
+            physical_sector: true,
+            one_mib: true,
+            boundary: true,
+        }
+    );
+    assert!(alignment.is_fully_aligned());
+
+    // A partition created by an old DOS-era tool.
+    let alignment = entry(63).check_alignment(bs, 4096, 512);
+    assert_eq!(
+        alignment,
+        GptPartitionAlignment {
+            physical_sector: false,
+            one_mib: false,
+            boundary: true,
+        }
+    );
+    assert!(!alignment.is_fully_aligned());
+    assert_eq!(
+        alignment.to_string(),
+        "physical_sector: false, one_mib: false, boundary: true"
+    );
+
+    let alignment = entry(40).check_alignment(bs, 4096, 8 * 1024 * 1024);
+    assert!(alignment.physical_sector);
+    assert!(!alignment.one_mib);
+    assert!(!alignment.boundary);
+}
@@ -0,0 +1,407 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{check_gpt_valid, load_test_disk};
+use gpt_disk_io::{
+    BlockIo, BlockIoAdapter, Disk, DiskError, GptPartitionMoveProgress,
+    GptTableError, SliceBlockIoError,
+};
+use gpt_disk_types::{BlockSize, Lba, LbaRangeInclusive};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const BS: usize = 512;
+const PART_START: usize = 2048;
+const PART_BLOCKS: usize = 2049;
+
+/// Fill each block of the test partition with its index within the
+/// partition.
+fn load_test_disk_with_data() -> Vec<u8> {
+    let mut contents = load_test_disk();
+    for i in 0..PART_BLOCKS {
+        let block = &mut contents[(PART_START + i) * BS..][..BS];
+        for word in block.chunks_mut(4) {
+            word.copy_from_slice(&u32::try_from(i).unwrap().to_le_bytes());
+        }
+    }
+    contents
+}
+
+fn check_data(contents: &[u8], start: usize) {
+    let expected = load_test_disk_with_data();
+    assert_eq!(
+        contents[start * BS..][..PART_BLOCKS * BS],
+        expected[PART_START * BS..][..PART_BLOCKS * BS]
+    );
+}
+
+fn move_partition(
+    contents: &mut [u8],
+    new_start: u64,
+    chunk_blocks: usize,
+    progress: &mut Vec<GptPartitionMoveProgress>,
+) -> Result<LbaRangeInclusive, DiskError<SliceBlockIoError>> {
+    let bs = BlockSize::BS_512;
+    let mut chunk_buf = vec![0u8; BS * chunk_blocks];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut disk = Disk::new(BlockIoAdapter::new(contents, bs)).unwrap();
+    disk.move_partition(
+        0,
+        Lba(new_start),
+        &mut chunk_buf,
+        &mut array_buf,
+        |p| progress.push(*p),
+    )
+}
+
+#[test]
+fn test_move_partition_up() {
+    let mut contents = load_test_disk_with_data();
+    let mut progress = Vec::new();
+    let range = move_partition(&mut contents, 3000, 8, &mut progress).unwrap();
+    assert_eq!(range, LbaRangeInclusive::new(Lba(3000), Lba(5048)).unwrap());
+    check_data(&contents, 3000);
+    check_gpt_valid(&mut contents, BlockSize::BS_512);
+
+    // Each chunk is a full buffer except the last.
+    assert_eq!(progress.len(), (PART_BLOCKS + 7) / 8);
+    assert_eq!(progress[0].blocks_copied, 8);
+    assert!(!progress[0].is_copy_complete());
+    let last = progress.last().unwrap();
+    assert!(last.is_copy_complete());
+    assert_eq!(last.destination(), Some(range));
+}
+
+#[test]
+fn test_move_partition_down() {
+    // The chunk size is limited by the distance of the move.
+    let mut contents = load_test_disk_with_data();
+    let mut progress = Vec::new();
+    let range = move_partition(&mut contents, 2043, 64, &mut progress).unwrap();
+    assert_eq!(range, LbaRangeInclusive::new(Lba(2043), Lba(4091)).unwrap());
+    check_data(&contents, 2043);
+    check_gpt_valid(&mut contents, BlockSize::BS_512);
+    assert_eq!(progress.len(), (PART_BLOCKS + 4) / 5);
+    assert!(progress
+        .iter()
+        .rev()
+        .skip(1)
+        .all(|p| p.blocks_copied % 5 == 0));
+
+    // No overlap between source and destination.
+    let mut contents = load_test_disk_with_data();
+    let range = move_partition(&mut contents, 34, 64, &mut progress).unwrap();
+    assert_eq!(range, LbaRangeInclusive::new(Lba(34), Lba(2082)).unwrap());
+    check_data(&contents, 34);
+    check_gpt_valid(&mut contents, BlockSize::BS_512);
+}
+
+#[test]
+fn test_move_partition_invalid() {
+    let mut contents = load_test_disk_with_data();
+    let expected = contents.clone();
+    let mut progress = Vec::new();
+
+    // Past the last usable LBA.
+    assert!(matches!(
+        move_partition(&mut contents, 7000, 8, &mut progress),
+        Err(DiskError::Table(GptTableError::OutsideUsableRange))
+    ));
+
+    // Before the first usable LBA.
+    assert!(matches!(
+        move_partition(&mut contents, 2, 8, &mut progress),
+        Err(DiskError::Table(GptTableError::OutsideUsableRange))
+    ));
+
+    // Buffer smaller than a block.
+    assert!(matches!(
+        move_partition(&mut contents, 3000, 0, &mut progress),
+        Err(DiskError::BufferTooSmall)
+    ));
+
+    assert!(progress.is_empty());
+    assert_eq!(contents, expected);
+}
+
+/// Block IO that fails all writes after a limit is reached.
+struct FailingBlockIo<'a> {
+    inner: BlockIoAdapter<&'a mut [u8]>,
+    writes_left: usize,
+}
+
+impl BlockIo for FailingBlockIo<'_> {
+    type Error = SliceBlockIoError;
+
+    fn block_size(&self) -> BlockSize {
+        self.inner.block_size()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        self.inner.num_blocks()
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.inner.read_blocks(start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        if self.writes_left == 0 {
+            return Err(SliceBlockIoError::ReadOnly);
+        }
+        self.writes_left -= 1;
+        self.inner.write_blocks(start_lba, src)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+#[test]
+fn test_resume_partition_move() {
+    let bs = BlockSize::BS_512;
+    let mut chunk_buf = vec![0u8; BS * 16];
+    let mut array_buf = vec![0u8; BS * 32];
+
+    for new_start in [2100, 1000] {
+        // Interrupt the move part way through the copy.
+        let mut contents = load_test_disk_with_data();
+        let mut last_progress = None;
+        let mut disk = Disk::new(FailingBlockIo {
+            inner: BlockIoAdapter::new(contents.as_mut_slice(), bs),
+            writes_left: 50,
+        })
+        .unwrap();
+        assert!(matches!(
+            disk.move_partition(
+                0,
+                Lba(new_start),
+                &mut chunk_buf,
+                &mut array_buf,
+                |p| last_progress = Some(*p),
+            ),
+            Err(DiskError::Io(SliceBlockIoError::ReadOnly))
+        ));
+        drop(disk);
+        let last_progress = last_progress.unwrap();
+        assert_eq!(last_progress.blocks_copied, 50 * 16);
+
+        // Resume with the last reported progress.
+        let mut disk =
+            Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs))
+                .unwrap();
+        let mut num_callbacks = 0;
+        let range = disk
+            .resume_partition_move(
+                &last_progress,
+                &mut chunk_buf,
+                &mut array_buf,
+                |_| num_callbacks += 1,
+            )
+            .unwrap();
+        drop(disk);
+        assert_eq!(Some(range), last_progress.destination());
+        assert_eq!(num_callbacks, (PART_BLOCKS - 50 * 16 + 15) / 16);
+
+        // Resuming a completed move does nothing.
+        let expected = contents.clone();
+        let mut disk =
+            Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs))
+                .unwrap();
+        assert_eq!(
+            disk.resume_partition_move(
+                &last_progress,
+                &mut chunk_buf,
+                &mut array_buf,
+                |_| panic!("unexpected progress"),
+            )
+            .unwrap(),
+            range
+        );
+        drop(disk);
+        assert_eq!(contents, expected);
+
+        check_data(&contents, usize::try_from(new_start).unwrap());
+        check_gpt_valid(&mut contents, BlockSize::BS_512);
+    }
+}
+
+#[test]
+fn test_resume_partition_move_during_gpt_commit() {
+    let bs = BlockSize::BS_512;
+    let mut chunk_buf = vec![0u8; BS * 16];
+    let mut array_buf = vec![0u8; BS * 32];
+    let num_chunks = (PART_BLOCKS + 15) / 16;
+
+    // The GPT commit writes the secondary array and header, then the
+    // primary array and header. Interrupt it before each write.
+    for gpt_writes in 0..4 {
+        let mut contents = load_test_disk_with_data();
+        let mut last_progress = None;
+        let mut disk = Disk::new(FailingBlockIo {
+            inner: BlockIoAdapter::new(contents.as_mut_slice(), bs),
+            writes_left: num_chunks + gpt_writes,
+        })
+        .unwrap();
+        assert!(matches!(
+            disk.move_partition(
+                0,
+                Lba(3000),
+                &mut chunk_buf,
+                &mut array_buf,
+                |p| last_progress = Some(*p),
+            ),
+            Err(DiskError::Io(SliceBlockIoError::ReadOnly))
+        ));
+        drop(disk);
+        let last_progress = last_progress.unwrap();
+        assert!(last_progress.is_copy_complete());
+
+        let mut disk =
+            Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs))
+                .unwrap();
+        let range = disk
+            .resume_partition_move(
+                &last_progress,
+                &mut chunk_buf,
+                &mut array_buf,
+                |_| panic!("unexpected progress"),
+            )
+            .unwrap();
+        drop(disk);
+        assert_eq!(Some(range), last_progress.destination());
+        check_data(&contents, 3000);
+        check_gpt_valid(&mut contents, BlockSize::BS_512);
+    }
+}
+
+/// Block IO that only makes writes durable when flushed, like a disk
+/// with a volatile write cache. Reads see all writes.
+struct VolatileBlockIo {
+    visible: Vec<u8>,
+    durable: Rc<RefCell<Vec<u8>>>,
+}
+
+impl BlockIo for VolatileBlockIo {
+    type Error = SliceBlockIoError;
+
+    fn block_size(&self) -> BlockSize {
+        BlockSize::BS_512
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        Ok(u64::try_from(self.visible.len() / BS).unwrap())
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        BlockIoAdapter::new(self.visible.as_mut_slice(), BlockSize::BS_512)
+            .read_blocks(start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        BlockIoAdapter::new(self.visible.as_mut_slice(), BlockSize::BS_512)
+            .write_blocks(start_lba, src)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.durable.borrow_mut().copy_from_slice(&self.visible);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_resume_partition_move_after_power_loss() {
+    let bs = BlockSize::BS_512;
+    let mut chunk_buf = vec![0u8; BS * 256];
+    let mut array_buf = vec![0u8; BS * 32];
+
+    for new_start in [2100, 1000] {
+        // Record what was durable each time progress was reported.
+        let durable = Rc::new(RefCell::new(load_test_disk_with_data()));
+        let mut disk = Disk::new(VolatileBlockIo {
+            visible: load_test_disk_with_data(),
+            durable: durable.clone(),
+        })
+        .unwrap();
+        let mut saved = Vec::new();
+        disk.move_partition(
+            0,
+            Lba(new_start),
+            &mut chunk_buf,
+            &mut array_buf,
+            |p| saved.push((*p, durable.borrow().clone())),
+        )
+        .unwrap();
+        drop(disk);
+        assert!(saved.len() > 1);
+
+        // Losing power right after any progress report and resuming
+        // from the durable contents gives the correct data.
+        for (progress, mut contents) in saved {
+            let mut disk =
+                Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs))
+                    .unwrap();
+            disk.resume_partition_move(
+                &progress,
+                &mut chunk_buf,
+                &mut array_buf,
+                |_| {},
+            )
+            .unwrap();
+            drop(disk);
+            check_data(&contents, usize::try_from(new_start).unwrap());
+            check_gpt_valid(&mut contents, BlockSize::BS_512);
+        }
+    }
+}
+
+#[test]
+fn test_resume_partition_move_mismatch() {
+    let bs = BlockSize::BS_512;
+    let mut contents = load_test_disk_with_data();
+    let mut chunk_buf = vec![0u8; BS * 16];
+    let mut array_buf = vec![0u8; BS * 32];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+
+    // The partition entry covers neither the source nor destination.
+    let progress = GptPartitionMoveProgress {
+        index: 0,
+        source: LbaRangeInclusive::new(Lba(100), Lba(200)).unwrap(),
+        destination_start: Lba(300),
+        blocks_copied: 0,
+    };
+    assert!(matches!(
+        disk.resume_partition_move(
+            &progress,
+            &mut chunk_buf,
+            &mut array_buf,
+            |_| {}
+        ),
+        Err(DiskError::Table(GptTableError::InvalidRange))
+    ));
+}
@@ -0,0 +1,124 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::load_test_disk;
+use gpt_disk_io::{
+    BlockIoAdapter, BlockSizeConfidence, BlockSizeProbe, Disk, GptBuilder,
+};
+use gpt_disk_types::{guid, BlockSize};
+
+fn probe(contents: &mut [u8], bs: BlockSize) -> Option<BlockSizeProbe> {
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut disk = Disk::new(BlockIoAdapter::new(contents, bs)).unwrap();
+    disk.probe_block_size(&mut block_buf).unwrap()
+}
+
+/// Create a disk image formatted with the given block size.
+fn create_disk(bs: BlockSize) -> Vec<u8> {
+    let mut contents = vec![0; 4 * 1024 * 1024];
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    disk.format_gpt(
+        &GptBuilder::new(guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870")),
+        &mut block_buf,
+    )
+    .unwrap();
+    drop(disk);
+    contents
+}
+
+#[test]
+fn test_probe_block_size() {
+    let expected = |block_size| {
+        Some(BlockSizeProbe {
+            block_size,
+            confidence: BlockSizeConfidence::BothHeaders,
+            ambiguous: false,
+        })
+    };
+
+    let mut contents = load_test_disk();
+    assert_eq!(
+        probe(&mut contents, BlockSize::BS_512),
+        expected(BlockSize::BS_512)
+    );
+
+    // A 4Kn image can be probed with either block size.
+    let mut contents = create_disk(BlockSize::BS_4096);
+    assert_eq!(
+        probe(&mut contents, BlockSize::BS_512),
+        expected(BlockSize::BS_4096)
+    );
+    assert_eq!(
+        probe(&mut contents, BlockSize::BS_4096),
+        expected(BlockSize::BS_4096)
+    );
+
+    // Reading with a block size larger than the GPT's block size finds
+    // the header within the first block.
+    let mut contents = create_disk(BlockSize::BS_512);
+    assert_eq!(
+        probe(&mut contents, BlockSize::BS_4096),
+        expected(BlockSize::BS_512)
+    );
+}
+
+#[test]
+fn test_probe_block_size_partial() {
+    // Corrupt the secondary header.
+    let mut contents = create_disk(BlockSize::BS_4096);
+    let len = contents.len();
+    contents[len - 4096] = 0;
+    let result = probe(&mut contents, BlockSize::BS_512).unwrap();
+    assert_eq!(result.block_size, BlockSize::BS_4096);
+    assert_eq!(result.confidence, BlockSizeConfidence::PrimaryHeader);
+
+    // Corrupt the primary header's checksum.
+    contents[4096 + 16] ^= 1;
+    let result = probe(&mut contents, BlockSize::BS_512).unwrap();
+    assert_eq!(result.block_size, BlockSize::BS_4096);
+    assert_eq!(result.confidence, BlockSizeConfidence::Signature);
+    assert_eq!(result.to_string(), "4096 bytes (signature only)");
+
+    // Stronger evidence wins over a stale signature at another size.
+    let mut contents = create_disk(BlockSize::BS_4096);
+    contents[512..520].copy_from_slice(b"EFI PART");
+    let result = probe(&mut contents, BlockSize::BS_512).unwrap();
+    assert_eq!(result.block_size, BlockSize::BS_4096);
+    assert!(!result.ambiguous);
+}
+
+#[test]
+fn test_probe_block_size_ambiguous() {
+    let mut contents = vec![0; 1024 * 1024];
+    contents[512..520].copy_from_slice(b"EFI PART");
+    contents[4096..4104].copy_from_slice(b"EFI PART");
+    let result = probe(&mut contents, BlockSize::BS_512).unwrap();
+    assert_eq!(
+        result,
+        BlockSizeProbe {
+            block_size: BlockSize::BS_512,
+            confidence: BlockSizeConfidence::Signature,
+            ambiguous: true,
+        }
+    );
+    assert_eq!(result.to_string(), "512 bytes (signature only, ambiguous)");
+}
+
+#[test]
+fn test_probe_block_size_none() {
+    let mut contents = vec![0; 1024 * 1024];
+    assert_eq!(probe(&mut contents, BlockSize::BS_512), None);
+
+    // Disk too small for any header.
+    let mut contents = vec![0; 512];
+    assert_eq!(probe(&mut contents, BlockSize::BS_512), None);
+}
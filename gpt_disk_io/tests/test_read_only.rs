@@ -0,0 +1,103 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{load_test_disk, RecordingBlockIo};
+use gpt_disk_io::{
+    BlockIo, Disk, PartitionScheme, ReadOnlyBlockIo, ReadOnlyBlockIoError,
+    SliceBlockIoError,
+};
+use gpt_disk_types::Lba;
+
+const BS: usize = 512;
+
+#[test]
+fn test_read_only_block_io() {
+    let mut contents: Vec<u8> = (0..4u8).flat_map(|n| [n; BS]).collect();
+    let mut io = ReadOnlyBlockIo::new(RecordingBlockIo::new(&mut contents));
+    assert_eq!(io.num_blocks().unwrap(), 4);
+
+    let mut buf = vec![0; BS * 2];
+    io.read_blocks(Lba(2), &mut buf).unwrap();
+    assert_eq!(buf[..BS], [2; BS]);
+    assert_eq!(buf[BS..], [3; BS]);
+
+    // Writes never reach the underlying IO, and neither do flushes.
+    let err = io.write_blocks(Lba(0), &[0xff; BS]).unwrap_err();
+    assert_eq!(err, ReadOnlyBlockIoError::ReadOnly);
+    assert_eq!(err.to_string(), "attempted to write to a read-only device");
+    io.flush().unwrap();
+    assert_eq!(io.inner().reads, [(2, 2)]);
+    assert!(io.inner().writes.is_empty());
+    assert_eq!(io.inner().flushes, 0);
+
+    // Errors from the underlying IO are passed through.
+    assert_eq!(
+        io.read_blocks(Lba(4), &mut buf),
+        Err(ReadOnlyBlockIoError::Io(SliceBlockIoError::OutOfBounds {
+            start_lba: Lba(4),
+            length_in_bytes: BS * 2,
+        }))
+    );
+
+    drop(io.into_inner());
+    assert_eq!(contents[0], 0);
+}
+
+#[test]
+fn test_disk_open_read_only() {
+    let mut block_buf = vec![0; BS];
+    let mut array_buf = vec![0; BS * 32];
+    let mut contents = load_test_disk();
+    let mut io = RecordingBlockIo::new(&mut contents);
+
+    {
+        let mut disk = Disk::open_read_only(&mut io).unwrap();
+        assert_eq!(
+            disk.detect_partition_scheme(&mut block_buf).unwrap(),
+            PartitionScheme::Gpt
+        );
+        let mut num_findings = 0;
+        disk.verify_gpt_with(&mut block_buf, &mut array_buf, |_| {
+            num_findings += 1;
+        })
+        .unwrap();
+        assert_eq!(num_findings, 0);
+
+        let table =
+            disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+        assert!(table.get_partition_entry(0).unwrap().is_used());
+        let layout = table.primary_header().get_partition_entry_array_layout();
+        let num_used = disk
+            .gpt_partition_entry_array_iter(layout.unwrap(), &mut block_buf)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().is_used())
+            .count();
+        assert_eq!(num_used, 1);
+    }
+
+    // Nothing was written, and dropping the disk did not flush.
+    assert!(!io.reads.is_empty());
+    assert!(io.writes.is_empty());
+    assert_eq!(io.flushes, 0);
+    drop(io);
+    assert_eq!(contents, load_test_disk());
+}
+
+#[test]
+fn test_disk_read_write_flushes_on_drop() {
+    let mut contents = load_test_disk();
+    let mut io = RecordingBlockIo::new(&mut contents);
+    {
+        let mut disk = Disk::new(&mut io).unwrap();
+        let mut block_buf = vec![0; BS];
+        disk.read_mbr(&mut block_buf).unwrap();
+    }
+    assert_eq!(io.flushes, 1);
+}
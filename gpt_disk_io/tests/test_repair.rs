@@ -0,0 +1,168 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{create_second_partition_entry, load_test_disk};
+use gpt_disk_io::{BlockIoAdapter, Disk, DiskError, GptRepairSummary};
+use gpt_disk_types::BlockSize;
+
+fn repair(
+    contents: &mut [u8],
+) -> Result<GptRepairSummary, DiskError<gpt_disk_io::SliceBlockIoError>> {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+    let mut disk = Disk::new(BlockIoAdapter::new(contents, bs)).unwrap();
+    let summary = disk.repair_gpt(&mut block_buf, &mut array_buf)?;
+    disk.flush().unwrap();
+    Ok(summary)
+}
+
+const PRIMARY_HEADER: usize = 0x200;
+const PRIMARY_ARRAY: usize = 0x400;
+const SECONDARY_ARRAY: usize = 8159 * 512;
+const SECONDARY_HEADER: usize = 8191 * 512;
+
+#[test]
+fn test_repair_intact() {
+    let mut contents = load_test_disk();
+    let summary = repair(&mut contents).unwrap();
+    assert_eq!(summary, GptRepairSummary::default());
+    assert!(!summary.any_repaired());
+    assert_eq!(contents, load_test_disk());
+}
+
+#[test]
+fn test_repair_primary_header() {
+    // Corrupt the checksum.
+    let mut contents = load_test_disk();
+    contents[PRIMARY_HEADER + 0x10] ^= 1;
+    assert_eq!(
+        repair(&mut contents).unwrap(),
+        GptRepairSummary {
+            primary_header_repaired: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(contents, load_test_disk());
+
+    // Destroy the header and the array.
+    let mut contents = load_test_disk();
+    contents[PRIMARY_HEADER..PRIMARY_ARRAY + 512].fill(0);
+    assert_eq!(
+        repair(&mut contents).unwrap(),
+        GptRepairSummary {
+            primary_header_repaired: true,
+            primary_array_repaired: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(contents, load_test_disk());
+}
+
+#[test]
+fn test_repair_secondary_header() {
+    let mut contents = load_test_disk();
+    contents[SECONDARY_HEADER..].fill(0);
+    contents[SECONDARY_ARRAY] ^= 1;
+    assert_eq!(
+        repair(&mut contents).unwrap(),
+        GptRepairSummary {
+            secondary_header_repaired: true,
+            secondary_array_repaired: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(contents, load_test_disk());
+}
+
+#[test]
+fn test_repair_array() {
+    // A corrupt primary array is restored from the secondary array.
+    let mut contents = load_test_disk();
+    contents[PRIMARY_ARRAY] ^= 1;
+    assert_eq!(
+        repair(&mut contents).unwrap(),
+        GptRepairSummary {
+            primary_array_repaired: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(contents, load_test_disk());
+
+    // A corrupt secondary array is restored from the primary array.
+    let mut contents = load_test_disk();
+    contents[SECONDARY_ARRAY + 0x20] ^= 1;
+    assert_eq!(
+        repair(&mut contents).unwrap(),
+        GptRepairSummary {
+            secondary_array_repaired: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(contents, load_test_disk());
+}
+
+#[test]
+fn test_repair_stale_primary_header() {
+    // Add a partition, then put back the old primary header, as if the
+    // write of the primary copy was interrupted after its array was
+    // written. Both headers are intact, but only the secondary header
+    // matches the arrays.
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+    let mut contents = load_test_disk();
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    table
+        .add_partition(create_second_partition_entry())
+        .unwrap();
+    table.commit(&mut disk, &mut block_buf).unwrap();
+    drop(disk);
+    let expected = contents.clone();
+    contents[PRIMARY_HEADER..PRIMARY_ARRAY]
+        .copy_from_slice(&load_test_disk()[PRIMARY_HEADER..PRIMARY_ARRAY]);
+
+    assert_eq!(
+        repair(&mut contents).unwrap(),
+        GptRepairSummary {
+            primary_header_repaired: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(contents, expected);
+}
+
+#[test]
+fn test_repair_unrecoverable() {
+    // Both headers damaged.
+    let mut contents = load_test_disk();
+    contents[PRIMARY_HEADER] = 0;
+    contents[SECONDARY_HEADER] = 0;
+    let expected = contents.clone();
+    assert!(matches!(
+        repair(&mut contents),
+        Err(DiskError::InvalidGptHeader)
+    ));
+    assert_eq!(contents, expected);
+
+    // Both arrays damaged.
+    let mut contents = load_test_disk();
+    contents[PRIMARY_ARRAY] ^= 1;
+    contents[SECONDARY_ARRAY] ^= 1;
+    let expected = contents.clone();
+    assert!(matches!(
+        repair(&mut contents),
+        Err(DiskError::InvalidPartitionEntryArray)
+    ));
+    assert_eq!(contents, expected);
+}
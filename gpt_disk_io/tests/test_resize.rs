@@ -0,0 +1,205 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{
+    check_gpt_valid, create_partition_entry, load_test_disk,
+    load_test_disk_with_reserved_blocks,
+};
+use gpt_disk_io::{BlockIoAdapter, Disk, DiskError};
+use gpt_disk_types::{BlockSize, LbaLe, MasterBootRecord};
+
+#[test]
+fn test_relocate_backup_to_end() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+
+    // Grow the disk from 4MiB to 8MiB.
+    let mut contents = load_test_disk();
+    contents.resize(8 * 1024 * 1024, 0);
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+
+    let primary = disk
+        .relocate_backup_to_end(&mut block_buf, &mut array_buf)
+        .unwrap();
+    assert_eq!(primary.alternate_lba, LbaLe::from_u64(16383));
+    assert_eq!(primary.last_usable_lba, LbaLe::from_u64(16350));
+    assert_eq!(
+        disk.read_primary_gpt_header(&mut block_buf).unwrap(),
+        primary
+    );
+
+    let secondary = disk.read_secondary_gpt_header(&mut block_buf).unwrap();
+    assert_eq!(secondary.my_lba, LbaLe::from_u64(16383));
+    assert_eq!(secondary.alternate_lba, LbaLe::from_u64(1));
+    assert_eq!(secondary.partition_entry_lba, LbaLe::from_u64(16351));
+    assert_eq!(secondary.last_usable_lba, LbaLe::from_u64(16350));
+    assert_eq!(secondary.calculate_header_crc32(), secondary.header_crc32);
+
+    // The partition is unchanged.
+    let table = disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    assert_eq!(
+        *table.get_partition_entry(0).unwrap(),
+        create_partition_entry()
+    );
+
+    let mut findings = Vec::new();
+    disk.verify_gpt_with(&mut block_buf, &mut array_buf, |f| findings.push(f))
+        .unwrap();
+    assert_eq!(findings, []);
+    disk.flush().unwrap();
+    drop(disk);
+
+    // The old secondary header is erased, and the protective MBR covers
+    // the whole disk.
+    assert!(contents[8191 * 512..8192 * 512].iter().all(|b| *b == 0));
+    let mbr = MasterBootRecord::protective_mbr(16384);
+    assert_eq!(&contents[..512], bytemuck::bytes_of(&mbr));
+}
+
+#[test]
+fn test_relocate_backup_to_end_reserved_blocks() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 16];
+
+    // The array is only 16 blocks, but 32 blocks are still reserved
+    // before the secondary header.
+    let mut contents = load_test_disk_with_reserved_blocks();
+    contents.resize(8 * 1024 * 1024, 0);
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let primary = disk
+        .relocate_backup_to_end(&mut block_buf, &mut array_buf)
+        .unwrap();
+    assert_eq!(primary.last_usable_lba, LbaLe::from_u64(16350));
+    let secondary = disk.read_secondary_gpt_header(&mut block_buf).unwrap();
+    assert_eq!(secondary.partition_entry_lba, LbaLe::from_u64(16367));
+    disk.flush().unwrap();
+    drop(disk);
+    check_gpt_valid(&mut contents, bs);
+}
+
+#[test]
+fn test_relocate_backup_to_end_too_small() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+
+    // Shrink the disk so that the partition no longer fits.
+    let mut contents = load_test_disk();
+    contents.truncate(2 * 1024 * 1024);
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    assert!(matches!(
+        disk.relocate_backup_to_end(&mut block_buf, &mut array_buf),
+        Err(DiskError::PartitionOutsideDisk { index: 0 })
+    ));
+}
+
+#[test]
+fn test_shrink_gpt() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+
+    let mut contents = load_test_disk();
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+
+    // The partition ends at LBA 4096, followed by the secondary array
+    // and header.
+    let num_blocks = disk
+        .shrink_gpt(None, &mut block_buf, &mut array_buf)
+        .unwrap();
+    assert_eq!(num_blocks, 4096 + 1 + 32 + 1);
+    disk.flush().unwrap();
+    drop(disk);
+
+    contents.truncate(usize::try_from(num_blocks).unwrap() * 512);
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let primary = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    assert_eq!(primary.alternate_lba, LbaLe::from_u64(4129));
+    assert_eq!(primary.last_usable_lba, LbaLe::from_u64(4096));
+
+    let mut findings = Vec::new();
+    disk.verify_gpt_with(&mut block_buf, &mut array_buf, |f| findings.push(f))
+        .unwrap();
+    assert_eq!(findings, []);
+}
+
+#[test]
+fn test_shrink_gpt_reserved_blocks() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 16];
+
+    let mut contents = load_test_disk_with_reserved_blocks();
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+
+    // The partition ends at LBA 4096, followed by 32 reserved blocks
+    // and the secondary header.
+    let num_blocks = disk
+        .shrink_gpt(None, &mut block_buf, &mut array_buf)
+        .unwrap();
+    assert_eq!(num_blocks, 4096 + 1 + 32 + 1);
+    disk.flush().unwrap();
+    drop(disk);
+
+    contents.truncate(usize::try_from(num_blocks).unwrap() * 512);
+    check_gpt_valid(&mut contents, bs);
+}
+
+#[test]
+fn test_shrink_gpt_explicit_size() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+
+    let mut contents = load_test_disk();
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+
+    // Too small for the partition.
+    assert!(matches!(
+        disk.shrink_gpt(Some(4100), &mut block_buf, &mut array_buf),
+        Err(DiskError::PartitionOutsideDisk { index: 0 })
+    ));
+    // Larger than the disk.
+    let err = disk
+        .shrink_gpt(Some(10000), &mut block_buf, &mut array_buf)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        DiskError::ShrinkTargetTooLarge { num_blocks: 10000 }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "cannot shrink disk to 10000 blocks, which is larger than its \
+         current size"
+    );
+
+    assert_eq!(
+        disk.shrink_gpt(Some(6000), &mut block_buf, &mut array_buf)
+            .unwrap(),
+        6000
+    );
+    let primary = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    assert_eq!(primary.alternate_lba, LbaLe::from_u64(5999));
+    assert_eq!(primary.last_usable_lba, LbaLe::from_u64(5966));
+    disk.flush().unwrap();
+    drop(disk);
+
+    let mbr = MasterBootRecord::protective_mbr(6000);
+    assert_eq!(&contents[..512], bytemuck::bytes_of(&mbr));
+}
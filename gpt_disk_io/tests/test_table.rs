@@ -0,0 +1,431 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{
+    check_gpt_valid, create_partition_entry, create_primary_header,
+    create_secondary_header, load_test_disk, set_entries,
+};
+use core::num::NonZeroU64;
+use gpt_disk_io::{BlockIoAdapter, Disk, DiskError, GptBuilder, GptTableError};
+use gpt_disk_types::{
+    guid, BlockSize, GptPartitionAttributes, GptPartitionEntry,
+    GptPartitionType, Lba, LbaLe,
+};
+
+fn new_entry(start: u64, end: u64) -> GptPartitionEntry {
+    GptPartitionEntry {
+        partition_type_guid: GptPartitionType::BASIC_DATA,
+        starting_lba: LbaLe::from_u64(start),
+        ending_lba: LbaLe::from_u64(end),
+        ..create_partition_entry()
+    }
+}
+
+#[test]
+fn test_table_commit_matches_sgdisk() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+
+    let mut contents = vec![0; 4 * 1024 * 1024];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    disk.format_gpt(
+        &GptBuilder::new(guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870")),
+        &mut block_buf,
+    )
+    .unwrap();
+
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    assert_eq!(table.add_partition(create_partition_entry()), Ok(0));
+    assert_eq!(table.primary_header(), create_primary_header());
+    assert_eq!(table.secondary_header(bs), Some(create_secondary_header()));
+    table.commit(&mut disk, &mut block_buf).unwrap();
+    disk.flush().unwrap();
+    drop(disk);
+
+    assert_eq!(contents, load_test_disk());
+}
+
+#[test]
+fn test_table_modify() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+
+    let mut contents = load_test_disk();
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    assert_eq!(table.num_entries(), 128);
+    assert_eq!(table.usable_range().unwrap().start(), Lba(34));
+    assert_eq!(table.usable_range().unwrap().end(), Lba(8158));
+
+    // Overlapping partitions are rejected.
+    assert_eq!(
+        table.add_partition(new_entry(4000, 5000)),
+        Err(GptTableError::Overlap { index: 0 })
+    );
+    assert_eq!(
+        table.add_partition(new_entry(1000, 2048)),
+        Err(GptTableError::Overlap { index: 0 })
+    );
+
+    // Partitions outside the usable range are rejected.
+    assert_eq!(
+        table.add_partition(new_entry(33, 100)),
+        Err(GptTableError::OutsideUsableRange)
+    );
+    assert_eq!(
+        table.add_partition(new_entry(8000, 8159)),
+        Err(GptTableError::OutsideUsableRange)
+    );
+
+    // Invalid entries are rejected.
+    assert_eq!(
+        table.add_partition(new_entry(200, 100)),
+        Err(GptTableError::InvalidRange)
+    );
+    assert_eq!(
+        table.add_partition(GptPartitionEntry::default()),
+        Err(GptTableError::UnusedPartitionType)
+    );
+
+    // Valid partitions fill the first unused entries.
+    assert_eq!(table.add_partition(new_entry(34, 2047)), Ok(1));
+    assert_eq!(table.add_partition(new_entry(4097, 8158)), Ok(2));
+
+    // Resize.
+    assert_eq!(
+        table.resize_partition(1, Lba(2048)),
+        Err(GptTableError::Overlap { index: 0 })
+    );
+    assert_eq!(
+        table.resize_partition(1, Lba(33)),
+        Err(GptTableError::InvalidRange)
+    );
+    table.resize_partition(1, Lba(1000)).unwrap();
+    assert_eq!(
+        table.get_partition_entry(1).unwrap().ending_lba.to_u64(),
+        1000
+    );
+
+    // Modify fields.
+    table.set_name(1, "renamed".parse().unwrap()).unwrap();
+    table.set_type(1, GptPartitionType::EFI_SYSTEM).unwrap();
+    assert_eq!(
+        table.set_type(1, GptPartitionType::UNUSED),
+        Err(GptTableError::UnusedPartitionType)
+    );
+    let mut attributes = GptPartitionAttributes::default();
+    attributes.update_required_partition(true);
+    table.set_attributes(1, attributes).unwrap();
+
+    // Unused and out-of-range entries.
+    assert_eq!(
+        table.set_name(3, "x".parse().unwrap()),
+        Err(GptTableError::EntryNotUsed)
+    );
+    assert_eq!(
+        table.remove_partition(128),
+        Err(GptTableError::IndexOutOfRange)
+    );
+
+    // Remove.
+    assert_eq!(table.remove_partition(2), Ok(new_entry(4097, 8158)));
+    assert_eq!(table.remove_partition(2), Err(GptTableError::EntryNotUsed));
+
+    table.commit(&mut disk, &mut block_buf).unwrap();
+
+    // Read the changes back from both copies of the table.
+    let table = disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    let entry = *table.get_partition_entry(1).unwrap();
+    assert_eq!(entry.name, "renamed".parse().unwrap());
+    assert_eq!({ entry.partition_type_guid }, GptPartitionType::EFI_SYSTEM);
+    assert!(entry.attributes.required_partition());
+    assert!(!table.get_partition_entry(2).unwrap().is_used());
+
+    let secondary = disk.read_secondary_gpt_header(&mut block_buf).unwrap();
+    assert_eq!(Some(secondary), table.secondary_header(bs));
+    let layout = secondary.get_partition_entry_array_layout().unwrap();
+    let mut secondary_array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+    let secondary_array = disk
+        .read_gpt_partition_entry_array(layout, &mut secondary_array_buf)
+        .unwrap();
+    assert_eq!(secondary_array.storage(), table.entry_array().storage());
+}
+
+#[test]
+fn test_table_full() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap()];
+
+    let mut contents = vec![0; 1024 * 1024];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let builder =
+        GptBuilder::new(guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870"))
+            .num_partition_entries(2);
+    disk.format_gpt(&builder, &mut block_buf).unwrap();
+
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    table.add_partition(new_entry(100, 199)).unwrap();
+    table.add_partition(new_entry(200, 299)).unwrap();
+    assert_eq!(
+        table.add_partition(new_entry(300, 399)),
+        Err(GptTableError::NoUnusedEntry)
+    );
+}
+
+#[test]
+fn test_table_invalid() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+
+    // Corrupt the partition entry array.
+    let mut contents = load_test_disk();
+    contents[0x400] ^= 1;
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    assert!(matches!(
+        disk.read_gpt_table(&mut block_buf, &mut array_buf),
+        Err(DiskError::InvalidPartitionEntryArray)
+    ));
+
+    // Corrupt the primary header.
+    let mut contents = load_test_disk();
+    contents[0x230] ^= 1;
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    assert!(matches!(
+        disk.read_gpt_table(&mut block_buf, &mut array_buf),
+        Err(DiskError::InvalidGptHeader)
+    ));
+
+    // Storage too small.
+    let mut contents = load_test_disk();
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    assert!(matches!(
+        disk.read_gpt_table(&mut block_buf, &mut array_buf[..512]),
+        Err(DiskError::BufferTooSmall)
+    ));
+}
+
+#[test]
+fn test_table_error_display() {
+    assert_eq!(
+        GptTableError::Overlap { index: 3 }.to_string(),
+        "partition overlaps partition 3"
+    );
+    assert_eq!(
+        GptTableError::OutsideUsableRange.to_string(),
+        "partition is outside the usable range of the disk"
+    );
+    assert_eq!(
+        GptTableError::NotRepresentable { index: 1 }.to_string(),
+        "partition 1 is not aligned to the new block size"
+    );
+}
+
+#[test]
+fn test_table_grow_partition_to_fill() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+
+    let mut contents = load_test_disk();
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    assert_eq!(table.add_partition(new_entry(34, 100)), Ok(1));
+
+    // Grow up to the next partition.
+    assert_eq!(table.grow_partition_to_fill(1, None), Ok(Lba(2047)));
+
+    // Grow up to the last usable LBA.
+    assert_eq!(table.grow_partition_to_fill(0, None), Ok(Lba(8158)));
+    table.resize_partition(0, Lba(4096)).unwrap();
+
+    // Grow with 1MiB alignment.
+    let alignment = NonZeroU64::new(2048);
+    assert_eq!(table.grow_partition_to_fill(0, alignment), Ok(Lba(6143)));
+
+    // Alignment never shrinks the partition.
+    table.resize_partition(0, Lba(8000)).unwrap();
+    assert_eq!(table.grow_partition_to_fill(0, alignment), Ok(Lba(8000)));
+
+    assert_eq!(
+        table.grow_partition_to_fill(2, None),
+        Err(GptTableError::EntryNotUsed)
+    );
+
+    // The change is written to disk by commit.
+    table.commit(&mut disk, &mut block_buf).unwrap();
+    let table = disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    assert_eq!(
+        table.get_partition_entry(0).unwrap().ending_lba.to_u64(),
+        8000
+    );
+}
+
+#[test]
+fn test_table_grow_partition_to_fill_overlap() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * 32];
+
+    // Partitions that start before or inside partition 0 are both
+    // overlaps.
+    for other in [new_entry(1000, 2048), new_entry(3000, 5000)] {
+        let mut contents = load_test_disk();
+        set_entries(&mut contents, &[create_partition_entry(), other]);
+        let mut disk =
+            Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs))
+                .unwrap();
+        let mut table =
+            disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+        assert_eq!(
+            table.grow_partition_to_fill(0, None),
+            Err(GptTableError::Overlap { index: 1 })
+        );
+        assert_eq!(
+            table.get_partition_entry(0).unwrap().ending_lba.to_u64(),
+            4096
+        );
+    }
+}
+
+/// Read the table with `old_bs`, convert it, and write it back with
+/// `new_bs`.
+fn convert_block_size(
+    contents: &mut [u8],
+    old_bs: BlockSize,
+    new_bs: BlockSize,
+) -> Vec<GptPartitionEntry> {
+    let mut block_buf = vec![0u8; 4096];
+    let mut array_buf = vec![0u8; 16384];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(&mut *contents, old_bs)).unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    drop(disk);
+    table.convert_block_size(old_bs, new_bs).unwrap();
+
+    let mut disk = Disk::new(BlockIoAdapter::new(contents, new_bs)).unwrap();
+    disk.write_protective_mbr(&mut block_buf).unwrap();
+    table.commit(&mut disk, &mut block_buf).unwrap();
+    (0..2)
+        .map(|i| *table.get_partition_entry(i).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_table_convert_block_size() {
+    let bs512 = BlockSize::BS_512;
+    let bs4096 = BlockSize::BS_4096;
+    let mut block_buf = vec![0u8; 512];
+    let mut array_buf = vec![0u8; 16384];
+
+    let mut contents = vec![0; 4 * 1024 * 1024];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs512)).unwrap();
+    disk.format_gpt(
+        &GptBuilder::new(guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870")),
+        &mut block_buf,
+    )
+    .unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    table.add_partition(new_entry(2048, 4095)).unwrap();
+    table
+        .add_partition(GptPartitionEntry {
+            unique_partition_guid: guid!(
+                "8bc4c2ed-3a8e-4a8f-8f59-9d1b7c40e1e1"
+            ),
+            ..new_entry(4096, 6143)
+        })
+        .unwrap();
+    table.commit(&mut disk, &mut block_buf).unwrap();
+    drop(disk);
+
+    // 512 to 4096.
+    let entries = convert_block_size(&mut contents, bs512, bs4096);
+    assert_eq!(entries[0].lba_range(), new_entry(256, 511).lba_range());
+    assert_eq!(entries[1].lba_range(), new_entry(512, 767).lba_range());
+    check_gpt_valid(&mut contents, bs4096);
+    let mut block_buf = vec![0u8; 4096];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs4096))
+            .unwrap();
+    let header = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    drop(disk);
+    assert_eq!(header.alternate_lba, LbaLe::from_u64(1023));
+    assert_eq!(header.partition_entry_lba, LbaLe::from_u64(2));
+    assert_eq!(header.first_usable_lba, LbaLe::from_u64(6));
+    assert_eq!(header.last_usable_lba, LbaLe::from_u64(1018));
+
+    // And back again.
+    let entries = convert_block_size(&mut contents, bs4096, bs512);
+    assert_eq!(entries[0].lba_range(), new_entry(2048, 4095).lba_range());
+    assert_eq!(entries[1].lba_range(), new_entry(4096, 6143).lba_range());
+    check_gpt_valid(&mut contents, bs512);
+}
+
+#[test]
+fn test_table_convert_block_size_invalid() {
+    let bs512 = BlockSize::BS_512;
+    let bs4096 = BlockSize::BS_4096;
+    let mut block_buf = vec![0u8; 512];
+    let mut array_buf = vec![0u8; 16384];
+
+    // The test partition ends at LBA 4096, which is not at the end of
+    // a 4096-byte block.
+    let mut contents = load_test_disk();
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs512)).unwrap();
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    assert_eq!(
+        table.convert_block_size(bs512, bs4096),
+        Err(GptTableError::NotRepresentable { index: 0 })
+    );
+    assert_eq!(table.primary_header(), create_primary_header());
+
+    // A partition entry array smaller than one 4096-byte block.
+    let mut contents = vec![0; 4 * 1024 * 1024];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs512)).unwrap();
+    disk.format_gpt(
+        &GptBuilder::new(guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870"))
+            .num_partition_entries(4),
+        &mut block_buf,
+    )
+    .unwrap();
+    let mut table = disk
+        .read_gpt_table(&mut block_buf, &mut array_buf[..512])
+        .unwrap();
+    assert_eq!(
+        table.convert_block_size(bs512, bs4096),
+        Err(GptTableError::BufferTooSmall)
+    );
+
+    // With a larger storage buffer the array can be padded to the new
+    // block size.
+    let mut table =
+        disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    table.convert_block_size(bs512, bs4096).unwrap();
+}
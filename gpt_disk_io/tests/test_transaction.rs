@@ -0,0 +1,254 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{
+    check_gpt_valid, create_second_partition_entry,
+    load_test_disk_with_reserved_blocks, RecordingBlockIo,
+};
+use gpt_disk_io::{
+    BlockIo, BlockIoAdapter, Disk, DiskError, GptCommitPhase,
+    GptCommitRecovery, SliceBlockIoError,
+};
+use gpt_disk_types::{BlockSize, Lba};
+
+const BS: usize = 512;
+
+/// Reserved block between the primary partition entry array and the
+/// first usable LBA of the test disk.
+const INTENT_LBA: Lba = Lba(20);
+
+/// Block IO that stops writing after a fixed number of writes, as if
+/// power was lost.
+struct PowerLossBlockIo<'a> {
+    inner: BlockIoAdapter<&'a mut [u8]>,
+    writes_left: usize,
+}
+
+impl BlockIo for PowerLossBlockIo<'_> {
+    type Error = SliceBlockIoError;
+
+    fn block_size(&self) -> BlockSize {
+        self.inner.block_size()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        self.inner.num_blocks()
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.inner.read_blocks(start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        if self.writes_left == 0 {
+            return Err(SliceBlockIoError::ReadOnly);
+        }
+        self.writes_left -= 1;
+        self.inner.write_blocks(start_lba, src)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+/// Add a partition to the GPT on `io` with `commit_transactional`.
+fn add_partition<Io: BlockIo>(
+    io: Io,
+    intent_lba: Option<Lba>,
+) -> Result<(), DiskError<Io::Error>> {
+    let mut block_buf = vec![0; BS];
+    let mut array_buf = vec![0; BS * 32];
+    let mut disk = Disk::new(io)?;
+    let mut table = disk.read_gpt_table(&mut block_buf, &mut array_buf)?;
+    table
+        .add_partition(create_second_partition_entry())
+        .unwrap();
+    table.commit_transactional(&mut disk, intent_lba, &mut block_buf)
+}
+
+/// Check that the GPT on `contents` is consistent, and return whether
+/// the new partition is present.
+fn check_gpt(contents: &mut [u8]) -> bool {
+    let mut block_buf = vec![0; BS];
+    let mut array_buf = vec![0; BS * 32];
+    check_gpt_valid(contents, BlockSize::BS_512);
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents, BlockSize::BS_512)).unwrap();
+    let table = disk.read_gpt_table(&mut block_buf, &mut array_buf).unwrap();
+    assert!(table.get_partition_entry(0).unwrap().is_used());
+    table.get_partition_entry(1).unwrap().is_used()
+}
+
+#[test]
+fn test_commit_transactional_order() {
+    let mut contents = load_test_disk_with_reserved_blocks();
+    let mut io = RecordingBlockIo::new(&mut contents);
+    add_partition(&mut io, Some(INTENT_LBA)).unwrap();
+    assert_eq!(
+        io.writes,
+        [
+            (20, 1),
+            (8175, 16),
+            (8191, 1),
+            (20, 1),
+            (2, 16),
+            (1, 1),
+            (20, 1),
+        ]
+    );
+    assert!(io.flushes >= 5);
+    drop(io);
+    assert!(contents[20 * BS..21 * BS].iter().all(|b| *b == 0));
+    assert!(check_gpt(&mut contents));
+
+    // Without an intent record, only the GPT is written.
+    let mut contents = load_test_disk_with_reserved_blocks();
+    let mut io = RecordingBlockIo::new(&mut contents);
+    add_partition(&mut io, None).unwrap();
+    assert_eq!(io.writes, [(8175, 16), (8191, 1), (2, 16), (1, 1)]);
+    assert!(io.flushes >= 2);
+    drop(io);
+    assert!(check_gpt(&mut contents));
+}
+
+#[test]
+fn test_commit_transactional_invalid_intent_lba() {
+    // The MBR, the headers, the arrays, the usable range, and blocks
+    // outside the disk.
+    for lba in [0, 1, 2, 17, 34, 40, 8158, 8175, 8190, 8191, 8192] {
+        let mut contents = load_test_disk_with_reserved_blocks();
+        let io =
+            BlockIoAdapter::new(contents.as_mut_slice(), BlockSize::BS_512);
+        assert!(
+            matches!(
+                add_partition(io, Some(Lba(lba))),
+                Err(DiskError::InvalidIntentLba)
+            ),
+            "lba={lba}"
+        );
+        assert_eq!(contents, load_test_disk_with_reserved_blocks());
+    }
+    assert_eq!(
+        DiskError::<SliceBlockIoError>::InvalidIntentLba.to_string(),
+        "intent record block is outside the disk, overlaps the GPT, or is \
+         in the usable range"
+    );
+}
+
+#[test]
+fn test_commit_transactional_intent_lba_in_partition() {
+    // Inside the existing partition, and inside the partition being
+    // added.
+    for lba in [3000, 5500] {
+        let mut contents = load_test_disk_with_reserved_blocks();
+        let io =
+            BlockIoAdapter::new(contents.as_mut_slice(), BlockSize::BS_512);
+        assert!(
+            matches!(
+                add_partition(io, Some(Lba(lba))),
+                Err(DiskError::InvalidIntentLba)
+            ),
+            "lba={lba}"
+        );
+        assert_eq!(contents, load_test_disk_with_reserved_blocks());
+    }
+
+    // Any reserved block can be used.
+    for lba in [18, 33, 8159, 8174] {
+        let mut contents = load_test_disk_with_reserved_blocks();
+        let io =
+            BlockIoAdapter::new(contents.as_mut_slice(), BlockSize::BS_512);
+        add_partition(io, Some(Lba(lba))).unwrap();
+        assert!(check_gpt(&mut contents));
+    }
+}
+
+#[test]
+fn test_recover_gpt_commit() {
+    let mut block_buf = vec![0; BS];
+    let mut array_buf = vec![0; BS * 32];
+
+    // Interrupt the commit after each write, then recover. The GPT is
+    // always consistent afterwards, and the new partition is present
+    // once the secondary copy has been completely written.
+    for num_writes in 0..7 {
+        let mut contents = load_test_disk_with_reserved_blocks();
+        let io = PowerLossBlockIo {
+            inner: BlockIoAdapter::new(&mut contents, BlockSize::BS_512),
+            writes_left: num_writes,
+        };
+        assert!(add_partition(io, Some(INTENT_LBA)).is_err());
+
+        let mut disk = Disk::new(BlockIoAdapter::new(
+            contents.as_mut_slice(),
+            BlockSize::BS_512,
+        ))
+        .unwrap();
+        let intent = disk
+            .read_gpt_commit_intent(INTENT_LBA, &mut block_buf)
+            .unwrap();
+        let recovery = disk
+            .recover_gpt_commit(INTENT_LBA, &mut block_buf, &mut array_buf)
+            .unwrap();
+        assert!(disk
+            .read_gpt_commit_intent(INTENT_LBA, &mut block_buf)
+            .unwrap()
+            .is_none());
+        drop(disk);
+
+        let (phase, expected_recovery, expected_new) = match num_writes {
+            0 => (None, GptCommitRecovery::NotNeeded, false),
+            1..=3 => (
+                Some(GptCommitPhase::Started),
+                GptCommitRecovery::RolledBack,
+                false,
+            ),
+            _ => (
+                Some(GptCommitPhase::SecondaryWritten),
+                GptCommitRecovery::Completed,
+                true,
+            ),
+        };
+        assert_eq!(intent.map(|i| i.phase), phase, "num_writes={num_writes}");
+        assert_eq!(recovery, expected_recovery, "num_writes={num_writes}");
+        assert_eq!(check_gpt(&mut contents), expected_new);
+        if !expected_new {
+            assert_eq!(contents, load_test_disk_with_reserved_blocks());
+        }
+    }
+}
+
+#[test]
+fn test_recover_gpt_commit_not_needed() {
+    let mut block_buf = vec![0; BS];
+    let mut array_buf = vec![0; BS * 32];
+    let mut contents = load_test_disk_with_reserved_blocks();
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+    assert_eq!(
+        disk.recover_gpt_commit(INTENT_LBA, &mut block_buf, &mut array_buf)
+            .unwrap(),
+        GptCommitRecovery::NotNeeded
+    );
+    drop(disk);
+    assert_eq!(contents, load_test_disk_with_reserved_blocks());
+}
@@ -0,0 +1,276 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{
+    create_partition_entry, load_test_disk, set_entries, RecordingBlockIo,
+};
+use gpt_disk_io::{
+    BlockIoAdapter, Disk, GptHeaderCopy, GptHeaderField, GptVerifyFinding,
+    GptVerifyIssue, VerifySeverity,
+};
+use gpt_disk_types::{guid, BlockSize, GptPartitionEntry, LbaLe};
+
+#[cfg(feature = "alloc")]
+use gpt_disk_types::U32Le;
+
+fn verify_with_buffers(
+    contents: &mut [u8],
+    num_buf_blocks: usize,
+    num_array_blocks: usize,
+) -> Vec<GptVerifyFinding> {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap() * num_buf_blocks];
+    let mut array_buf = vec![0u8; bs.to_usize().unwrap() * num_array_blocks];
+    let mut disk = Disk::new(BlockIoAdapter::new(contents, bs)).unwrap();
+
+    let mut findings = Vec::new();
+    disk.verify_gpt_with(&mut block_buf, &mut array_buf, |finding| {
+        findings.push(finding);
+    })
+    .unwrap();
+    findings
+}
+
+fn verify(contents: &mut [u8]) -> Vec<GptVerifyFinding> {
+    let findings = verify_with_buffers(contents, 1, 32);
+
+    // Buffers large enough to read both copies at once give the same
+    // result.
+    assert_eq!(verify_with_buffers(contents, 2, 64), findings);
+    findings
+}
+
+fn error(issue: GptVerifyIssue) -> GptVerifyFinding {
+    GptVerifyFinding {
+        severity: VerifySeverity::Error,
+        issue,
+    }
+}
+
+fn invalid_field(
+    header: GptHeaderCopy,
+    field: GptHeaderField,
+) -> GptVerifyFinding {
+    error(GptVerifyIssue::InvalidHeaderField { header, field })
+}
+
+#[test]
+fn test_verify_valid() {
+    let mut contents = load_test_disk();
+    assert_eq!(verify(&mut contents), []);
+}
+
+#[test]
+fn test_verify_header_corruption() {
+    // Change the primary header's last usable LBA without updating
+    // the checksum.
+    let mut contents = load_test_disk();
+    contents[0x230] ^= 1;
+    assert_eq!(
+        verify(&mut contents),
+        [
+            invalid_field(GptHeaderCopy::Primary, GptHeaderField::HeaderCrc32),
+            error(GptVerifyIssue::HeaderFieldMismatch {
+                field: GptHeaderField::LastUsableLba
+            }),
+        ]
+    );
+
+    // Destroy the secondary header's signature.
+    let mut contents = load_test_disk();
+    let len = contents.len();
+    contents[len - 512] = 0;
+    assert_eq!(
+        verify(&mut contents),
+        [invalid_field(
+            GptHeaderCopy::Secondary,
+            GptHeaderField::Signature
+        )]
+    );
+}
+
+#[test]
+fn test_verify_vectored_reads() {
+    let mut block_buf = vec![0u8; 512 * 2];
+    let mut array_buf = vec![0u8; 512 * 64];
+    let mut contents = load_test_disk();
+    let mut io = RecordingBlockIo::new(&mut contents);
+    let mut disk = Disk::new(&mut io).unwrap();
+    disk.verify_gpt_with(&mut block_buf, &mut array_buf, |_| {})
+        .unwrap();
+    drop(disk);
+
+    // Both headers, then both arrays, are read with one call each.
+    assert_eq!(
+        io.vectored_reads,
+        [vec![(1, 1), (8191, 1)], vec![(2, 32), (8159, 32)]]
+    );
+
+    // With smaller buffers, each copy is read separately.
+    io.vectored_reads.clear();
+    let mut disk = Disk::new(&mut io).unwrap();
+    disk.verify_gpt_with(
+        &mut block_buf[..512],
+        &mut array_buf[..512 * 32],
+        |_| {},
+    )
+    .unwrap();
+    drop(disk);
+    assert!(io.vectored_reads.is_empty());
+}
+
+#[test]
+fn test_verify_array_corruption() {
+    let mut contents = load_test_disk();
+    contents[0x400] ^= 1;
+    assert_eq!(
+        verify(&mut contents),
+        [invalid_field(
+            GptHeaderCopy::Primary,
+            GptHeaderField::PartitionEntryArrayCrc32
+        )]
+    );
+}
+
+#[test]
+fn test_verify_entries() {
+    let entry = create_partition_entry();
+    let mut contents = load_test_disk();
+    set_entries(
+        &mut contents,
+        &[
+            entry,
+            // Overlaps entry 0 and has the same unique GUID.
+            GptPartitionEntry {
+                starting_lba: LbaLe::from_u64(4096),
+                ending_lba: LbaLe::from_u64(5000),
+                ..entry
+            },
+            // Ends before it starts.
+            GptPartitionEntry {
+                unique_partition_guid: guid!(
+                    "00000000-0000-0000-0000-000000000002"
+                ),
+                starting_lba: LbaLe::from_u64(6000),
+                ending_lba: LbaLe::from_u64(5999),
+                ..entry
+            },
+            // Extends into the secondary partition entry array.
+            GptPartitionEntry {
+                unique_partition_guid: guid!(
+                    "00000000-0000-0000-0000-000000000003"
+                ),
+                starting_lba: LbaLe::from_u64(8000),
+                ending_lba: LbaLe::from_u64(8159),
+                ..entry
+            },
+        ],
+    );
+    assert_eq!(
+        verify(&mut contents),
+        [
+            error(GptVerifyIssue::EntryOverlap {
+                index: 0,
+                other_index: 1
+            }),
+            error(GptVerifyIssue::DuplicateUniquePartitionGuid {
+                index: 0,
+                other_index: 1
+            }),
+            error(GptVerifyIssue::InvalidEntryRange { index: 2 }),
+            error(GptVerifyIssue::EntryOutsideUsableRange { index: 3 }),
+        ]
+    );
+}
+
+#[test]
+fn test_verify_mbr() {
+    let mut contents = load_test_disk();
+    contents[..512].fill(0);
+    assert_eq!(
+        verify(&mut contents),
+        [
+            error(GptVerifyIssue::InvalidMbrSignature),
+            error(GptVerifyIssue::MissingProtectivePartition),
+        ]
+    );
+
+    // Add a second partition, as in a hybrid MBR.
+    let mut contents = load_test_disk();
+    contents[446 + 16 + 4] = 0x0c;
+    assert_eq!(
+        verify(&mut contents),
+        [GptVerifyFinding {
+            severity: VerifySeverity::Warning,
+            issue: GptVerifyIssue::ExtraMbrPartition { index: 1 },
+        }]
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_verify_report() {
+    let bs = BlockSize::BS_512;
+    let mut contents = load_test_disk();
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let report = disk.verify_gpt().unwrap();
+    assert!(report.is_valid());
+    assert_eq!(report.findings(), []);
+    drop(disk);
+
+    contents[0x400] ^= 1;
+    contents[446 + 16 + 4] = 0x0c;
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let report = disk.verify_gpt().unwrap();
+    assert!(!report.is_valid());
+    assert_eq!(report.findings().len(), 2);
+    assert_eq!(report.errors().count(), 1);
+    assert_eq!(
+        report.to_string(),
+        "warning: MBR partition 1: not a protective partition\n\
+         error: primary header: invalid partition_entry_array_crc32\n"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_verify_report_huge_array() {
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut contents = load_test_disk();
+
+    // Claim a 2 MiB primary partition entry array. It fits on the
+    // disk, but is too large to be read.
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(contents.as_mut_slice(), bs)).unwrap();
+    let mut primary = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    primary.number_of_partition_entries = U32Le::from_u32(16384);
+    primary.update_header_crc32();
+    disk.write_primary_gpt_header(&primary, &mut block_buf)
+        .unwrap();
+    drop(disk);
+
+    let mut io = RecordingBlockIo::new(&mut contents);
+    let report = Disk::new(&mut io).unwrap().verify_gpt().unwrap();
+    assert!(report.findings().contains(&invalid_field(
+        GptHeaderCopy::Primary,
+        GptHeaderField::NumberOfPartitionEntries
+    )));
+    assert!(!report.is_valid());
+
+    // Only the secondary array was read.
+    assert!(io.reads.iter().all(|(_, len)| *len <= 32));
+    assert!(io
+        .vectored_reads
+        .iter()
+        .all(|requests| requests == &[(1, 1), (8191, 1)]));
+}
@@ -26,6 +26,61 @@ impl Lba {
     pub fn to_u64(self) -> u64 {
         self.0
     }
+
+    /// Check if the byte offset of this LBA is a multiple of
+    /// `alignment` bytes. An `alignment` of zero means no alignment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::{BlockSize, Lba};
+    ///
+    /// let bs = BlockSize::BS_512;
+    /// assert!(Lba(2048).is_aligned(bs, 1024 * 1024));
+    /// assert!(!Lba(63).is_aligned(bs, 4096));
+    /// ```
+    #[must_use]
+    pub fn is_aligned(self, block_size: BlockSize, alignment: u64) -> bool {
+        self.0 % alignment_in_blocks(block_size, alignment) == 0
+    }
+
+    /// Round up to the nearest LBA whose byte offset is a multiple of
+    /// `alignment` bytes. An `alignment` of zero means no alignment.
+    /// Returns `None` if overflow occurs.
+    #[must_use]
+    pub fn align_up(
+        self,
+        block_size: BlockSize,
+        alignment: u64,
+    ) -> Option<Lba> {
+        let step = alignment_in_blocks(block_size, alignment);
+        Some(Lba(self.0.checked_add(step - 1)? / step * step))
+    }
+
+    /// Round down to the nearest LBA whose byte offset is a multiple of
+    /// `alignment` bytes. An `alignment` of zero means no alignment.
+    #[must_use]
+    pub fn align_down(self, block_size: BlockSize, alignment: u64) -> Lba {
+        let step = alignment_in_blocks(block_size, alignment);
+        Lba(self.0 / step * step)
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Get the distance in blocks between consecutive LBAs whose byte
+/// offsets are multiples of `alignment` bytes. Always at least one.
+fn alignment_in_blocks(block_size: BlockSize, alignment: u64) -> u64 {
+    if alignment == 0 {
+        1
+    } else {
+        alignment / gcd(alignment, block_size.to_u64())
+    }
 }
 
 impl PartialEq<u64> for Lba {
@@ -216,6 +271,28 @@ impl LbaRangeInclusive {
         // Add one here since the range is inclusive.
         self.end().to_u64() - self.start.to_u64() + 1
     }
+
+    /// Get the largest range within this range that starts at a byte
+    /// offset that is a multiple of `alignment` bytes, and ends
+    /// immediately before such an offset. Returns `None` if there is no
+    /// such range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::{BlockSize, Lba, LbaRangeInclusive};
+    ///
+    /// let r = LbaRangeInclusive::new(Lba(34), Lba(8158)).unwrap();
+    /// let aligned = r.align(BlockSize::BS_512, 1024 * 1024).unwrap();
+    /// assert_eq!(aligned, LbaRangeInclusive::new(Lba(2048), Lba(6143)).unwrap());
+    /// ```
+    #[must_use]
+    pub fn align(self, block_size: BlockSize, alignment: u64) -> Option<Self> {
+        let start = self.start.align_up(block_size, alignment)?;
+        let end_exclusive =
+            Lba(self.end.0.checked_add(1)?).align_down(block_size, alignment);
+        Self::new(start, Lba(end_exclusive.0.checked_sub(1)?))
+    }
 }
 
 impl Display for LbaRangeInclusive {
@@ -224,6 +301,156 @@ impl Display for LbaRangeInclusive {
     }
 }
 
+/// Proposes aligned locations for new or moved partitions.
+///
+/// The planner is created with the usable range of the disk, and each
+/// call takes the ranges of the partitions that are already in use.
+/// Partitions are placed at the lowest LBA in the usable range that
+/// does not overlap a used range and whose byte offset is a multiple
+/// of the alignment, as checked by [`Lba::is_aligned`].
+///
+/// # Examples
+///
+/// ```
+/// use gpt_disk_types::{AlignmentPlanner, BlockSize, Lba, LbaRangeInclusive};
+///
+/// let range = |start, end| LbaRangeInclusive::new(Lba(start), Lba(end));
+/// let planner = AlignmentPlanner::new(
+///     BlockSize::BS_512,
+///     1024 * 1024,
+///     range(34, 8158).unwrap(),
+/// );
+/// let used = [range(2048, 4095).unwrap()];
+///
+/// // A new partition is placed after the used range, and its size is
+/// // rounded up so that the next partition can also be aligned.
+/// assert_eq!(planner.plan_new(used, 1000), range(4096, 6143));
+///
+/// // A moved partition keeps its size, and its own range is not
+/// // treated as used.
+/// let current = range(63, 1062).unwrap();
+/// assert_eq!(
+///     planner.plan_move([current, used[0]], current),
+///     range(4096, 5095),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct AlignmentPlanner {
+    block_size: BlockSize,
+    alignment: u64,
+    usable: LbaRangeInclusive,
+}
+
+impl AlignmentPlanner {
+    /// Create a planner for a disk with the given block size and
+    /// usable range. The `alignment` is in bytes; zero means no
+    /// alignment.
+    #[must_use]
+    pub fn new(
+        block_size: BlockSize,
+        alignment: u64,
+        usable: LbaRangeInclusive,
+    ) -> Self {
+        Self {
+            block_size,
+            alignment,
+            usable,
+        }
+    }
+
+    /// Check whether `start..=end` is within the usable range and does
+    /// not overlap any of the `used` ranges.
+    fn is_free(
+        &self,
+        mut used: impl Iterator<Item = LbaRangeInclusive>,
+        start: Lba,
+        end: Lba,
+    ) -> bool {
+        start >= self.usable.start
+            && end <= self.usable.end
+            && used.all(|r| r.end < start || r.start > end)
+    }
+
+    /// Find the lowest aligned range of `num_blocks` blocks that is
+    /// free.
+    fn first_fit(
+        &self,
+        used: &(impl Iterator<Item = LbaRangeInclusive> + Clone),
+        num_blocks: u64,
+    ) -> Option<LbaRangeInclusive> {
+        let last_offset = num_blocks.checked_sub(1)?;
+
+        // The lowest free aligned start is either the first aligned LBA
+        // in the usable range, or the first aligned LBA after a used
+        // range.
+        let candidates = used
+            .clone()
+            .filter_map(|r| r.end.0.checked_add(1))
+            .chain([self.usable.start.0]);
+        candidates
+            .filter_map(|start| {
+                let start =
+                    Lba(start).align_up(self.block_size, self.alignment)?;
+                let end = Lba(start.0.checked_add(last_offset)?);
+                self.is_free(used.clone(), start, end)
+                    .then_some((start, end))
+            })
+            .min()
+            .and_then(|(start, end)| LbaRangeInclusive::new(start, end))
+    }
+
+    /// Propose a range for a new partition of at least `num_blocks`
+    /// blocks that does not overlap any of the `used` ranges.
+    ///
+    /// The partition starts at the lowest free aligned LBA. Its size is
+    /// rounded up so that it ends immediately before an aligned LBA, if
+    /// the larger range is also free; otherwise the exact size is used.
+    /// Returns `None` if `num_blocks` is zero or the partition does not
+    /// fit.
+    #[must_use]
+    pub fn plan_new<I>(
+        &self,
+        used: I,
+        num_blocks: u64,
+    ) -> Option<LbaRangeInclusive>
+    where
+        I: IntoIterator<Item = LbaRangeInclusive>,
+        I::IntoIter: Clone,
+    {
+        let used = used.into_iter();
+        let range = self.first_fit(&used, num_blocks)?;
+        let rounded_end = Lba(range.end.0.checked_add(1)?)
+            .align_up(self.block_size, self.alignment)
+            .and_then(|lba| lba.0.checked_sub(1).map(Lba));
+        match rounded_end {
+            Some(end) if self.is_free(used, range.start, end) => {
+                LbaRangeInclusive::new(range.start, end)
+            }
+            _ => Some(range),
+        }
+    }
+
+    /// Propose a new range for the partition that currently covers
+    /// `current`. The partition keeps its size, and starts at the
+    /// lowest aligned LBA where it does not overlap any of the `used`
+    /// ranges. `used` may include `current`; the partition's own range
+    /// is ignored, so the proposed range may overlap it. Returns `None`
+    /// if the partition does not fit.
+    #[must_use]
+    pub fn plan_move<I>(
+        &self,
+        used: I,
+        current: LbaRangeInclusive,
+    ) -> Option<LbaRangeInclusive>
+    where
+        I: IntoIterator<Item = LbaRangeInclusive>,
+        I::IntoIter: Clone,
+    {
+        let used = used.into_iter().filter(move |r| *r != current);
+        self.first_fit(&used, current.num_blocks())
+    }
+}
+
 /// Size of a block in bytes.
 ///
 /// This type enforces some restrictions on the block size: it must be
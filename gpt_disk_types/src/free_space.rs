@@ -0,0 +1,258 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{BlockSize, GptPartitionEntryArray, Lba, LbaRangeInclusive};
+
+/// Iterator over the unallocated ranges of a partition entry array.
+///
+/// Created by [`GptPartitionEntryArray::free_ranges`].
+#[allow(missing_debug_implementations)]
+pub struct GptFreeRangeIter<'array, 'storage> {
+    array: &'array GptPartitionEntryArray<'storage>,
+    /// Start of the next candidate free range, or `None` if iteration
+    /// is complete.
+    next_lba: Option<u64>,
+    last_usable_lba: u64,
+}
+
+impl Iterator for GptFreeRangeIter<'_, '_> {
+    type Item = LbaRangeInclusive;
+
+    fn next(&mut self) -> Option<LbaRangeInclusive> {
+        loop {
+            let start = self.next_lba?;
+            if start > self.last_usable_lba {
+                self.next_lba = None;
+                return None;
+            }
+
+            // Find the end of the partition containing `start` (if any),
+            // and the start of the first partition after `start`.
+            let mut containing_end = None;
+            let mut next_start = self.last_usable_lba.checked_add(1);
+            for index in 0..self.array.layout().num_entries {
+                // OK to unwrap: the index is within the array.
+                let entry = self.array.get_partition_entry(index).unwrap();
+                if !entry.is_used() {
+                    continue;
+                }
+                let Some(range) = entry.lba_range() else {
+                    continue;
+                };
+                let (entry_start, entry_end) =
+                    (range.start().to_u64(), range.end().to_u64());
+
+                if entry_start <= start && start <= entry_end {
+                    containing_end = containing_end.max(Some(entry_end));
+                } else if entry_start > start {
+                    next_start = Some(
+                        next_start.map_or(entry_start, |s| s.min(entry_start)),
+                    );
+                }
+            }
+
+            if let Some(end) = containing_end {
+                // Skip over the partition and try again.
+                self.next_lba = end.checked_add(1);
+                continue;
+            }
+
+            let end = next_start.map_or(u64::MAX, |s| s - 1);
+            self.next_lba = next_start;
+            return LbaRangeInclusive::new(Lba(start), Lba(end));
+        }
+    }
+}
+
+impl<'storage> GptPartitionEntryArray<'storage> {
+    /// Get an iterator over the ranges of blocks between
+    /// `first_usable_lba` and `last_usable_lba` (inclusive) that are
+    /// not in any used partition. The ranges are yielded in ascending
+    /// order.
+    ///
+    /// Used entries whose ending LBA is less than their starting LBA
+    /// are ignored.
+    #[must_use]
+    pub fn free_ranges(
+        &self,
+        first_usable_lba: Lba,
+        last_usable_lba: Lba,
+    ) -> GptFreeRangeIter<'_, 'storage> {
+        GptFreeRangeIter {
+            array: self,
+            next_lba: Some(first_usable_lba.to_u64()),
+            last_usable_lba: last_usable_lba.to_u64(),
+        }
+    }
+}
+
+/// How [`GptPartitionAllocator`] chooses between free ranges.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum GptAllocPolicy {
+    /// Place the partition at the start of the first free range it
+    /// fits in.
+    #[default]
+    FirstFit,
+
+    /// Place the partition at the start of the smallest free range it
+    /// fits in. If there are multiple such ranges, the first is used.
+    BestFit,
+
+    /// Place the partition at the end of the last free range it fits
+    /// in.
+    LastFit,
+}
+
+/// Finds space for new partitions in a partition entry array.
+///
+/// Partitions are placed in the unallocated ranges returned by
+/// [`GptPartitionEntryArray::free_ranges`], according to a
+/// [`GptAllocPolicy`]. The start of each new partition is placed at a
+/// byte offset that is a multiple of the alignment, which defaults to
+/// [`DEFAULT_ALIGNMENT`] bytes, so every partition it returns passes
+/// [`Lba::is_aligned`].
+///
+/// # Examples
+///
+/// ```
+/// use gpt_disk_types::{
+///     BlockSize, GptAllocPolicy, GptPartitionAllocator,
+///     GptPartitionEntryArray, GptPartitionEntryArrayLayout, Lba,
+///     LbaRangeInclusive,
+/// };
+///
+/// let bs = BlockSize::BS_512;
+/// let layout = GptPartitionEntryArrayLayout {
+///     start_lba: Lba(2),
+///     entry_size: Default::default(),
+///     num_entries: 128,
+/// };
+/// let mut storage = [0; 512 * 32];
+/// let array = GptPartitionEntryArray::new(layout, bs, &mut storage).unwrap();
+///
+/// let allocator = GptPartitionAllocator::new(bs);
+/// assert_eq!(
+///     allocator.allocate(&array, Lba(34), Lba(8158), 1000),
+///     LbaRangeInclusive::new(Lba(2048), Lba(3047)),
+/// );
+///
+/// let allocator = allocator.policy(GptAllocPolicy::LastFit);
+/// assert_eq!(
+///     allocator.allocate(&array, Lba(34), Lba(8158), 1000),
+///     LbaRangeInclusive::new(Lba(6144), Lba(7143)),
+/// );
+/// ```
+///
+/// [`DEFAULT_ALIGNMENT`]: Self::DEFAULT_ALIGNMENT
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct GptPartitionAllocator {
+    block_size: BlockSize,
+    policy: GptAllocPolicy,
+    alignment: u64,
+}
+
+impl GptPartitionAllocator {
+    /// Default alignment in bytes (1 MiB). This is what most
+    /// partitioning tools use, and is a multiple of the physical sector
+    /// size and erase block size of most disks.
+    pub const DEFAULT_ALIGNMENT: u64 = 1024 * 1024;
+
+    /// Create an allocator for a disk with the given block size. The
+    /// policy defaults to [`GptAllocPolicy::FirstFit`], and the
+    /// alignment to [`DEFAULT_ALIGNMENT`].
+    ///
+    /// [`DEFAULT_ALIGNMENT`]: Self::DEFAULT_ALIGNMENT
+    #[must_use]
+    pub fn new(block_size: BlockSize) -> Self {
+        Self {
+            block_size,
+            policy: GptAllocPolicy::default(),
+            alignment: Self::DEFAULT_ALIGNMENT,
+        }
+    }
+
+    /// Set the allocation policy.
+    #[must_use]
+    pub fn policy(mut self, policy: GptAllocPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the alignment in bytes; zero means no alignment. See
+    /// [`Lba::is_aligned`] for how alignments that are not a multiple
+    /// of the block size are handled.
+    #[must_use]
+    pub fn alignment(mut self, alignment: u64) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Find where to place a partition within `range`, or `None` if it
+    /// doesn't fit.
+    fn place_in_range(
+        &self,
+        range: LbaRangeInclusive,
+        num_blocks: u64,
+    ) -> Option<LbaRangeInclusive> {
+        let (range_start, range_end) =
+            (range.start().to_u64(), range.end().to_u64());
+
+        let start = if self.policy == GptAllocPolicy::LastFit {
+            let start = range_end.checked_add(1)?.checked_sub(num_blocks)?;
+            Lba(start).align_down(self.block_size, self.alignment)
+        } else {
+            range.start().align_up(self.block_size, self.alignment)?
+        }
+        .to_u64();
+        if start < range_start {
+            return None;
+        }
+        let end = start.checked_add(num_blocks.checked_sub(1)?)?;
+        if end > range_end {
+            return None;
+        }
+        LbaRangeInclusive::new(Lba(start), Lba(end))
+    }
+
+    /// Find space for a partition of `num_blocks` blocks between
+    /// `first_usable_lba` and `last_usable_lba` (inclusive) that does
+    /// not overlap any used entry in `array`. Returns `None` if there
+    /// is no free range large enough, or if `num_blocks` is zero.
+    #[must_use]
+    pub fn allocate(
+        &self,
+        array: &GptPartitionEntryArray,
+        first_usable_lba: Lba,
+        last_usable_lba: Lba,
+        num_blocks: u64,
+    ) -> Option<LbaRangeInclusive> {
+        let mut candidates = array
+            .free_ranges(first_usable_lba, last_usable_lba)
+            .filter_map(|range| {
+                Some((range, self.place_in_range(range, num_blocks)?))
+            });
+
+        match self.policy {
+            GptAllocPolicy::FirstFit => {
+                candidates.next().map(|(_, placed)| placed)
+            }
+            GptAllocPolicy::BestFit => candidates
+                .fold(None, |best: Option<(u64, LbaRangeInclusive)>, c| {
+                    let size = c.0.num_blocks();
+                    match best {
+                        Some((best_size, _)) if best_size <= size => best,
+                        _ => Some((size, c.1)),
+                    }
+                })
+                .map(|(_, placed)| placed),
+            GptAllocPolicy::LastFit => {
+                candidates.last().map(|(_, placed)| placed)
+            }
+        }
+    }
+}
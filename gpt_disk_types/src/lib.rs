@@ -42,8 +42,9 @@
 //! # Features
 //!
 //! * `bytemuck`: Implements bytemuck's `Pod` and `Zeroable` traits for
-//!    many of the types in this crate. Also enables some methods that
-//!    rely on byte access.
+//!   many of the types in this crate. Also enables some methods that
+//!   rely on byte access, as well as free space enumeration and
+//!   allocation with [`GptPartitionAllocator`].
 //! * `std`: Provides `std::error::Error` implementations for all of the
 //!   error types. Off by default.
 //!
@@ -102,6 +103,8 @@
 
 mod block;
 mod crc32;
+#[cfg(feature = "bytemuck")]
+mod free_space;
 mod header;
 mod mbr;
 mod num;
@@ -115,17 +118,22 @@ pub use crc;
 pub use ucs2;
 pub use uguid::{guid, Guid, GuidFromStrError};
 
-pub use block::{BlockSize, Lba, LbaLe, LbaRangeInclusive};
+pub use block::{AlignmentPlanner, BlockSize, Lba, LbaLe, LbaRangeInclusive};
 pub use crc32::Crc32;
+#[cfg(feature = "bytemuck")]
+pub use free_space::{GptAllocPolicy, GptFreeRangeIter, GptPartitionAllocator};
 pub use header::{GptHeader, GptHeaderRevision, GptHeaderSignature};
-pub use mbr::{Chs, DiskGeometry, MasterBootRecord, MbrPartitionRecord};
+pub use mbr::{
+    Chs, DiskGeometry, MasterBootRecord, MbrKind, MbrPartitionRecord,
+};
 pub use num::{U16Le, U32Le, U64Le};
 pub use partition_array::{
     GptPartitionEntryArray, GptPartitionEntryArrayError,
     GptPartitionEntryArrayLayout,
 };
 pub use partition_entry::{
-    GptPartitionAttributes, GptPartitionEntry, GptPartitionEntrySize,
-    GptPartitionEntrySizeError, GptPartitionName, GptPartitionNameFromStrError,
-    GptPartitionNameSetCharError, GptPartitionType,
+    GptPartitionAlignment, GptPartitionAttributes, GptPartitionEntry,
+    GptPartitionEntrySize, GptPartitionEntrySizeError, GptPartitionName,
+    GptPartitionNameFromStrError, GptPartitionNameSetCharError,
+    GptPartitionType,
 };
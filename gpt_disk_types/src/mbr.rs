@@ -7,7 +7,7 @@
 // except according to those terms.
 
 use crate::num::format_u8_slice_lower_hex_le;
-use crate::{Lba, U32Le};
+use crate::{Lba, LbaRangeInclusive, U32Le};
 use core::fmt::{self, Display, Formatter};
 
 #[cfg(feature = "bytemuck")]
@@ -168,6 +168,54 @@ pub struct MbrPartitionRecord {
     pub size_in_lba: U32Le,
 }
 
+impl MbrPartitionRecord {
+    /// [`boot_indicator`] of a legacy bootable (active) partition.
+    ///
+    /// [`boot_indicator`]: Self::boot_indicator
+    pub const BOOTABLE_BOOT_INDICATOR: u8 = 0x80;
+
+    /// Return true if the record describes a partition, meaning the
+    /// [`os_indicator`] and [`size_in_lba`] are both non-zero.
+    ///
+    /// [`os_indicator`]: Self::os_indicator
+    /// [`size_in_lba`]: Self::size_in_lba
+    #[must_use]
+    pub fn is_used(&self) -> bool {
+        self.os_indicator != 0 && self.size_in_lba.to_u32() != 0
+    }
+
+    /// Return true if the [`os_indicator`] is one of the values used
+    /// for an extended partition (`0x05`, `0x0f`, or `0x85`). An
+    /// extended partition contains a chain of extended boot records
+    /// describing logical partitions.
+    ///
+    /// [`os_indicator`]: Self::os_indicator
+    #[must_use]
+    pub fn is_extended(&self) -> bool {
+        matches!(self.os_indicator, 0x05 | 0x0f | 0x85)
+    }
+
+    /// Get the range of blocks covered by the partition, with
+    /// [`starting_lba`] interpreted relative to `base`. For records in
+    /// the MBR `base` is zero; records in an extended boot record are
+    /// relative to other blocks.
+    ///
+    /// Returns `None` if [`size_in_lba`] is zero or overflow occurs.
+    ///
+    /// [`size_in_lba`]: Self::size_in_lba
+    /// [`starting_lba`]: Self::starting_lba
+    #[must_use]
+    pub fn lba_range(&self, base: Lba) -> Option<LbaRangeInclusive> {
+        let start = base
+            .to_u64()
+            .checked_add(u64::from(self.starting_lba.to_u32()))?;
+        let end = start.checked_add(u64::from(
+            self.size_in_lba.to_u32().checked_sub(1)?,
+        ))?;
+        LbaRangeInclusive::new(Lba(start), Lba(end))
+    }
+}
+
 impl Display for MbrPartitionRecord {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.write_str("MbrPartitionRecord { ")?;
@@ -181,6 +229,35 @@ impl Display for MbrPartitionRecord {
     }
 }
 
+/// Classification of a [`MasterBootRecord`]. See
+/// [`MasterBootRecord::kind`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum MbrKind {
+    /// The MBR contains a protective partition (OS type `0xee`) and no
+    /// other partitions. The disk is partitioned with a GPT.
+    Protective,
+
+    /// The MBR contains a protective partition as well as other
+    /// partitions that mirror partitions in the GPT. This allows
+    /// legacy firmware and operating systems to access those
+    /// partitions.
+    Hybrid,
+
+    /// The MBR does not contain a protective partition. The disk is
+    /// partitioned with a legacy MBR (or not at all).
+    Legacy,
+}
+
+impl Display for MbrKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Protective => f.write_str("protective"),
+            Self::Hybrid => f.write_str("hybrid"),
+            Self::Legacy => f.write_str("legacy"),
+        }
+    }
+}
+
 /// Legacy master boot record.
 ///
 /// See Table 5-1 "Legacy MBR" in the UEFI Specification.
@@ -229,6 +306,10 @@ unsafe impl Pod for MasterBootRecord {}
 unsafe impl Zeroable for MasterBootRecord {}
 
 impl MasterBootRecord {
+    /// OS type of the partition in a protective MBR that covers the
+    /// GPT.
+    pub const PROTECTIVE_OS_TYPE: u8 = 0xee;
+
     /// Return whether the [`boot_strap_code`] field is all zeros or not.
     ///
     /// [`boot_strap_code`]: Self::boot_strap_code
@@ -237,6 +318,37 @@ impl MasterBootRecord {
         self.boot_strap_code.iter().all(|b| *b == 0)
     }
 
+    /// Return true if the [`signature`] field is `0xaa55`.
+    ///
+    /// [`signature`]: Self::signature
+    #[must_use]
+    pub fn is_signature_valid(&self) -> bool {
+        self.signature == [0x55, 0xaa]
+    }
+
+    /// Classify the MBR based on its partition records. Records that
+    /// are not [used] are ignored. Returns `None` if the signature is
+    /// not valid.
+    ///
+    /// [used]: MbrPartitionRecord::is_used
+    #[must_use]
+    pub fn kind(&self) -> Option<MbrKind> {
+        if !self.is_signature_valid() {
+            return None;
+        }
+        let mut used = self.partitions.iter().filter(|p| p.is_used());
+        let has_protective = used
+            .clone()
+            .any(|p| p.os_indicator == Self::PROTECTIVE_OS_TYPE);
+        let has_other =
+            used.any(|p| p.os_indicator != Self::PROTECTIVE_OS_TYPE);
+        Some(match (has_protective, has_other) {
+            (true, false) => MbrKind::Protective,
+            (true, true) => MbrKind::Hybrid,
+            (false, _) => MbrKind::Legacy,
+        })
+    }
+
     /// Create a protective MBR for the given disk size.
     ///
     /// See section 5.2.3 "Protective MBR" of the UEFI Specification.
@@ -253,7 +365,7 @@ impl MasterBootRecord {
                     boot_indicator: 0,
                     // CHS=0,0,2
                     start_chs: Chs([0, 2, 0]),
-                    os_indicator: 0xee,
+                    os_indicator: Self::PROTECTIVE_OS_TYPE,
                     end_chs: Chs::from_lba(
                         Lba(num_blocks - 1),
                         DiskGeometry::UNKNOWN,
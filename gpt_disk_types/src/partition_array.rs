@@ -11,7 +11,7 @@ use core::fmt::{self, Display, Formatter};
 
 #[cfg(feature = "bytemuck")]
 use {
-    crate::GptPartitionEntry,
+    crate::{GptPartitionAlignment, GptPartitionEntry},
     bytemuck::{from_bytes, from_bytes_mut},
     core::mem,
     core::ops::Range,
@@ -150,6 +150,11 @@ impl Display for GptPartitionEntryArrayError {
 pub struct GptPartitionEntryArray<'a> {
     layout: GptPartitionEntryArrayLayout,
     num_bytes_exact: usize,
+    /// Length of the array rounded up to the current block size. This
+    /// is the part of `storage` that is in use.
+    num_bytes_rounded: usize,
+    /// The whole storage buffer, which may be longer than
+    /// `num_bytes_rounded`.
     storage: &'a mut [u8],
 }
 
@@ -172,27 +177,36 @@ impl<'a> GptPartitionEntryArray<'a> {
             .num_bytes_exact_as_usize()
             .ok_or(GptPartitionEntryArrayError::Overflow)?;
 
-        let storage = storage
-            .get_mut(..num_bytes_required)
-            .ok_or(GptPartitionEntryArrayError::BufferTooSmall)?;
+        if storage.len() < num_bytes_required {
+            return Err(GptPartitionEntryArrayError::BufferTooSmall);
+        }
 
         Ok(Self {
             layout,
             num_bytes_exact,
+            num_bytes_rounded: num_bytes_required,
             storage,
         })
     }
 
-    /// Get a reference to the storage buffer.
+    /// Get a reference to the storage buffer. The length is
+    /// [`layout.num_bytes_rounded_to_block`] for the current block
+    /// size.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
     #[must_use]
     pub fn storage(&self) -> &[u8] {
-        self.storage
+        &self.storage[..self.num_bytes_rounded]
     }
 
-    /// Get a mutable reference to the storage buffer.
+    /// Get a mutable reference to the storage buffer. The length is
+    /// [`layout.num_bytes_rounded_to_block`] for the current block
+    /// size.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
     #[must_use]
     pub fn storage_mut(&mut self) -> &mut [u8] {
-        self.storage
+        &mut self.storage[..self.num_bytes_rounded]
     }
 
     /// Get the partition entry array layout.
@@ -206,6 +220,37 @@ impl<'a> GptPartitionEntryArray<'a> {
         self.layout.start_lba = start_lba;
     }
 
+    /// Change the block size that the storage is rounded to, so that
+    /// the array can be written to a disk with a different block size.
+    /// The length of [`storage`] becomes
+    /// [`layout.num_bytes_rounded_to_block`] for the new block size,
+    /// and the padding after the last entry is zeroed. The storage
+    /// buffer passed to [`new`] is kept, so the block size can be
+    /// changed back later.
+    ///
+    /// Returns [`GptPartitionEntryArrayError::BufferTooSmall`] if the
+    /// storage buffer is not large enough for the new block size. The
+    /// array is unchanged if an error occurs.
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    /// [`new`]: Self::new
+    /// [`storage`]: Self::storage
+    pub fn set_block_size(
+        &mut self,
+        block_size: BlockSize,
+    ) -> Result<(), GptPartitionEntryArrayError> {
+        let num_bytes_required = self
+            .layout
+            .num_bytes_rounded_to_block_as_usize(block_size)
+            .ok_or(GptPartitionEntryArrayError::Overflow)?;
+        if num_bytes_required > self.storage.len() {
+            return Err(GptPartitionEntryArrayError::BufferTooSmall);
+        }
+        self.storage[self.num_bytes_exact..num_bytes_required].fill(0);
+        self.num_bytes_rounded = num_bytes_required;
+        Ok(())
+    }
+
     #[cfg(feature = "bytemuck")]
     fn get_entry_byte_range(&self, index: u32) -> Option<Range<usize>> {
         if index >= self.layout.num_entries {
@@ -240,6 +285,31 @@ impl<'a> GptPartitionEntryArray<'a> {
         Some(from_bytes_mut(&mut self.storage[range]))
     }
 
+    /// Check the alignment of each used partition. See
+    /// [`GptPartitionEntry::check_alignment`]. The iterator yields the
+    /// index of each used entry along with its alignment.
+    #[cfg(feature = "bytemuck")]
+    pub fn check_alignment(
+        &self,
+        block_size: BlockSize,
+        physical_sector_size: u64,
+        boundary: u64,
+    ) -> impl Iterator<Item = (u32, GptPartitionAlignment)> + '_ {
+        (0..self.layout.num_entries).filter_map(move |index| {
+            let entry = self.get_partition_entry(index)?;
+            entry.is_used().then(|| {
+                (
+                    index,
+                    entry.check_alignment(
+                        block_size,
+                        physical_sector_size,
+                        boundary,
+                    ),
+                )
+            })
+        })
+    }
+
     /// Calculate the CRC32 checksum for the partition entry array. The
     /// return value can then be set in the
     /// [`GptHeader::partition_entry_array_crc32`] field.
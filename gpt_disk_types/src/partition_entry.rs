@@ -7,7 +7,8 @@
 // except according to those terms.
 
 use crate::{
-    guid, Guid, GuidFromStrError, LbaLe, LbaRangeInclusive, U16Le, U64Le,
+    guid, BlockSize, Guid, GuidFromStrError, Lba, LbaLe, LbaRangeInclusive,
+    U16Le, U64Le,
 };
 use core::fmt::{self, Display, Formatter};
 use core::num::NonZeroU32;
@@ -54,8 +55,92 @@ impl GptPartitionType {
     pub const CHROME_OS_ROOT_FS: Self =
         Self(guid!("3cb8e202-3b7e-47dd-8a3c-7ff2a13cfcec"));
 
+    /// Linux filesystem data partition.
+    pub const LINUX_FILESYSTEM: Self =
+        Self(guid!("0fc63daf-8483-4772-8e79-3d69d8477de4"));
+
+    /// Linux swap partition.
+    pub const LINUX_SWAP: Self =
+        Self(guid!("0657fd6d-a4ab-43c4-84e5-0933c84b4f4f"));
+
+    /// Linux Logical Volume Manager partition.
+    pub const LINUX_LVM: Self =
+        Self(guid!("e6d6d379-f507-44c2-a23c-238f2a3df928"));
+
+    /// Linux software RAID partition.
+    pub const LINUX_RAID: Self =
+        Self(guid!("a19d880f-05fc-4d3b-a006-743f0f84911e"));
+
+    /// Windows recovery environment partition.
+    pub const WINDOWS_RECOVERY: Self =
+        Self(guid!("de94bba4-06d1-4d40-a16a-bfd50179d6ac"));
+
+    /// FreeBSD disklabel partition.
+    pub const FREEBSD_DISKLABEL: Self =
+        Self(guid!("516e7cb4-6ecf-11d6-8ff8-00022d09712b"));
+
+    /// Apple HFS+ partition.
+    pub const APPLE_HFS_PLUS: Self =
+        Self(guid!("48465300-0000-11aa-aa11-00306543ecac"));
+
     // TODO: there are many more "known" partition types for which we
     // could add constants.
+
+    /// Get the GPT partition type equivalent to a legacy MBR partition
+    /// type (see [`MbrPartitionRecord::os_indicator`]). This is the
+    /// same mapping that `gdisk` uses when converting an MBR disk.
+    ///
+    /// FAT and NTFS types (`0x01`, `0x04`, `0x06`, `0x07`, `0x0b`,
+    /// `0x0c`, and `0x0e`) all map to [`BASIC_DATA`]. Returns `None`
+    /// for unused, extended, and protective (`0xee`) types, and for
+    /// types without a known equivalent.
+    ///
+    /// [`BASIC_DATA`]: Self::BASIC_DATA
+    /// [`MbrPartitionRecord::os_indicator`]: crate::MbrPartitionRecord::os_indicator
+    #[must_use]
+    pub fn from_mbr_os_indicator(os_indicator: u8) -> Option<Self> {
+        match os_indicator {
+            0x01 | 0x04 | 0x06 | 0x07 | 0x0b | 0x0c | 0x0e => {
+                Some(Self::BASIC_DATA)
+            }
+            0x27 => Some(Self::WINDOWS_RECOVERY),
+            0x82 => Some(Self::LINUX_SWAP),
+            0x83 => Some(Self::LINUX_FILESYSTEM),
+            0x8e => Some(Self::LINUX_LVM),
+            0xa5 => Some(Self::FREEBSD_DISKLABEL),
+            0xaf => Some(Self::APPLE_HFS_PLUS),
+            0xef => Some(Self::EFI_SYSTEM),
+            0xfd => Some(Self::LINUX_RAID),
+            _ => None,
+        }
+    }
+
+    /// Get the legacy MBR partition type (see
+    /// [`MbrPartitionRecord::os_indicator`]) equivalent to this GPT
+    /// partition type. This is the reverse of
+    /// [`from_mbr_os_indicator`]; [`BASIC_DATA`] maps to `0x07`.
+    ///
+    /// Returns `None` for types without a known equivalent.
+    ///
+    /// [`BASIC_DATA`]: Self::BASIC_DATA
+    /// [`MbrPartitionRecord::os_indicator`]: crate::MbrPartitionRecord::os_indicator
+    /// [`from_mbr_os_indicator`]: Self::from_mbr_os_indicator
+    #[must_use]
+    pub fn to_mbr_os_indicator(self) -> Option<u8> {
+        let os_indicator = match self {
+            Self::BASIC_DATA => 0x07,
+            Self::WINDOWS_RECOVERY => 0x27,
+            Self::LINUX_SWAP => 0x82,
+            Self::LINUX_FILESYSTEM => 0x83,
+            Self::LINUX_LVM => 0x8e,
+            Self::FREEBSD_DISKLABEL => 0xa5,
+            Self::APPLE_HFS_PLUS => 0xaf,
+            Self::EFI_SYSTEM => 0xef,
+            Self::LINUX_RAID => 0xfd,
+            _ => return None,
+        };
+        Some(os_indicator)
+    }
 }
 
 impl Display for GptPartitionType {
@@ -448,6 +533,60 @@ impl GptPartitionEntry {
         let partition_type_guid = self.partition_type_guid;
         partition_type_guid != GptPartitionType::UNUSED
     }
+
+    /// Check the alignment of the partition's starting LBA on a disk
+    /// with the given logical `block_size`. The `physical_sector_size`
+    /// and caller-supplied `boundary` are in bytes.
+    #[must_use]
+    pub fn check_alignment(
+        &self,
+        block_size: BlockSize,
+        physical_sector_size: u64,
+        boundary: u64,
+    ) -> GptPartitionAlignment {
+        let start = Lba::from(self.starting_lba);
+        GptPartitionAlignment {
+            physical_sector: start.is_aligned(block_size, physical_sector_size),
+            one_mib: start.is_aligned(block_size, 1024 * 1024),
+            boundary: start.is_aligned(block_size, boundary),
+        }
+    }
+}
+
+/// Alignment of a partition's starting LBA. See
+/// [`GptPartitionEntry::check_alignment`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct GptPartitionAlignment {
+    /// The start is aligned to the physical sector size. Partitions
+    /// that are not aligned to the physical sector size are slow to
+    /// write on 512e disks, where each 4KiB physical sector holds
+    /// multiple 512-byte logical blocks.
+    pub physical_sector: bool,
+
+    /// The start is aligned to 1MiB, as created by most partitioning
+    /// tools. This is a multiple of the erase block size of most SSDs.
+    pub one_mib: bool,
+
+    /// The start is aligned to the caller-supplied boundary.
+    pub boundary: bool,
+}
+
+impl GptPartitionAlignment {
+    /// True if all the alignment checks passed.
+    #[must_use]
+    pub fn is_fully_aligned(&self) -> bool {
+        self.physical_sector && self.one_mib && self.boundary
+    }
+}
+
+impl Display for GptPartitionAlignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "physical_sector: {}, one_mib: {}, boundary: {}",
+            self.physical_sector, self.one_mib, self.boundary
+        )
+    }
 }
 
 impl Display for GptPartitionEntry {